@@ -14,28 +14,77 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::{
+     f64::consts::PI,
+     io::Write,
+     path::{Path, PathBuf},
+     sync::mpsc::Receiver,
+     time::Instant
+};
+
 use anyhow::Context;
-use sdl2::{event::Event, mouse::MouseButton, pixels::Color};
+use sdl2::{
+     event::{Event, WindowEvent},
+     mouse::MouseWheelDirection,
+     pixels::Color
+};
 
 use crate::{
-     common::vec2::Vec2,
+     common::{
+          units::{Dimension, UnitSystem},
+          vec2::{Vec2, Vec2F},
+          vector::Vector
+     },
+     controls::{Action, Bindings, Input},
      renderer,
      renderer::{
-          graphics::{self, Graphics},
+          graphics::{
+               self,
+               trails::{self, Trails},
+               Graphics
+          },
+          headless,
+          target::{RenderTarget, WindowTarget},
+          video::{self, VideoSettings, VideoWriter},
           viewport::Viewport
      },
-     simulation::{object::Object, thread}
+     simulation::{
+          object::{self, Object, ObjectId},
+          save,
+          thread::Snapshot
+     }
 };
 
-/// Create all the threads for the simulation and launch the window
-pub fn run(
-     objects: Vec<Object>,
-     delta_t: f64,
-     force_smoothings: f64,
-     substep: u32,
-     graphics: Graphics,
+const SNAPSHOT_PATH: &str = "snapshot.gxo";
+
+/// Start of the files the sessions are recorded to, numbered so a recording
+/// doesn't overwrite the previous ones, a new frame being added for each
+/// snapshot
+const RECORDING_PREFIX: &str = "recording_";
+
+/// Rotation of the camera for each press on a rotation key, in radians
+const ROTATION_STEP: f64 = PI / 36.0;
+
+/// Move of the view for each press on a pan key, in logical pixels
+const PAN_STEP: f64 = 20.0;
+
+/// Number of snapshots the trails go back
+const TRAIL_LENGTH: usize = 64;
+
+/// Launch the window, drawing the snapshots sent by the engine thread through
+/// `receiver`, the values being displayed and saved in `units`, the recordings
+/// encoded with `video_settings` and the keys mapped by `bindings`. The
+/// viewport is the one of a screen of `window_size` logical pixels.
+#[allow(clippy::too_many_arguments)]
+pub fn run<V: Vector<f64>>(
+     objects: Vec<Object<f64, V>>,
+     receiver: Receiver<Snapshot<V>>,
+     units: UnitSystem,
+     mut graphics: Graphics,
      mut viewport: Viewport,
-     window_size: Vec2<u32>
+     window_size: Vec2<u32>,
+     video_settings: VideoSettings,
+     bindings: Bindings
 ) -> anyhow::Result<()> {
      // -------------------------------------------------------------------------
      // Window creation
@@ -47,58 +96,290 @@ pub fn run(
           .with_context(|| "Couldn't initialize SDL modules.")?;
 
      // create the window
-     let mut canvas =
-          renderer::window::create(video, window_size.x, window_size.y, "GalaxyX", true)
+     let canvas =
+          renderer::window::create(video, window_size.x, window_size.y, "GalaxyX", true, true)
                .map_err(|e| anyhow::anyhow!(e))
                .with_context(|| "Couldn't create the window.")?;
+     let creator = canvas.texture_creator();
+     let mut target = WindowTarget::new(canvas, &creator);
+
+     // the drawings are made on the physical pixels, the mouse giving logical
+     // ones, more than one of the first per one of the second on high DPI
+     let (mut size, mut dpi) = renderer::window::drawable_size(&target.canvas);
+     viewport.resize(window_size, size);
+     viewport.pixel_ratio = dpi;
+
+     let mut objects_to_draw = objects;
+
+     // the values of the color mapping only change with the snapshots
+     let mut mapped_values = graphics.mapped_values(&objects_to_draw);
+
+     let mut selected: Option<ObjectId> = None;
+     let mut focused: Option<ObjectId> = None;
+
+     let mut recording: Option<(VideoWriter<_>, Vec2<u32>, PathBuf)> = None;
 
-     // ------------------------------------------------------------------------
-     // Thread launch
-     // ------------------------------------------------------------------------
+     let mut trails = Trails::new(TRAIL_LENGTH);
+     let mut show_trails = false;
 
-     let mut objects_to_draw = objects.clone();
-     let receiver = thread::launch_engine_thread(objects, force_smoothings, delta_t, substep);
+     // the engine stops once a few snapshots wait to be drawn
+     let mut paused = false;
+     let mut step = false;
+
+     // the mouse zooms around the cursor, and moves the view while dragging
+     let mut cursor = center(size);
+     let mut dragging = false;
+
+     let mut last_frame = Instant::now();
 
      // -------------------------------------------------------------------------
      // Window loop
      // -------------------------------------------------------------------------
 
-     'win_loop: loop {
+     let result = 'win_loop: loop {
           // handle events if any
           for event in event_pump.poll_iter() {
-               match event {
+               // the mouse location for the actions it triggers, and the
+               // number of times they are done
+               let (action, pointer, steps) = match event {
                     // window close, since there is only one
-                    Event::Quit { .. } => break 'win_loop,
+                    Event::Quit { .. } => break 'win_loop Ok(()),
+
+                    // the region seen stays the same, even on a display with
+                    // another DPI, whose change comes with a move as these
+                    // bindings of SDL don't know the display change event
+                    Event::Window {
+                         win_event: WindowEvent::SizeChanged(..) | WindowEvent::Moved(..),
+                         ..
+                    } => {
+                         let (new_size, new_dpi) = renderer::window::drawable_size(&target.canvas);
+
+                         // the last size is kept while minimized
+                         if new_size.x > 0 && new_size.y > 0 && (new_size, new_dpi) != (size, dpi) {
+                              viewport.resize(size, new_size);
+                              viewport.pixel_ratio = new_dpi;
+                              (size, dpi) = (new_size, new_dpi);
+                         }
+
+                         continue;
+                    },
 
                     // -------------------------------------------------------------
-                    // Viewport controls
+                    // Mouse
                     // -------------------------------------------------------------
-                    Event::MouseWheel { y, .. } => {
-                         let center = window_size / 2;
+                    Event::MouseWheel { y, direction, .. } => {
+                         let steps = match direction {
+                              MouseWheelDirection::Flipped => -y,
+                              _ => y
+                         };
+                         let input = match steps.signum() {
+                              1 => Input::WheelUp,
+                              -1 => Input::WheelDown,
+                              _ => continue
+                         };
 
-                         viewport.zoom(y as f64, center.convert_as_to_type());
+                         match bindings.action(input) {
+                              Some(action) => (action, Some(cursor), steps.unsigned_abs()),
+                              None => continue
+                         }
                     },
 
                     Event::MouseMotion {
-                         xrel,
-                         yrel,
-                         mousestate,
-                         ..
+                         x, y, xrel, yrel, ..
+                    } => {
+                         if dragging {
+                              viewport.pan(Vec2F::new(xrel.into(), yrel.into()) * dpi);
+                              focused = None;
+                         } else {
+                              cursor = Vec2F::new(x.into(), y.into()) * dpi;
+                         }
+
+                         continue;
+                    },
+
+                    Event::MouseButtonDown {
+                         mouse_btn, x, y, ..
                     } => {
-                         if mousestate.left() {
-                              mouse.set_relative_mouse_mode(true);
+                         cursor = Vec2F::new(x.into(), y.into()) * dpi;
 
-                              viewport.move_(xrel, yrel);
+                         match bindings.action(Input::Button(mouse_btn)) {
+                              Some(action) => (action, Some(cursor), 1),
+                              None => continue
                          }
                     },
 
+                    // the drag lasts as long as its button or key is held
                     Event::MouseButtonUp { mouse_btn, .. } => {
-                         if mouse_btn == MouseButton::Left {
+                         if bindings.action(Input::Button(mouse_btn)) == Some(Action::Drag) {
+                              dragging = false;
                               mouse.set_relative_mouse_mode(false);
                          }
+
+                         continue;
+                    },
+
+                    // -------------------------------------------------------------
+                    // Keyboard
+                    // -------------------------------------------------------------
+                    Event::KeyDown {
+                         keycode: Some(key),
+                         repeat,
+                         ..
+                    } => match bindings.action(key) {
+                         Some(action) if !repeat || action.repeats() => (action, None, 1),
+                         _ => continue
+                    },
+
+                    Event::KeyUp {
+                         keycode: Some(key), ..
+                    } => {
+                         if bindings.action(key) == Some(Action::Drag) {
+                              dragging = false;
+                              mouse.set_relative_mouse_mode(false);
+                         }
+
+                         continue;
+                    },
+
+                    _ => continue
+               };
+
+               match action {
+                    // -------------------------------------------------------------
+                    // Viewport controls
+                    // -------------------------------------------------------------
+                    Action::PanLeft | Action::PanRight | Action::PanUp | Action::PanDown => {
+                         // the view moves, the drawings going the other way
+                         let direction = match action {
+                              Action::PanLeft => Vec2F::new(1.0, 0.0),
+                              Action::PanRight => Vec2F::new(-1.0, 0.0),
+                              Action::PanUp => Vec2F::new(0.0, 1.0),
+                              _ => Vec2F::new(0.0, -1.0)
+                         };
+                         viewport.pan(direction * (PAN_STEP * dpi));
+                         focused = None;
+                    },
+                    Action::Drag => {
+                         dragging = true;
+                         mouse.set_relative_mouse_mode(true);
+                    },
+                    Action::ZoomIn => viewport.zoom(steps as f64, pointer.unwrap_or(center(size))),
+                    Action::ZoomOut => {
+                         viewport.zoom(-(steps as f64), pointer.unwrap_or(center(size)))
+                    },
+                    Action::ResetView => {
+                         viewport.reset();
+                         focused = None;
+                    },
+                    Action::ZoomToFit => {
+                         viewport.fit(&objects_to_draw, size);
+                         focused = None;
+                    },
+                    Action::RotateLeft => viewport.rotate(-ROTATION_STEP, 0.0),
+                    Action::RotateRight => viewport.rotate(ROTATION_STEP, 0.0),
+                    Action::RotateUp => viewport.rotate(0.0, -ROTATION_STEP),
+                    Action::RotateDown => viewport.rotate(0.0, ROTATION_STEP),
+
+                    // -------------------------------------------------------------
+                    // Selection and focus
+                    // -------------------------------------------------------------
+                    Action::Select => {
+                         let location = viewport.screen_to_world(cursor);
+                         let projected = viewport.project_all(&objects_to_draw);
+
+                         if let Some(o) = object::find_nearest(&projected, location) {
+                              if let Some(o) = object::find_by_id(&objects_to_draw, o.id) {
+                                   println!(
+                                        "Selected {} (mass {}, speed {}).",
+                                        o.label(),
+                                        units.format(o.mass, Dimension::Mass),
+                                        units.format(
+                                             o.velocity.dot(o.velocity).sqrt(),
+                                             Dimension::Velocity
+                                        )
+                                   );
+                              }
+
+                              selected = Some(o.id);
+                         }
+                    },
+                    Action::Focus => {
+                         focused = if focused.is_none() { selected } else { None };
+                    },
+
+                    // -------------------------------------------------------------
+                    // Display
+                    // -------------------------------------------------------------
+                    Action::ToggleVelocity => {
+                         let shown = graphics.toggle_velocity();
+                         println!("Velocities {}.", if shown { "shown" } else { "hidden" });
+                    },
+                    Action::ToggleForce => {
+                         let shown = graphics.toggle_force();
+                         println!("Forces {}.", if shown { "shown" } else { "hidden" });
+                    },
+                    Action::ToggleTrails => {
+                         show_trails = !show_trails;
+                         trails.clear();
+                    },
+                    Action::ToggleFullscreen => {
+                         if let Err(e) = renderer::window::toggle_fullscreen(&mut target.canvas) {
+                              println!("Couldn't switch the fullscreen: {}", e);
+                         }
+                    },
+
+                    // -------------------------------------------------------------
+                    // Time
+                    // -------------------------------------------------------------
+                    Action::Pause => {
+                         paused = !paused;
+                         println!("{}.", if paused { "Paused" } else { "Resumed" });
+                    },
+                    Action::Step => step = true,
+
+                    // -------------------------------------------------------------
+                    // Snapshot
+                    // -------------------------------------------------------------
+                    Action::Snapshot => {
+                         if V::DIMENSIONS != 2 {
+                              println!("Snapshots can only be saved in the plane.");
+
+                              continue;
+                         }
+
+                         let objects = objects_to_draw
+                              .iter()
+                              .map(|o| o.map_vectors(|v| Vec2::new(v.to_vec3().x, v.to_vec3().y)))
+                              .collect::<Vec<_>>();
+
+                         match save::save_objects(Path::new(SNAPSHOT_PATH), &objects, &units) {
+                              Ok(()) => println!("Snapshot saved to {}.", SNAPSHOT_PATH),
+                              Err(e) => println!("Couldn't save the snapshot: {:#}", e)
+                         }
+                    },
+
+                    // -------------------------------------------------------------
+                    // Recording
+                    // -------------------------------------------------------------
+                    // the size of the window when it starts
+                    Action::Record => match recording.take() {
+                         Some((writer, _, path)) => finish_recording(writer, &path),
+                         None => {
+                              let path = next_recording_path();
+
+                              match video::create(&path, size, video_settings) {
+                                   Ok(writer) => {
+                                        println!("Recording to {}.", path.display());
+
+                                        recording = Some((writer, size, path));
+                                   },
+                                   Err(e) => println!("Couldn't start the recording: {:#}", e)
+                              }
+                         }
                     },
 
-                    _ => ()
+                    Action::Help => print!("{}", bindings),
+                    Action::Quit => break 'win_loop Ok(())
                }
           }
 
@@ -106,25 +387,113 @@ pub fn run(
           // Physics computation
           // ---------------------------------------------------------------------
 
-          if let Ok(new_data) = receiver.try_recv() {
-               objects_to_draw = new_data;
+          let snapshot = if paused && !step {
+               None
+          } else {
+               receiver.try_recv().ok()
+          };
+
+          if let Some(snapshot) = snapshot {
+               objects_to_draw = snapshot.objects;
+               mapped_values = graphics.mapped_values(&objects_to_draw);
+               step = false;
+
+               for event in snapshot.events.iter() {
+                    println!("{}", event.display(&units));
+               }
+
+               if show_trails {
+                    trails.record(&objects_to_draw);
+               }
+
+               if let Some((writer, recording_size, _)) = &mut recording {
+                    let mut viewport = viewport.clone();
+                    viewport.resize(size, *recording_size);
+
+                    let frame = headless::render_frame(
+                         &objects_to_draw,
+                         mapped_values.as_deref(),
+                         &graphics,
+                         &viewport,
+                         *recording_size,
+                         1
+                    )
+                    .and_then(|image| writer.write_frame(&image));
+
+                    if let Err(e) = frame {
+                         println!("Couldn't record the frame: {:#}", e);
+
+                         recording = None;
+                    }
+               }
+          }
+
+          let now = Instant::now();
+          viewport.update((now - last_frame).as_secs_f64());
+          last_frame = now;
+
+          // follow the focused object, as long as it exists
+          if let Some(id) = focused {
+               match object::find_by_id(&objects_to_draw, id) {
+                    Some(o) => {
+                         let (_, projected) = viewport.project(o);
+
+                         viewport.focus(projected.location, center(size));
+                    },
+                    None => focused = None
+               }
           }
 
           // ---------------------------------------------------------------------
           // Rendering
           // ---------------------------------------------------------------------
 
-          canvas.set_draw_color(Color::BLACK);
-          canvas.clear();
+          target.clear(Color::BLACK);
 
-          for o in objects_to_draw.iter() {
-               graphics::draw_object(&mut canvas, o, &graphics, &viewport)
+          if show_trails {
+               let drawn = trails::draw_trails(&mut target, &trails, &objects_to_draw, &viewport);
+               if let Err(e) = drawn {
+                    break 'win_loop Err(anyhow::anyhow!(e).context("Couldn't draw the trails."));
+               }
+          }
+          let drawn = graphics::draw_objects(
+               &mut target,
+               &objects_to_draw,
+               mapped_values.as_deref(),
+               &graphics,
+               &viewport
+          );
+          if let Err(e) = drawn {
+               break 'win_loop Err(anyhow::anyhow!(e).context("Couldn't draw the objects."));
           }
 
-          canvas.present();
+          target.canvas.present();
 
           // compute the time passed during the physics computation and display
+     };
+
+     if let Some((writer, _, path)) = recording {
+          finish_recording(writer, &path);
      }
 
-     Ok(())
+     result
+}
+
+/// Center of a screen of the given size
+fn center(size: Vec2<u32>) -> Vec2F {
+     size.convert_as_to_type::<f64>() / 2.0
+}
+
+/// First numbered recording file not existing yet
+fn next_recording_path() -> PathBuf {
+     (1..).map(|n| PathBuf::from(format!("{}{:03}.gif", RECORDING_PREFIX, n)))
+          .find(|path| !path.exists())
+          .unwrap()
+}
+
+fn finish_recording<W: Write>(writer: VideoWriter<W>, path: &Path) {
+     match writer.finish() {
+          Ok(_) => println!("Recording saved to {}.", path.display()),
+          Err(e) => println!("Couldn't save the recording: {:#}", e)
+     }
 }
@@ -17,10 +17,12 @@
 #[cfg(test)]
 mod test;
 
-use super::vec2::Vec2F;
+use num_traits::Float;
+
+use super::vec2::{Vec2, Vec2F};
 
 /// Compute the distance between p1 and p2
-pub fn compute_distance(p1: Vec2F, p2: Vec2F) -> f64 {
+pub fn compute_distance<T: Float>(p1: Vec2<T>, p2: Vec2<T>) -> T {
      ((p1.x - p2.x).powi(2) + (p1.y - p2.y).powi(2)).sqrt()
 }
 
@@ -31,3 +33,22 @@ pub fn compute_angle(origin: Vec2F, a: Vec2F) -> f64 {
 
      vec.y.atan2(vec.x) - ref_vec.y.atan2(ref_vec.x)
 }
+
+/// Compute the error function, with an absolute error below 1.5e-7
+/// (Abramowitz and Stegun, 7.1.26)
+pub fn erf<T: Float>(x: T) -> T {
+     let k = |value: f64| T::from(value).unwrap();
+
+     let t = T::one() / (T::one() + k(0.3275911) * x.abs());
+     let polynomial = t
+          * (k(0.254829592)
+               + t * (k(-0.284496736)
+                    + t * (k(1.421413741) + t * (k(-1.453152027) + t * k(1.061405429)))));
+     let y = T::one() - polynomial * (-x * x).exp();
+
+     if x < T::zero() {
+          -y
+     } else {
+          y
+     }
+}
@@ -16,7 +16,7 @@
 
 use std::f64::consts::{FRAC_PI_2, PI};
 
-use super::{compute_angle, compute_distance};
+use super::{compute_angle, compute_distance, erf};
 use crate::common::vec2::Vec2F;
 
 #[test]
@@ -54,3 +54,12 @@ fn test_compute_distance() {
           2.0_f64.sqrt()
      );
 }
+
+#[test]
+fn test_erf() {
+     assert!(erf(0.0_f64).abs() < 1e-7);
+     assert!((erf(0.5_f64) - 0.520_499_877_8).abs() < 1e-6);
+     assert!((erf(1.0_f64) - 0.842_700_792_9).abs() < 1e-6);
+     assert!((erf(-2.0_f64) + 0.995_322_265_0).abs() < 1e-6);
+     assert!((erf(5.0_f64) - 1.0).abs() < 1e-6);
+}
@@ -15,12 +15,19 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 pub mod maths;
+pub mod units;
 pub mod vec2;
+pub mod vec3;
+pub mod vector;
 
 // =============================================================================
 // Constants
 // =============================================================================
 
 pub mod constants {
-     pub const G: f64 = 6.67433 * 10e-11;
+     /// Gravitational constant, in m^3 / (kg s^2)
+     pub const G: f64 = 6.67433e-11;
+
+     /// Speed of light in vacuum, in m / s
+     pub const SPEED_OF_LIGHT: f64 = 299_792_458.0;
 }
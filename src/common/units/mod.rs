@@ -0,0 +1,306 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use std::{
+     fmt::{Debug, Display},
+     str::FromStr
+};
+
+use super::constants::{G, SPEED_OF_LIGHT};
+
+/// Mass of the Sun, in kg
+const SOLAR_MASS: f64 = 1.988_47e30;
+
+/// Astronomical unit, in m
+const ASTRONOMICAL_UNIT: f64 = 1.495_978_707e11;
+
+/// Parsec, in m
+const PARSEC: f64 = 3.085_677_581_491_367e16;
+
+/// Light year, in m
+const LIGHT_YEAR: f64 = 9.460_730_472_580_8e15;
+
+/// Julian year, in s
+const YEAR: f64 = 365.25 * 86400.0;
+
+// =============================================================================
+// Dimension
+// =============================================================================
+
+/// The kind of a physical quantity
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Dimension {
+     Mass,
+     Length,
+     Time,
+     Velocity,
+     Acceleration,
+     Force
+}
+
+impl Dimension {
+     /// Exponents of the mass, the length and the time in the dimension
+     fn exponents(&self) -> (i32, i32, i32) {
+          match self {
+               Dimension::Mass => (1, 0, 0),
+               Dimension::Length => (0, 1, 0),
+               Dimension::Time => (0, 0, 1),
+               Dimension::Velocity => (0, 1, -1),
+               Dimension::Acceleration => (0, 1, -2),
+               Dimension::Force => (1, 1, -2)
+          }
+     }
+}
+
+impl Display for Dimension {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.write_str(match self {
+               Dimension::Mass => "mass",
+               Dimension::Length => "length",
+               Dimension::Time => "time",
+               Dimension::Velocity => "velocity",
+               Dimension::Acceleration => "acceleration",
+               Dimension::Force => "force"
+          })
+     }
+}
+
+/// Units accepted after the values, with their dimension and their value in SI
+const UNITS: &[(&str, Dimension, f64)] = &[
+     ("kg", Dimension::Mass, 1.0),
+     ("g", Dimension::Mass, 1e-3),
+     ("Msun", Dimension::Mass, SOLAR_MASS),
+     ("m", Dimension::Length, 1.0),
+     ("km", Dimension::Length, 1e3),
+     ("AU", Dimension::Length, ASTRONOMICAL_UNIT),
+     ("ly", Dimension::Length, LIGHT_YEAR),
+     ("pc", Dimension::Length, PARSEC),
+     ("kpc", Dimension::Length, 1e3 * PARSEC),
+     ("Mpc", Dimension::Length, 1e6 * PARSEC),
+     ("s", Dimension::Time, 1.0),
+     ("h", Dimension::Time, 3600.0),
+     ("day", Dimension::Time, 86400.0),
+     ("yr", Dimension::Time, YEAR),
+     ("Myr", Dimension::Time, 1e6 * YEAR),
+     ("Gyr", Dimension::Time, 1e9 * YEAR),
+     ("m/s", Dimension::Velocity, 1.0),
+     ("km/s", Dimension::Velocity, 1e3),
+     ("kpc/Myr", Dimension::Velocity, 1e3 * PARSEC / (1e6 * YEAR)),
+     ("m/s^2", Dimension::Acceleration, 1.0),
+     ("N", Dimension::Force, 1.0)
+];
+
+// =============================================================================
+// Unit system
+// =============================================================================
+
+/// The units the values of a simulation are expressed in
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UnitSystem {
+     /// kg, m and s
+     Si,
+
+     /// Solar mass, kpc and Myr
+     Astronomical,
+
+     /// Units of mass and length given in SI, the unit of time making `G = 1`
+     NBody { mass: f64, length: f64 }
+}
+
+impl UnitSystem {
+     /// SI values of the units of mass, length and time
+     fn base(&self) -> (f64, f64, f64) {
+          match *self {
+               UnitSystem::Si => (1.0, 1.0, 1.0),
+               UnitSystem::Astronomical => (SOLAR_MASS, 1e3 * PARSEC, 1e6 * YEAR),
+               UnitSystem::NBody { mass, length } => {
+                    (mass, length, (length.powi(3) / (G * mass)).sqrt())
+               },
+          }
+     }
+
+     /// SI value of the unit of the given dimension
+     pub fn scale(&self, dimension: Dimension) -> f64 {
+          let (m, l, t) = self.base();
+          let (a, b, c) = dimension.exponents();
+
+          m.powi(a) * l.powi(b) * t.powi(c)
+     }
+
+     /// Gravitational constant in the units of the system
+     pub fn gravitational_constant(&self) -> f64 {
+          match self {
+               UnitSystem::NBody { .. } => 1.0,
+               _ => {
+                    let (m, l, t) = self.base();
+
+                    G * m * t * t / l.powi(3)
+               }
+          }
+     }
+
+     /// Speed of light in the units of the system
+     pub fn speed_of_light(&self) -> f64 {
+          self.convert_from_si(SPEED_OF_LIGHT, Dimension::Velocity)
+     }
+
+     /// Convert a value in SI into the system
+     pub fn convert_from_si(&self, value: f64, dimension: Dimension) -> f64 {
+          value / self.scale(dimension)
+     }
+
+     /// Symbol of the unit of the given dimension
+     pub fn symbol(&self, dimension: Dimension) -> &'static str {
+          let symbols = match self {
+               UnitSystem::Si => ["kg", "m", "s", "m/s", "m/s^2", "N"],
+               UnitSystem::Astronomical => [
+                    "Msun",
+                    "kpc",
+                    "Myr",
+                    "kpc/Myr",
+                    "kpc/Myr^2",
+                    "Msun kpc/Myr^2"
+               ],
+               UnitSystem::NBody { .. } => ["M", "L", "T", "L/T", "L/T^2", "M L/T^2"]
+          };
+
+          symbols[dimension as usize]
+     }
+
+     /// Parse a value of the given dimension, followed by its unit if it
+     /// isn't in the units of the system, like `1.5 Msun` or `220 km/s`
+     pub fn parse(&self, value: &str, dimension: Dimension) -> Result<f64, QuantityParseError> {
+          let error = || QuantityParseError {
+               value: value.to_string(),
+               dimension
+          };
+
+          let (number, unit) = match value.trim().split_once(char::is_whitespace) {
+               Some((number, unit)) => (number, Some(unit.trim())),
+               None => (value.trim(), None)
+          };
+          let number = number.parse::<f64>().map_err(|_| error())?;
+
+          match unit {
+               None => Ok(number),
+               Some(unit) if unit == self.symbol(dimension) => Ok(number),
+               Some(unit) => {
+                    let (_, _, si) = UNITS
+                         .iter()
+                         .find(|(symbol, d, _)| *symbol == unit && *d == dimension)
+                         .ok_or_else(error)?;
+
+                    Ok(self.convert_from_si(number * si, dimension))
+               }
+          }
+     }
+
+     /// Format a value of the system with its unit
+     pub fn format(&self, value: f64, dimension: Dimension) -> String {
+          format!("{} {}", value, self.symbol(dimension))
+     }
+}
+
+impl Display for UnitSystem {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          match self {
+               UnitSystem::Si => f.write_str("si"),
+               UnitSystem::Astronomical => f.write_str("astronomical"),
+               UnitSystem::NBody { mass, length } => write!(f, "n_body:{} kg:{} m", mass, length)
+          }
+     }
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+pub struct QuantityParseError {
+     value: String,
+     dimension: Dimension
+}
+
+impl Debug for QuantityParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.debug_struct("QuantityParseError")
+               .field("value", &self.value)
+               .field("dimension", &self.dimension)
+               .finish()
+     }
+}
+
+impl Display for QuantityParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          write!(f, "'{}' is not a valid {}.", self.value, self.dimension)
+     }
+}
+
+impl std::error::Error for QuantityParseError {}
+
+pub struct UnitSystemParseError {
+     value: String
+}
+
+impl Debug for UnitSystemParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.debug_struct("UnitSystemParseError")
+               .field("value", &self.value)
+               .finish()
+     }
+}
+
+impl Display for UnitSystemParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          write!(f, "'{}' is not a valid unit system.", self.value)
+     }
+}
+
+impl std::error::Error for UnitSystemParseError {}
+
+impl FromStr for UnitSystem {
+     type Err = UnitSystemParseError;
+
+     /// Parse `si`, `astronomical` or `n_body:<mass>:<length>`, the units of
+     /// the N-body system being given in SI or followed by their unit
+     fn from_str(s: &str) -> Result<Self, Self::Err> {
+          let error = || UnitSystemParseError {
+               value: s.to_string()
+          };
+
+          match s.split(':').collect::<Vec<_>>().as_slice() {
+               ["si"] => Ok(UnitSystem::Si),
+               ["astronomical"] => Ok(UnitSystem::Astronomical),
+               ["n_body", mass, length] => {
+                    let mass = UnitSystem::Si
+                         .parse(mass, Dimension::Mass)
+                         .map_err(|_| error())?;
+                    let length = UnitSystem::Si
+                         .parse(length, Dimension::Length)
+                         .map_err(|_| error())?;
+
+                    if mass > 0.0 && length > 0.0 {
+                         Ok(UnitSystem::NBody { mass, length })
+                    } else {
+                         Err(error())
+                    }
+               },
+               _ => Err(error())
+          }
+     }
+}
@@ -0,0 +1,112 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::{Dimension, UnitSystem};
+use crate::common::constants::G;
+
+fn assert_close(value: f64, expected: f64, tolerance: f64) {
+     assert!(
+          (value - expected).abs() <= tolerance * expected.abs(),
+          "{} instead of {}",
+          value,
+          expected
+     );
+}
+
+#[test]
+fn test_gravitational_constant() {
+     assert_eq!(UnitSystem::Si.gravitational_constant(), 6.67433e-11);
+
+     // kpc^3 / (Msun Myr^2)
+     assert_close(
+          UnitSystem::Astronomical.gravitational_constant(),
+          4.4985e-12,
+          1e-4
+     );
+
+     let n_body = "n_body:1e10 Msun:1 kpc".parse::<UnitSystem>().unwrap();
+     assert_eq!(n_body.gravitational_constant(), 1.0);
+
+     // the unit of time is the one making G = 1
+     let (m, l, t) = (
+          n_body.scale(Dimension::Mass),
+          n_body.scale(Dimension::Length),
+          n_body.scale(Dimension::Time)
+     );
+     assert_close(G * m * t * t / l.powi(3), 1.0, 1e-12);
+}
+
+#[test]
+fn test_speed_of_light() {
+     assert_eq!(UnitSystem::Si.speed_of_light(), 299_792_458.0);
+
+     // about 0.3 pc per year
+     assert_close(UnitSystem::Astronomical.speed_of_light(), 306.601, 1e-5);
+
+     let n_body = "n_body:1e10 Msun:1 kpc".parse::<UnitSystem>().unwrap();
+     assert_close(
+          n_body.speed_of_light() * n_body.scale(Dimension::Velocity),
+          299_792_458.0,
+          1e-12
+     );
+}
+
+#[test]
+fn test_parse_quantities() {
+     let si = UnitSystem::Si;
+     let astronomical = UnitSystem::Astronomical;
+
+     // values without unit are in the units of the system
+     assert_eq!(si.parse("3", Dimension::Length).unwrap(), 3.0);
+     assert_eq!(astronomical.parse("3 kpc", Dimension::Length).unwrap(), 3.0);
+
+     assert_close(
+          si.parse("1.5 Msun", Dimension::Mass).unwrap(),
+          1.5 * 1.988_47e30,
+          1e-12
+     );
+     assert_close(
+          astronomical.parse("220 km/s", Dimension::Velocity).unwrap(),
+          0.225,
+          1e-3
+     );
+     assert_close(
+          astronomical.parse("2 Gyr", Dimension::Time).unwrap(),
+          2000.0,
+          1e-12
+     );
+
+     assert!(si.parse("3 kg", Dimension::Length).is_err());
+     assert!(si.parse("3 parsecs", Dimension::Length).is_err());
+     assert!(si.parse("three", Dimension::Length).is_err());
+}
+
+#[test]
+fn test_unit_system_round_trip() {
+     for units in [
+          UnitSystem::Si,
+          UnitSystem::Astronomical,
+          UnitSystem::NBody {
+               mass: 2e40,
+               length: 3e19
+          }
+     ] {
+          assert_eq!(units.to_string().parse::<UnitSystem>().unwrap(), units);
+     }
+
+     assert!("imperial".parse::<UnitSystem>().is_err());
+     assert!("n_body:0:1".parse::<UnitSystem>().is_err());
+}
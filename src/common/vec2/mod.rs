@@ -21,7 +21,7 @@ use std::{
      ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign}
 };
 
-use num_traits::{AsPrimitive, Float, Num};
+use num_traits::{AsPrimitive, Float, Num, NumAssign, NumCast, ToPrimitive};
 
 // =============================================================================
 // Traits
@@ -33,6 +33,11 @@ pub trait VecInto<T> {
           T: Num;
 }
 
+/// The floating point types the physics can be run with
+pub trait Real: Float + NumAssign + Debug {}
+
+impl<T> Real for T where T: Float + NumAssign + Debug {}
+
 pub trait VecLength<T> {
      fn length_f64(&self) -> f64
      where
@@ -87,6 +92,7 @@ where
      }
 
      /// Convert to one type to another
+     #[allow(clippy::multiple_bound_locations)]
      pub fn convert_as_to_type<F: 'static>(self) -> Vec2<F>
      where
           F: Num + marker::Copy,
@@ -119,6 +125,23 @@ where
      }
 }
 
+impl<T> Vec2<T>
+where
+     T: Num + ToPrimitive
+{
+     /// Convert to another numeric type, panicking if a value can't be
+     /// represented in it
+     pub fn cast<F>(self) -> Vec2<F>
+     where
+          F: Num + NumCast
+     {
+          Vec2 {
+               x: F::from(self.x).unwrap(),
+               y: F::from(self.y).unwrap()
+          }
+     }
+}
+
 impl<T> Vec2<T>
 where
      T: Float
@@ -0,0 +1,257 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use std::{
+     fmt::Debug,
+     iter::Sum,
+     ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign}
+};
+
+use num_traits::{Num, NumCast, ToPrimitive};
+
+use super::vec2::VecLength;
+
+// =============================================================================
+// Type
+// =============================================================================
+
+/// A group of three values
+#[derive(Clone, Copy)]
+pub struct Vec3<T>
+where
+     T: Num
+{
+     pub x: T,
+     pub y: T,
+     pub z: T
+}
+
+impl<T> Vec3<T>
+where
+     T: Num
+{
+     /// Construct a new Vec3F object
+     pub fn new(x: T, y: T, z: T) -> Self {
+          Self { x, y, z }
+     }
+
+     /// Construct a new null Vec3F object
+     pub fn new_null() -> Self {
+          Self {
+               x: T::zero(),
+               y: T::zero(),
+               z: T::zero()
+          }
+     }
+}
+
+impl<T> Vec3<T>
+where
+     T: Num + Copy
+{
+     /// Dot product of two vectors
+     pub fn dot(self, rhs: Self) -> T {
+          self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+     }
+
+     /// Cross product of two vectors
+     pub fn cross(self, rhs: Self) -> Self {
+          Self {
+               x: self.y * rhs.z - self.z * rhs.y,
+               y: self.z * rhs.x - self.x * rhs.z,
+               z: self.x * rhs.y - self.y * rhs.x
+          }
+     }
+}
+
+impl<T> Vec3<T>
+where
+     T: Num + ToPrimitive
+{
+     /// Convert to another numeric type, panicking if a value can't be
+     /// represented in it
+     #[allow(unused)]
+     pub fn cast<F>(self) -> Vec3<F>
+     where
+          F: Num + NumCast
+     {
+          Vec3 {
+               x: F::from(self.x).unwrap(),
+               y: F::from(self.y).unwrap(),
+               z: F::from(self.z).unwrap()
+          }
+     }
+}
+
+impl<T> VecLength<T> for Vec3<T>
+where
+     T: Into<f64> + Num + Copy
+{
+     fn length_f64(&self) -> f64
+     where
+          T: Into<f64>
+     {
+          (self.x.into().powi(2) + self.y.into().powi(2) + self.z.into().powi(2)).sqrt()
+     }
+}
+
+impl<T> Sub for Vec3<T>
+where
+     T: Num
+{
+     type Output = Self;
+
+     fn sub(self, rhs: Self) -> Self::Output {
+          Self {
+               x: self.x - rhs.x,
+               y: self.y - rhs.y,
+               z: self.z - rhs.z
+          }
+     }
+}
+
+impl<T> SubAssign for Vec3<T>
+where
+     T: Num + SubAssign
+{
+     fn sub_assign(&mut self, rhs: Self) {
+          self.x -= rhs.x;
+          self.y -= rhs.y;
+          self.z -= rhs.z;
+     }
+}
+
+impl<T> Div<T> for Vec3<T>
+where
+     T: Num + Copy
+{
+     type Output = Self;
+
+     fn div(self, rhs: T) -> Self::Output {
+          Self {
+               x: self.x / rhs,
+               y: self.y / rhs,
+               z: self.z / rhs
+          }
+     }
+}
+
+impl<T> Mul<T> for Vec3<T>
+where
+     T: Num + Copy
+{
+     type Output = Self;
+
+     fn mul(self, rhs: T) -> Self::Output {
+          Self {
+               x: self.x * rhs,
+               y: self.y * rhs,
+               z: self.z * rhs
+          }
+     }
+}
+
+impl<T> Add for Vec3<T>
+where
+     T: Num + Copy
+{
+     type Output = Self;
+
+     fn add(self, rhs: Vec3<T>) -> Self::Output {
+          Self {
+               x: self.x + rhs.x,
+               y: self.y + rhs.y,
+               z: self.z + rhs.z
+          }
+     }
+}
+
+impl<T> AddAssign for Vec3<T>
+where
+     T: Num + AddAssign
+{
+     fn add_assign(&mut self, rhs: Self) {
+          self.x += rhs.x;
+          self.y += rhs.y;
+          self.z += rhs.z;
+     }
+}
+
+impl<T> PartialEq for Vec3<T>
+where
+     T: Num
+{
+     fn eq(&self, other: &Self) -> bool {
+          self.x == other.x && self.y == other.y && self.z == other.z
+     }
+}
+
+impl<T> Debug for Vec3<T>
+where
+     T: Num + Debug
+{
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.debug_struct("Vec3F")
+               .field("x", &self.x)
+               .field("y", &self.y)
+               .field("z", &self.z)
+               .finish()
+     }
+}
+
+impl<T> Neg for Vec3<T>
+where
+     T: Num + Neg<Output = T>
+{
+     type Output = Self;
+
+     fn neg(self) -> Self::Output {
+          Self {
+               x: -self.x,
+               y: -self.y,
+               z: -self.z
+          }
+     }
+}
+
+impl<T> Sum for Vec3<T>
+where
+     T: Num
+{
+     fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+          iter.fold(
+               Self {
+                    x: T::zero(),
+                    y: T::zero(),
+                    z: T::zero()
+               },
+               |a, b| Self {
+                    x: a.x + b.x,
+                    y: a.y + b.y,
+                    z: a.z + b.z
+               }
+          )
+     }
+}
+
+// =============================================================================
+// Alias
+// =============================================================================
+
+pub type Vec3F = Vec3<f64>;
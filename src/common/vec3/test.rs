@@ -0,0 +1,46 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use super::Vec3F;
+use crate::common::vec2::VecLength;
+
+#[test]
+fn test_operators() {
+     let a = Vec3F::new(1.0, 2.0, 3.0);
+     let b = Vec3F::new(4.0, -5.0, 6.0);
+
+     assert_eq!(a + b, Vec3F::new(5.0, -3.0, 9.0));
+     assert_eq!(a - b, Vec3F::new(-3.0, 7.0, -3.0));
+     assert_eq!(a * 2.0, Vec3F::new(2.0, 4.0, 6.0));
+     assert_eq!(b / 2.0, Vec3F::new(2.0, -2.5, 3.0));
+     assert_eq!(-a, Vec3F::new(-1.0, -2.0, -3.0));
+     assert_eq!([a, b].into_iter().sum::<Vec3F>(), a + b);
+
+     let mut c = a;
+     c += b;
+     c -= a;
+     assert_eq!(c, b);
+}
+
+#[test]
+fn test_products() {
+     let x = Vec3F::new(1.0, 0.0, 0.0);
+     let y = Vec3F::new(0.0, 1.0, 0.0);
+
+     assert_eq!(x.dot(y), 0.0);
+     assert_eq!(x.cross(y), Vec3F::new(0.0, 0.0, 1.0));
+     assert_eq!(Vec3F::new(2.0, 3.0, 6.0).length_f64(), 7.0);
+}
@@ -0,0 +1,94 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+     fmt::Debug,
+     iter::Sum,
+     ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign}
+};
+
+use super::{
+     vec2::{Real, Vec2},
+     vec3::Vec3
+};
+
+// =============================================================================
+// Trait
+// =============================================================================
+
+/// The vectors of the space the physics is run in, `Vec2` for the plane and
+/// `Vec3` for the space
+pub trait Vector<F: Real>:
+     Copy
+     + Add<Output = Self>
+     + Sub<Output = Self>
+     + Mul<F, Output = Self>
+     + Div<F, Output = Self>
+     + AddAssign
+     + SubAssign
+     + Neg<Output = Self>
+     + PartialEq
+     + Debug
+     + Sum
+{
+     /// Number of coordinates of the vectors
+     const DIMENSIONS: usize;
+
+     /// The null vector
+     fn null() -> Self;
+
+     /// Dot product of two vectors
+     fn dot(self, rhs: Self) -> F;
+
+     /// The vector in space, with a null `z` in the plane
+     fn to_vec3(self) -> Vec3<F>;
+}
+
+// =============================================================================
+// Implementations
+// =============================================================================
+
+impl<F: Real> Vector<F> for Vec2<F> {
+     const DIMENSIONS: usize = 2;
+
+     fn null() -> Self {
+          Vec2::new_null()
+     }
+
+     fn dot(self, rhs: Self) -> F {
+          self.x * rhs.x + self.y * rhs.y
+     }
+
+     fn to_vec3(self) -> Vec3<F> {
+          Vec3::new(self.x, self.y, F::zero())
+     }
+}
+
+impl<F: Real> Vector<F> for Vec3<F> {
+     const DIMENSIONS: usize = 3;
+
+     fn null() -> Self {
+          Vec3::new_null()
+     }
+
+     fn dot(self, rhs: Self) -> F {
+          Vec3::dot(self, rhs)
+     }
+
+     fn to_vec3(self) -> Vec3<F> {
+          self
+     }
+}
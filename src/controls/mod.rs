@@ -0,0 +1,509 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use std::{
+     collections::{HashMap, HashSet},
+     fmt::{Debug, Display},
+     fs,
+     path::Path,
+     str::FromStr
+};
+
+use anyhow::Context;
+use sdl2::{keyboard::Keycode, mouse::MouseButton};
+
+// =============================================================================
+// Actions
+// =============================================================================
+
+/// What the user can do from the keyboard or the mouse
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+     PanLeft,
+     PanRight,
+     PanUp,
+     PanDown,
+
+     /// Move the view with the mouse while held
+     Drag,
+
+     /// Zoom around the mouse when triggered by it, around the center else
+     ZoomIn,
+     ZoomOut,
+
+     /// Go back to the view of the launch
+     ResetView,
+
+     /// Show all the objects
+     ZoomToFit,
+
+     RotateLeft,
+     RotateRight,
+     RotateUp,
+     RotateDown,
+
+     /// Select the object nearest to the mouse
+     Select,
+
+     /// Follow the selected object, or stop following
+     Focus,
+
+     ToggleVelocity,
+     ToggleForce,
+     ToggleTrails,
+     ToggleFullscreen,
+
+     /// Stop drawing the new snapshots, or start again
+     Pause,
+
+     /// Draw the next snapshot while paused
+     Step,
+
+     Snapshot,
+     Record,
+     Help,
+     Quit
+}
+
+/// All the actions, in the order they are listed
+pub const ACTIONS: [Action; 25] = [
+     Action::PanLeft,
+     Action::PanRight,
+     Action::PanUp,
+     Action::PanDown,
+     Action::Drag,
+     Action::ZoomIn,
+     Action::ZoomOut,
+     Action::ResetView,
+     Action::ZoomToFit,
+     Action::RotateLeft,
+     Action::RotateRight,
+     Action::RotateUp,
+     Action::RotateDown,
+     Action::Select,
+     Action::Focus,
+     Action::ToggleVelocity,
+     Action::ToggleForce,
+     Action::ToggleTrails,
+     Action::ToggleFullscreen,
+     Action::Pause,
+     Action::Step,
+     Action::Snapshot,
+     Action::Record,
+     Action::Help,
+     Action::Quit
+];
+
+impl Action {
+     /// Inputs the action is bound to by default
+     fn default_inputs(&self) -> &'static [Input] {
+          match self {
+               Action::PanLeft => &[Input::Key(Keycode::A)],
+               Action::PanRight => &[Input::Key(Keycode::D)],
+               Action::PanUp => &[Input::Key(Keycode::W)],
+               Action::PanDown => &[Input::Key(Keycode::S)],
+               Action::Drag => &[Input::Button(MouseButton::Left)],
+               Action::ZoomIn => &[
+                    Input::Key(Keycode::Equals),
+                    Input::Key(Keycode::KpPlus),
+                    Input::WheelUp
+               ],
+               Action::ZoomOut => &[
+                    Input::Key(Keycode::Minus),
+                    Input::Key(Keycode::KpMinus),
+                    Input::WheelDown
+               ],
+               Action::ResetView => &[Input::Key(Keycode::Home)],
+               Action::ZoomToFit => &[Input::Key(Keycode::Z)],
+               Action::RotateLeft => &[Input::Key(Keycode::Left)],
+               Action::RotateRight => &[Input::Key(Keycode::Right)],
+               Action::RotateUp => &[Input::Key(Keycode::Up)],
+               Action::RotateDown => &[Input::Key(Keycode::Down)],
+               Action::Select => &[Input::Button(MouseButton::Right)],
+               Action::Focus => &[Input::Key(Keycode::F)],
+               Action::ToggleVelocity => &[Input::Key(Keycode::V)],
+               Action::ToggleForce => &[Input::Key(Keycode::G)],
+               Action::ToggleTrails => &[Input::Key(Keycode::T)],
+               Action::ToggleFullscreen => &[Input::Key(Keycode::F11)],
+               Action::Pause => &[Input::Key(Keycode::Space)],
+               Action::Step => &[Input::Key(Keycode::N)],
+               Action::Snapshot => &[Input::Key(Keycode::F5)],
+               Action::Record => &[Input::Key(Keycode::R)],
+               Action::Help => &[Input::Key(Keycode::H)],
+               Action::Quit => &[Input::Key(Keycode::Escape)]
+          }
+     }
+
+     /// Whether holding the key repeats the action
+     pub fn repeats(&self) -> bool {
+          matches!(
+               self,
+               Action::PanLeft
+                    | Action::PanRight
+                    | Action::PanUp
+                    | Action::PanDown
+                    | Action::ZoomIn
+                    | Action::ZoomOut
+                    | Action::RotateLeft
+                    | Action::RotateRight
+                    | Action::RotateUp
+                    | Action::RotateDown
+                    | Action::Step
+          )
+     }
+}
+
+impl Display for Action {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.write_str(match self {
+               Action::PanLeft => "pan_left",
+               Action::PanRight => "pan_right",
+               Action::PanUp => "pan_up",
+               Action::PanDown => "pan_down",
+               Action::Drag => "drag",
+               Action::ZoomIn => "zoom_in",
+               Action::ZoomOut => "zoom_out",
+               Action::ResetView => "reset_view",
+               Action::ZoomToFit => "zoom_to_fit",
+               Action::RotateLeft => "rotate_left",
+               Action::RotateRight => "rotate_right",
+               Action::RotateUp => "rotate_up",
+               Action::RotateDown => "rotate_down",
+               Action::Select => "select",
+               Action::Focus => "focus",
+               Action::ToggleVelocity => "toggle_velocity",
+               Action::ToggleForce => "toggle_force",
+               Action::ToggleTrails => "toggle_trails",
+               Action::ToggleFullscreen => "toggle_fullscreen",
+               Action::Pause => "pause",
+               Action::Step => "step",
+               Action::Snapshot => "snapshot",
+               Action::Record => "record",
+               Action::Help => "help",
+               Action::Quit => "quit"
+          })
+     }
+}
+
+impl FromStr for Action {
+     type Err = BindingsParseError;
+
+     fn from_str(s: &str) -> Result<Self, Self::Err> {
+          ACTIONS
+               .iter()
+               .find(|a| a.to_string() == s)
+               .copied()
+               .ok_or_else(|| BindingsParseError::new(0, format!("unknown action '{}'", s)))
+     }
+}
+
+// =============================================================================
+// Keys
+// =============================================================================
+
+/// Names of the keys which aren't a single character, as written by SDL, and
+/// of the ones separating the keys and starting the comments of the bindings
+const KEY_NAMES: &[(&str, Keycode)] = &[
+     ("Left", Keycode::Left),
+     ("Right", Keycode::Right),
+     ("Up", Keycode::Up),
+     ("Down", Keycode::Down),
+     ("Space", Keycode::Space),
+     ("Return", Keycode::Return),
+     ("Escape", Keycode::Escape),
+     ("Tab", Keycode::Tab),
+     ("Comma", Keycode::Comma),
+     ("Hash", Keycode::Hash),
+     ("Backspace", Keycode::Backspace),
+     ("Delete", Keycode::Delete),
+     ("Insert", Keycode::Insert),
+     ("Home", Keycode::Home),
+     ("End", Keycode::End),
+     ("PageUp", Keycode::PageUp),
+     ("PageDown", Keycode::PageDown),
+     ("F1", Keycode::F1),
+     ("F2", Keycode::F2),
+     ("F3", Keycode::F3),
+     ("F4", Keycode::F4),
+     ("F5", Keycode::F5),
+     ("F6", Keycode::F6),
+     ("F7", Keycode::F7),
+     ("F8", Keycode::F8),
+     ("F9", Keycode::F9),
+     ("F10", Keycode::F10),
+     ("F11", Keycode::F11),
+     ("F12", Keycode::F12),
+     ("Keypad +", Keycode::KpPlus),
+     ("Keypad -", Keycode::KpMinus),
+     ("Keypad 0", Keycode::Kp0),
+     ("Keypad 1", Keycode::Kp1),
+     ("Keypad 2", Keycode::Kp2),
+     ("Keypad 3", Keycode::Kp3),
+     ("Keypad 4", Keycode::Kp4),
+     ("Keypad 5", Keycode::Kp5),
+     ("Keypad 6", Keycode::Kp6),
+     ("Keypad 7", Keycode::Kp7),
+     ("Keypad 8", Keycode::Kp8),
+     ("Keypad 9", Keycode::Kp9)
+];
+
+/// Get a key from its name, a single character for the printable keys,
+/// without asking SDL so it works before its initialization
+pub fn parse_key(name: &str) -> Option<Keycode> {
+     if let Some((_, key)) = KEY_NAMES.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+          return Some(*key);
+     }
+
+     // the printable keys are their lowercase character
+     let mut chars = name.chars();
+     match (chars.next(), chars.next()) {
+          (Some(c), None) if c.is_ascii_graphic() => {
+               Keycode::from_i32(c.to_ascii_lowercase() as i32)
+          },
+          _ => None
+     }
+}
+
+/// Name of a key, as accepted by `parse_key`
+pub fn key_name(key: Keycode) -> String {
+     match KEY_NAMES.iter().find(|(_, k)| *k == key) {
+          Some((name, _)) => name.to_string(),
+          None => match char::from_u32(key as u32) {
+               Some(c) if c.is_ascii_graphic() => c.to_ascii_uppercase().to_string(),
+               _ => format!("{:?}", key)
+          }
+     }
+}
+
+/// Something the user presses to trigger an action
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Input {
+     Key(Keycode),
+     Button(MouseButton),
+     WheelUp,
+     WheelDown
+}
+
+impl From<Keycode> for Input {
+     fn from(key: Keycode) -> Self {
+          Input::Key(key)
+     }
+}
+
+/// Names of the inputs of the mouse
+const MOUSE_NAMES: &[(&str, Input)] = &[
+     ("Mouse Left", Input::Button(MouseButton::Left)),
+     ("Mouse Middle", Input::Button(MouseButton::Middle)),
+     ("Mouse Right", Input::Button(MouseButton::Right)),
+     ("Mouse X1", Input::Button(MouseButton::X1)),
+     ("Mouse X2", Input::Button(MouseButton::X2)),
+     ("Wheel Up", Input::WheelUp),
+     ("Wheel Down", Input::WheelDown)
+];
+
+/// Get an input from its name, the one of a key or of the mouse
+pub fn parse_input(name: &str) -> Option<Input> {
+     match MOUSE_NAMES
+          .iter()
+          .find(|(n, _)| n.eq_ignore_ascii_case(name))
+     {
+          Some((_, input)) => Some(*input),
+          None => parse_key(name).map(Input::Key)
+     }
+}
+
+/// Name of an input, as accepted by `parse_input`
+pub fn input_name(input: Input) -> String {
+     match (input, MOUSE_NAMES.iter().find(|(_, i)| *i == input)) {
+          (_, Some((name, _))) => name.to_string(),
+          (Input::Key(key), None) => key_name(key),
+          _ => format!("{:?}", input)
+     }
+}
+
+// =============================================================================
+// Bindings
+// =============================================================================
+
+/// The action of each key, mouse button and wheel direction
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bindings {
+     inputs: HashMap<Input, Action>
+}
+
+impl Default for Bindings {
+     fn default() -> Self {
+          let inputs = ACTIONS
+               .iter()
+               .flat_map(|a| a.default_inputs().iter().map(move |i| (*i, *a)))
+               .collect();
+
+          Self { inputs }
+     }
+}
+
+impl Bindings {
+     /// Get the action bound to an input, if any
+     pub fn action(&self, input: impl Into<Input>) -> Option<Action> {
+          self.inputs.get(&input.into()).copied()
+     }
+
+     /// Inputs bound to an action, sorted by name
+     pub fn inputs(&self, action: Action) -> Vec<Input> {
+          let mut inputs = self
+               .inputs
+               .iter()
+               .filter(|(_, a)| **a == action)
+               .map(|(i, _)| *i)
+               .collect::<Vec<_>>();
+          inputs.sort_by_key(|i| input_name(*i));
+
+          inputs
+     }
+
+     /// Bind the inputs to an action, in place of the ones it had, and return
+     /// the inputs taken from other actions with the action each was bound to
+     pub fn bind(&mut self, action: Action, inputs: &[Input]) -> Vec<(Input, Action)> {
+          self.inputs.retain(|_, a| *a != action);
+
+          inputs
+               .iter()
+               .filter_map(|input| {
+                    self.inputs
+                         .insert(*input, action)
+                         .filter(|displaced| *displaced != action)
+                         .map(|displaced| (*input, displaced))
+               })
+               .collect()
+     }
+
+     /// Parse the bindings from lines `<action> = <input>[, <input>...]`, the
+     /// actions not listed keeping their default inputs, which the listed ones
+     /// can take. An input listed for two actions is an error. An action
+     /// without input is disabled, and `#` starts a comment. The inputs are
+     /// keys, `Comma` and `Hash` standing for `,` and `#`, or `Mouse Left`,
+     /// `Mouse Middle`, `Mouse Right`, `Wheel Up` and `Wheel Down`.
+     pub fn parse(text: &str) -> Result<Self, BindingsParseError> {
+          let mut bindings = Bindings::default();
+          let mut listed = HashSet::new();
+
+          for (i, line) in text.lines().enumerate() {
+               let line_number = i + 1;
+               let line = line.split('#').next().unwrap_or_default().trim();
+               if line.is_empty() {
+                    continue;
+               }
+
+               let (action, inputs) = line.split_once('=').ok_or_else(|| {
+                    BindingsParseError::new(line_number, "expected '<action> = <inputs>'")
+               })?;
+               let action = action
+                    .trim()
+                    .parse::<Action>()
+                    .map_err(|e| BindingsParseError::new(line_number, e.message))?;
+
+               let inputs = inputs
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|i| !i.is_empty())
+                    .map(|i| {
+                         parse_input(i).ok_or_else(|| {
+                              BindingsParseError::new(line_number, format!("unknown input '{}'", i))
+                         })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+               let displaced = bindings.bind(action, &inputs);
+               if let Some((input, other)) = displaced.iter().find(|(_, a)| listed.contains(a)) {
+                    return Err(BindingsParseError::new(
+                         line_number,
+                         format!("'{}' is already bound to {}", input_name(*input), other)
+                    ));
+               }
+               listed.insert(action);
+          }
+
+          Ok(bindings)
+     }
+
+     /// Load the bindings from a file
+     pub fn load(path: &Path) -> anyhow::Result<Self> {
+          let text = fs::read_to_string(path)
+               .with_context(|| format!("Couldn't read the bindings from {}.", path.display()))?;
+
+          Bindings::parse(&text)
+               .with_context(|| format!("Couldn't parse the bindings of {}.", path.display()))
+     }
+}
+
+impl Display for Bindings {
+     /// One line per action, in the format read by `parse`
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          for action in ACTIONS {
+               let inputs = self
+                    .inputs(action)
+                    .into_iter()
+                    .map(input_name)
+                    .collect::<Vec<_>>();
+
+               writeln!(f, "{} = {}", action, inputs.join(", "))?;
+          }
+
+          Ok(())
+     }
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+pub struct BindingsParseError {
+     line: usize,
+     message: String
+}
+
+impl BindingsParseError {
+     fn new(line: usize, message: impl Into<String>) -> Self {
+          Self {
+               line,
+               message: message.into()
+          }
+     }
+}
+
+impl Debug for BindingsParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.debug_struct("BindingsParseError")
+               .field("line", &self.line)
+               .field("message", &self.message)
+               .finish()
+     }
+}
+
+impl Display for BindingsParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          if self.line == 0 {
+               write!(f, "{}.", self.message)
+          } else {
+               write!(f, "line {}: {}.", self.line, self.message)
+          }
+     }
+}
+
+impl std::error::Error for BindingsParseError {}
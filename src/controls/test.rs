@@ -0,0 +1,156 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::{keyboard::Keycode, mouse::MouseButton};
+
+use super::{input_name, key_name, parse_input, parse_key, Action, Bindings, Input, ACTIONS};
+
+#[test]
+fn test_key_names() {
+     assert_eq!(parse_key("a"), Some(Keycode::A));
+     assert_eq!(parse_key("A"), Some(Keycode::A));
+     assert_eq!(parse_key("="), Some(Keycode::Equals));
+     assert_eq!(parse_key("space"), Some(Keycode::Space));
+     assert_eq!(parse_key("Keypad +"), Some(Keycode::KpPlus));
+     assert_eq!(parse_key("F11"), Some(Keycode::F11));
+     assert_eq!(parse_key("Hyper"), None);
+     assert_eq!(parse_key(""), None);
+
+     // the separators of the bindings have names
+     assert_eq!(parse_key("comma"), Some(Keycode::Comma));
+     assert_eq!(parse_key("Hash"), Some(Keycode::Hash));
+
+     for key in [
+          Keycode::Q,
+          Keycode::Num1,
+          Keycode::Left,
+          Keycode::Kp7,
+          Keycode::Period,
+          Keycode::Comma,
+          Keycode::Hash
+     ] {
+          assert_eq!(parse_key(&key_name(key)), Some(key));
+     }
+
+     assert_eq!(
+          parse_input("mouse right"),
+          Some(Input::Button(MouseButton::Right))
+     );
+     assert_eq!(parse_input("Wheel Up"), Some(Input::WheelUp));
+     assert_eq!(parse_input("x"), Some(Input::Key(Keycode::X)));
+     for input in [
+          Input::Button(MouseButton::Middle),
+          Input::WheelDown,
+          Input::Key(Keycode::Space)
+     ] {
+          assert_eq!(parse_input(&input_name(input)), Some(input));
+     }
+}
+
+#[test]
+fn test_default_bindings() {
+     let bindings = Bindings::default();
+
+     // every action has a key, and no key does two of them
+     for action in ACTIONS {
+          assert!(!bindings.inputs(action).is_empty(), "{}", action);
+          assert_eq!(action.to_string().parse::<Action>().unwrap(), action);
+     }
+     let count = ACTIONS
+          .iter()
+          .map(|a| a.default_inputs().len())
+          .sum::<usize>();
+     assert_eq!(bindings.inputs.len(), count);
+
+     assert_eq!(bindings.action(Keycode::Space), Some(Action::Pause));
+     assert_eq!(bindings.action(Keycode::Left), Some(Action::RotateLeft));
+     assert_eq!(bindings.action(Keycode::Y), None);
+
+     // the mouse as well
+     assert_eq!(
+          bindings.action(Input::Button(MouseButton::Left)),
+          Some(Action::Drag)
+     );
+     assert_eq!(
+          bindings.action(Input::Button(MouseButton::Right)),
+          Some(Action::Select)
+     );
+     assert_eq!(bindings.action(Input::WheelDown), Some(Action::ZoomOut));
+}
+
+#[test]
+fn test_parse_bindings() {
+     let bindings = Bindings::parse(
+          "# arrows to pan
+          pan_left = Left, j
+          pan_right = Right
+          rotate_left =   # disabled
+
+          quit = q
+          select = Mouse Middle, Comma
+          drag = Hash"
+     )
+     .unwrap();
+
+     assert_eq!(bindings.action(Keycode::Left), Some(Action::PanLeft));
+     assert_eq!(bindings.action(Keycode::J), Some(Action::PanLeft));
+     assert_eq!(bindings.action(Keycode::A), None);
+     assert!(bindings.inputs(Action::RotateLeft).is_empty());
+     assert_eq!(bindings.inputs(Action::Quit), vec![Input::Key(Keycode::Q)]);
+     assert_eq!(
+          bindings.action(Input::Button(MouseButton::Middle)),
+          Some(Action::Select)
+     );
+     assert_eq!(bindings.action(Keycode::Comma), Some(Action::Select));
+     assert_eq!(bindings.action(Keycode::Hash), Some(Action::Drag));
+     assert_eq!(bindings.action(Input::Button(MouseButton::Left)), None);
+
+     // the others are kept
+     assert_eq!(bindings.action(Keycode::W), Some(Action::PanUp));
+
+     // written back the same way
+     assert_eq!(Bindings::parse(&bindings.to_string()).unwrap(), bindings);
+
+     let error = |text| Bindings::parse(text).unwrap_err().to_string();
+     assert_eq!(
+          error("pause = Space\nfly = x"),
+          "line 2: unknown action 'fly'."
+     );
+     assert_eq!(
+          error("pause = Spacebar"),
+          "line 1: unknown input 'Spacebar'."
+     );
+     assert_eq!(error("pause"), "line 1: expected '<action> = <inputs>'.");
+     assert_eq!(
+          error("pause = x\nquit = q, x"),
+          "line 2: 'X' is already bound to pause."
+     );
+}
+
+#[test]
+fn test_bind_conflicts() {
+     let mut bindings = Bindings::default();
+
+     // the inputs taken from other actions are returned
+     assert_eq!(
+          bindings.bind(Action::Quit, &[Keycode::Space.into(), Keycode::Y.into()]),
+          vec![(Input::Key(Keycode::Space), Action::Pause)]
+     );
+     assert_eq!(bindings.action(Keycode::Space), Some(Action::Quit));
+
+     // but not the ones the action already had
+     assert!(bindings.bind(Action::Quit, &[Keycode::Y.into()]).is_empty());
+}
@@ -3,7 +3,11 @@ use std::{f64::consts::PI, ops::Range};
 use rand::{prelude::ThreadRng, Rng};
 use sdl2::pixels::Color;
 
-use crate::{common::vec2::Vec2F, object::Object, simulation::object::NegativeOrNullMassError};
+use crate::{
+     common::{vec2::Vec2F, vec3::Vec3F},
+     object::Object,
+     simulation::object::{GeneratorId, NegativeOrNullMassError}
+};
 
 /// Generate random points in a circle
 fn generate_random_points_in_circle(
@@ -59,9 +63,96 @@ pub fn generate_random_objects_in_circle(
 
      let masses = generate_random_values_in_range(rng, mass_range, number);
 
+     let generator = GeneratorId::generate();
      let mut objects = Vec::with_capacity(number);
      for (point, mass) in points.iter().zip(masses.iter()) {
-          objects.push(Object::new_inactive(*mass, *point, true, color)?)
+          objects.push(Object::new_inactive(*mass, *point, true, color)?.with_generator(generator))
+     }
+
+     Ok(objects)
+}
+
+/// Generate random objects in a disk around `origin`, inclined by
+/// `inclination` radians around the x axis
+pub fn generate_random_objects_in_disk(
+     rng: &mut ThreadRng,
+     origin: Vec3F,
+     r: f64,
+     inclination: f64,
+     mass_range: Range<f64>,
+     number: usize,
+     color: Color
+) -> Result<Vec<Object<f64, Vec3F>>, NegativeOrNullMassError> {
+     let points = generate_random_points_in_circle(rng, Vec2F::new_null(), r, number);
+
+     let masses = generate_random_values_in_range(rng, mass_range, number);
+
+     let (cos, sin) = (inclination.cos(), inclination.sin());
+
+     let generator = GeneratorId::generate();
+     let mut objects = Vec::with_capacity(number);
+     for (point, mass) in points.iter().zip(masses.iter()) {
+          let location = Vec3F::new(point.x, point.y * cos, point.y * sin) + origin;
+
+          objects
+               .push(Object::new_inactive(*mass, location, true, color)?.with_generator(generator))
+     }
+
+     Ok(objects)
+}
+
+/// Generate a random direction in space
+pub fn generate_random_direction(rng: &mut ThreadRng) -> Vec3F {
+     let z: f64 = rng.gen_range(-1.0..1.0);
+     let t = rng.gen_range(0.0..2.0 * PI);
+     let r = (1.0 - z * z).sqrt();
+
+     Vec3F::new(r * t.cos(), r * t.sin(), z)
+}
+
+/// Generate random points in a sphere of radius `r`, following a Plummer
+/// profile of scale `r / 4` so they are denser at the center
+fn generate_random_points_in_sphere(
+     rng: &mut ThreadRng,
+     origin: Vec3F,
+     r: f64,
+     number: usize
+) -> Vec<Vec3F> {
+     let scale = r / 4.0;
+     let mut points = Vec::<Vec3F>::with_capacity(number);
+
+     while points.len() < number {
+          // inverse of the cumulated mass of the Plummer profile
+          let m: f64 = rng.gen_range(0.0..1.0);
+          let d = scale / (m.powf(-2.0 / 3.0) - 1.0).sqrt();
+
+          if d > r {
+               continue;
+          }
+
+          points.push(generate_random_direction(rng) * d + origin);
+     }
+
+     points
+}
+
+/// Generate random objects in a spherical bulge around `origin`
+pub fn generate_random_objects_in_bulge(
+     rng: &mut ThreadRng,
+     origin: Vec3F,
+     r: f64,
+     mass_range: Range<f64>,
+     number: usize,
+     color: Color
+) -> Result<Vec<Object<f64, Vec3F>>, NegativeOrNullMassError> {
+     let points = generate_random_points_in_sphere(rng, origin, r, number);
+
+     let masses = generate_random_values_in_range(rng, mass_range, number);
+
+     let generator = GeneratorId::generate();
+     let mut objects = Vec::with_capacity(number);
+     for (point, mass) in points.iter().zip(masses.iter()) {
+          objects.push(Object::new_inactive(*mass, *point, true, color)?.with_generator(generator))
      }
 
      Ok(objects)
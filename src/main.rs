@@ -14,57 +14,440 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use std::{env, f64::consts::PI, path::Path, sync::mpsc::Receiver};
+
 use anyhow::Context;
-use common::vec2::{Vec2, Vec2F};
-use rand::thread_rng;
+use common::{
+     units::{Dimension, UnitSystem},
+     vec2::{Vec2, Vec2F},
+     vec3::Vec3F,
+     vector::Vector
+};
+use controls::Bindings;
+use rand::{prelude::ThreadRng, thread_rng};
 use renderer::{
+     colormap::Colormap,
+     density::{Bloom, DensitySettings, Splat, ToneMapping},
      graphics::{
+          mapping::{ColorMapping, ColorRange, Quantity},
           radius::{MassGraphics, RadiusType},
-          vectors::{ForceLengthType, VelocityLengthType},
+          vectors::{ForceLengthType, VectorStyle, VelocityLengthType},
           Graphics
      },
-     viewport
+     headless::HeadlessSettings,
+     video::{Palette, VideoSettings},
+     viewport::{self, Viewport, ZoomSettings}
 };
 use sdl2::pixels::Color;
-use simulation::object;
+use simulation::{
+     accretion::{AccretionSettings, TidalDisruption},
+     boundary::{Boundary, EscaperDetector},
+     forces::{Drag, DynamicalFriction, ForceModule},
+     gas::GasSettings,
+     object,
+     physics::ForceSolver,
+     relativity::PostNewtonian,
+     save,
+     settings::{EngineSettings, Precision},
+     thread::{self, Snapshot}
+};
 
-use crate::simulation::object::{macros::create_object_value_checked, Object};
+use crate::simulation::object::{macros::create_object_value_checked, Object, ObjectKind};
 
 mod app;
 mod common;
+mod controls;
 mod generation;
 mod renderer;
 mod simulation;
 
-fn main() -> anyhow::Result<()> {
-     let mut rng = thread_rng();
+/// Inclination of the disk of the scene in space, in radians
+const DISK_INCLINATION: f64 = PI / 6.0;
 
-     // -------------------------------------------------------------------------
-     // Objects creation and configuration
-     // -------------------------------------------------------------------------
+/// Create a galaxy in space: a black hole in a spherical bulge, surrounded by
+/// an inclined disk
+fn create_space_scene(
+     rng: &mut ThreadRng,
+     units: &UnitSystem
+) -> anyhow::Result<Vec<Object<f64, Vec3F>>> {
+     let bh1 = create_object_value_checked!(
+          ObjectKind::BlackHole {
+               accretion_radius: units.parse("20 m", Dimension::Length)?
+          },
+          units.parse("1000 kg", Dimension::Mass)?,
+          Vec3F::new_null(),
+          false,
+          Color::RED
+     )
+     .with_name("bh1");
+     let origins = [bh1.clone()];
 
-     let bh1 = create_object_value_checked!(1000.0, Vec2F::new(0.0, 0.0), false, Color::RED);
+     // stars of the bulge orbit in random planes
+     let g = units.gravitational_constant();
 
-     // let mut s1 = create_object_value_checked!(50.0, Vec2F::new(400.0, 200.0),
-     // true, Color::CYAN);
-     let mut objects = generation::generate_random_objects_in_circle(
-          &mut rng,
+     let mut objects = generation::generate_random_objects_in_bulge(
+          rng,
           bh1.location,
-          1000.0,
+          units.parse("300 m", Dimension::Length)?,
+          10.0..100.0,
+          150,
+          Color::YELLOW
+     )
+     .map_err(|e| anyhow::anyhow!(e))?;
+     for o in objects.iter_mut() {
+          let normal = generation::generate_random_direction(rng);
+
+          object::add_orbital_velocity_in_space(o, &origins, g, normal);
+     }
+
+     let mut disk = generation::generate_random_objects_in_disk(
+          rng,
+          bh1.location,
+          units.parse("1 km", Dimension::Length)?,
+          DISK_INCLINATION,
           10.0..100.0,
           500,
           Color::CYAN
      )
      .map_err(|e| anyhow::anyhow!(e))?;
+     let normal = Vec3F::new(0.0, -DISK_INCLINATION.sin(), DISK_INCLINATION.cos());
+     for o in disk.iter_mut() {
+          object::add_orbital_velocity_in_space(o, &origins, g, normal);
+     }
 
-     object::add_orbital_velocity_for_each(
-          &mut objects,
-          &vec![bh1],
-          object::VelocityDirection::Left
-     );
-
+     objects.append(&mut disk);
      objects.push(bh1);
 
+     Ok(objects)
+}
+
+// =============================================================================
+// Arguments
+// =============================================================================
+
+/// What the command line asks for
+struct Arguments {
+     /// Simulate the scene in space instead of the plane
+     space: bool,
+
+     /// File to load the objects from
+     path: Option<String>,
+
+     /// Render the frames in files instead of a window
+     headless: Option<HeadlessSettings>,
+
+     /// How the videos are recorded, by the window or without it
+     video: VideoSettings,
+
+     /// Paint the objects according to a quantity
+     color_mapping: Option<ColorMapping>,
+
+     /// Draw the density of the objects instead of the objects
+     density: Option<DensitySettings>,
+
+     /// Action of each key in the window
+     bindings: Bindings,
+
+     /// How the window zooms
+     zoom: ZoomSettings,
+
+     /// Limits of the domain, read once the units of the objects are known
+     boundary: Option<String>,
+
+     /// Method computing the gravity, read once the units are known
+     solver: Option<String>,
+
+     /// The float type of the engine
+     precision: Precision,
+
+     /// Order of the corrections between the black holes, and the speed of
+     /// light, read once the units are known
+     post_newtonian: Option<String>,
+     speed_of_light: Option<String>,
+
+     /// Hydrodynamics of the gas parcels, read once the units are known, and
+     /// its artificial viscosity
+     gas: Option<String>,
+     viscosity: Option<(f64, f64)>,
+
+     /// Forces added to the gravity, the friction being read once the units
+     /// are known
+     friction: Option<String>,
+     drag: Option<Drag>
+}
+
+/// Parse `[--3d] [--render <directory or video> [--size <width>x<height>]
+/// [--frames <count>] [--supersampling <factor>] [--frame-interval
+/// <snapshots>]] [--frame-rate <fps>] [--palette <palette>] [--color-by
+/// <quantity> [--colormap <colormap>] [--color-range <min>:<max>]
+/// [--log-colors]] [--density <tone mapping> [--splat <sigma>] [--bloom
+/// <radius>:<strength>] [--exposure <exposure>] [--weight-by-mass]]
+/// [--bindings <file>] [--zoom-rate <factor>] [--zoom-limits <min>:<max>]
+/// [--zoom-smoothing <seconds>] [--boundary <boundary>] [--solver <solver>]
+/// [--precision <single|double>] [--post-newtonian <1pn|2.5pn>
+/// [--speed-of-light <speed>]] [--gas <gas> [--viscosity <alpha>:<beta>]]
+/// [--friction <friction>] [--drag <drag>] [file]`
+fn parse_arguments(window_size: Vec2<u32>) -> anyhow::Result<Arguments> {
+     let mut space = false;
+     let mut path = None;
+     let mut output = None;
+     let (mut size, mut frames, mut supersampling, mut frame_interval) = (window_size, 100, 1, 1);
+     let mut video = VideoSettings::new(30, Palette::Adaptive { colors: 256 });
+     let (mut color_by, mut colormap, mut color_range, mut log_colors) =
+          (None, None, ColorRange::Auto, false);
+     let (mut tone_mapping, mut splat, mut bloom, mut exposure, mut by_mass) =
+          (None, Splat::Gaussian { sigma: 1.0 }, None, 1.0, false);
+
+     let mut bindings = Bindings::default();
+     let mut zoom = ZoomSettings::default();
+     let mut boundary = None;
+     let mut solver = None;
+     let mut precision = Precision::Double;
+     let (mut post_newtonian, mut speed_of_light) = (None, None);
+     let (mut gas, mut viscosity) = (None, None);
+     let (mut friction, mut drag) = (None, None);
+
+     let mut args = env::args().skip(1);
+     while let Some(arg) = args.next() {
+          let mut value = || {
+               args.next()
+                    .with_context(|| format!("Missing value after {}.", arg))
+          };
+
+          match arg.as_str() {
+               "--3d" => space = true,
+               "--render" => output = Some(value()?),
+               "--size" => {
+                    let value = value()?;
+                    let (width, height) = value
+                         .split_once('x')
+                         .with_context(|| format!("'{}' is not a valid size.", value))?;
+
+                    size = Vec2::new(width.parse()?, height.parse()?);
+               },
+               "--frames" => frames = value()?.parse()?,
+               "--supersampling" => supersampling = value()?.parse()?,
+               "--frame-interval" => frame_interval = value()?.parse()?,
+               "--frame-rate" => video.frame_rate = value()?.parse()?,
+               "--palette" => video.palette = value()?.parse()?,
+               "--color-by" => color_by = Some(value()?.parse::<Quantity>()?),
+               "--colormap" => colormap = Some(value()?.parse::<Colormap>()?),
+               "--color-range" => {
+                    let value = value()?;
+                    let (min, max) = value
+                         .split_once(':')
+                         .with_context(|| format!("'{}' is not a valid range.", value))?;
+
+                    color_range = ColorRange::Fixed {
+                         min: min.parse()?,
+                         max: max.parse()?
+                    };
+               },
+               "--log-colors" => log_colors = true,
+               "--density" => tone_mapping = Some(value()?.parse::<ToneMapping>()?),
+               "--splat" => {
+                    let sigma = value()?.parse()?;
+
+                    splat = if sigma > 0.0 {
+                         Splat::Gaussian { sigma }
+                    } else {
+                         Splat::Point
+                    };
+               },
+               "--bloom" => {
+                    let value = value()?;
+                    let (radius, strength) = value
+                         .split_once(':')
+                         .with_context(|| format!("'{}' is not a valid bloom.", value))?;
+
+                    bloom = Some(Bloom {
+                         radius: radius.parse()?,
+                         strength: strength.parse()?
+                    });
+               },
+               "--exposure" => exposure = value()?.parse()?,
+               "--weight-by-mass" => by_mass = true,
+               "--bindings" => bindings = Bindings::load(Path::new(&value()?))?,
+               "--zoom-rate" => zoom.rate = value()?.parse()?,
+               "--zoom-limits" => {
+                    let value = value()?;
+                    let (min, max) = value
+                         .split_once(':')
+                         .with_context(|| format!("'{}' is not a valid range.", value))?;
+
+                    zoom.min = min.parse()?;
+                    zoom.max = max.parse()?;
+               },
+               "--zoom-smoothing" => zoom.smoothing = value()?.parse()?,
+               "--boundary" => boundary = Some(value()?),
+               "--solver" => solver = Some(value()?),
+               "--precision" => precision = value()?.parse()?,
+               "--post-newtonian" => post_newtonian = Some(value()?),
+               "--speed-of-light" => speed_of_light = Some(value()?),
+               "--gas" => gas = Some(value()?),
+               "--viscosity" => {
+                    let value = value()?;
+                    let (alpha, beta) = value
+                         .split_once(':')
+                         .with_context(|| format!("'{}' is not a valid viscosity.", value))?;
+
+                    viscosity = Some((alpha.parse()?, beta.parse()?));
+               },
+               "--friction" => friction = Some(value()?),
+               "--drag" => drag = Some(Drag::parse(&value()?)?),
+               _ if arg.starts_with("--") => anyhow::bail!("Unknown option {}.", arg),
+               _ => path = Some(arg)
+          }
+     }
+
+     let headless = output.map(|output| {
+          let mut settings = HeadlessSettings::new(output.into(), size, frames, video);
+          settings.supersampling = supersampling;
+          settings.frame_interval = frame_interval;
+
+          settings
+     });
+
+     let color_mapping = color_by.map(|quantity| {
+          let mut mapping = ColorMapping::new(quantity, colormap.unwrap_or(Colormap::Viridis));
+          mapping.range = color_range;
+          mapping.logarithmic = log_colors;
+
+          mapping
+     });
+
+     let density = tone_mapping.map(|tone_mapping| {
+          let mut settings =
+               DensitySettings::new(tone_mapping, colormap.unwrap_or(Colormap::Inferno));
+          settings.splat = splat;
+          settings.exposure = exposure;
+          settings.bloom = bloom;
+          settings.by_mass = by_mass;
+
+          settings
+     });
+
+     // once all the zoom options are known
+     let zoom = ZoomSettings::new(zoom.rate, zoom.min, zoom.max, zoom.smoothing)?;
+
+     Ok(Arguments {
+          space,
+          path,
+          headless,
+          video,
+          color_mapping,
+          density,
+          bindings,
+          zoom,
+          boundary,
+          solver,
+          precision,
+          post_newtonian,
+          speed_of_light,
+          gas,
+          viscosity,
+          friction,
+          drag
+     })
+}
+
+/// Apply the settings of the command line to the engine, their values being
+/// in `units`
+fn configure_engine(
+     settings: &mut EngineSettings,
+     arguments: &Arguments,
+     units: &UnitSystem
+) -> anyhow::Result<()> {
+     if let Some(boundary) = &arguments.boundary {
+          settings.boundary = Some(Boundary::parse(boundary, units)?);
+     }
+     if let Some(solver) = &arguments.solver {
+          settings.solver = ForceSolver::parse(solver, units)?;
+     }
+     settings.precision = arguments.precision;
+     if let Some(order) = &arguments.post_newtonian {
+          let speed_of_light = match &arguments.speed_of_light {
+               Some(speed) => units.parse(speed, Dimension::Velocity)?,
+               None => units.speed_of_light()
+          };
+
+          settings.post_newtonian = Some(PostNewtonian::parse(order, speed_of_light)?);
+     }
+     if let Some(gas) = &arguments.gas {
+          let mut gas = GasSettings::parse(gas, units)?;
+          if let Some((alpha, beta)) = arguments.viscosity {
+               gas = gas.with_viscosity(alpha, beta)?;
+          }
+
+          settings.gas = Some(gas);
+     } else if arguments.viscosity.is_some() {
+          anyhow::bail!("The viscosity needs the gas to be simulated, with --gas.");
+     }
+     if let Some(friction) = &arguments.friction {
+          let friction = DynamicalFriction::parse(friction, units)?;
+          settings
+               .forces
+               .push(ForceModule::DynamicalFriction(friction));
+     }
+     if let Some(drag) = arguments.drag {
+          settings.forces.push(ForceModule::Drag(drag));
+     }
+
+     Ok(())
+}
+
+/// Compute the energies of the color mapping like the engine does
+fn configure_color_mapping(arguments: &mut Arguments, settings: &EngineSettings) {
+     arguments.color_mapping = arguments.color_mapping.map(|mapping| {
+          mapping
+               .with_gravitational_constant(settings.units.gravitational_constant())
+               .with_force_smoothings(settings.force_smoothings)
+     });
+}
+
+/// Draw the snapshots in the window, or in files if asked to
+fn show<V: Vector<f64>>(
+     objects: Vec<Object<f64, V>>,
+     receiver: Receiver<Snapshot<V>>,
+     units: UnitSystem,
+     graphics: Graphics,
+     viewport: Viewport,
+     window_size: Vec2<u32>,
+     arguments: Arguments
+) -> anyhow::Result<()> {
+     let graphics = match arguments.color_mapping {
+          Some(mapping) => graphics.with_color_mapping(mapping),
+          None => graphics
+     };
+     let graphics = match arguments.density {
+          Some(density) => graphics.with_density(density),
+          None => graphics
+     };
+
+     match arguments.headless {
+          // the same region as in the window
+          Some(settings) => {
+               let mut viewport = viewport;
+               viewport.resize(window_size, settings.size);
+
+               renderer::headless::run(objects, receiver, units, &graphics, &viewport, &settings)
+          },
+          None => app::run(
+               objects,
+               receiver,
+               units,
+               graphics,
+               viewport,
+               window_size,
+               arguments.video,
+               arguments.bindings
+          )
+     }
+}
+
+fn main() -> anyhow::Result<()> {
+     let mut rng = thread_rng();
+
      // -------------------------------------------------------------------------
      // Graphics configuration
      // -------------------------------------------------------------------------
@@ -76,11 +459,119 @@ fn main() -> anyhow::Result<()> {
           radius_type,
           Some(VelocityLengthType::Constant(20.0)),
           Some(ForceLengthType::Constant(10.0))
-     );
+     )
+     .with_velocity_style(VectorStyle::new(Color::GREEN, 1.0, 4.0))
+     .with_force_style(VectorStyle::new(Color::MAGENTA, 1.0, 3.0));
 
      let window_size = Vec2::new(1024, 768);
-     let viewport = viewport::Viewport::new(1.0, Vec2F::new_null());
+     let mut arguments = parse_arguments(window_size)?;
+     let viewport =
+          viewport::Viewport::new(1.0, Vec2F::new_null()).with_zoom_settings(arguments.zoom);
+
+     // -------------------------------------------------------------------------
+     // Scene in space
+     // -------------------------------------------------------------------------
+
+     if arguments.space {
+          let units = UnitSystem::Si;
+          let objects = create_space_scene(&mut rng, &units)?;
+
+          // the accretion and the other modules only work in the plane
+          let mut settings = EngineSettings::new(units.parse("10 h", Dimension::Time)?, 50.0, 5);
+          settings.units = units;
+          configure_engine(&mut settings, &arguments, &units)?;
+          configure_color_mapping(&mut arguments, &settings);
+
+          let receiver = thread::launch_engine_thread_3d(objects.clone(), settings)?;
+
+          return show(
+               objects,
+               receiver,
+               units,
+               graphics,
+               viewport,
+               window_size,
+               arguments
+          );
+     }
+
+     // -------------------------------------------------------------------------
+     // Objects creation and configuration
+     // -------------------------------------------------------------------------
+
+     // load the objects from the file given as argument if any, in the units
+     // of the file
+     let (objects, units) = match &arguments.path {
+          Some(path) => {
+               let scenario = save::load_objects(Path::new(&path))
+                    .with_context(|| "Couldn't load the objects.")?;
+
+               (scenario.objects, scenario.units)
+          },
+          None => {
+               let units = UnitSystem::Si;
 
-     // launch the app
-     app::run(objects, 36000.0, 50.0, 5, graphics, viewport, window_size)
+               let bh1 = create_object_value_checked!(
+                    ObjectKind::BlackHole {
+                         accretion_radius: units.parse("20 m", Dimension::Length)?
+                    },
+                    units.parse("1000 kg", Dimension::Mass)?,
+                    Vec2F::new(0.0, 0.0),
+                    false,
+                    Color::RED
+               )
+               .with_name("bh1");
+
+               // let mut s1 = create_object_value_checked!(50.0, Vec2F::new(400.0,
+               // 200.0), true, Color::CYAN);
+               let mut objects = generation::generate_random_objects_in_circle(
+                    &mut rng,
+                    bh1.location,
+                    units.parse("1 km", Dimension::Length)?,
+                    10.0..100.0,
+                    500,
+                    Color::CYAN
+               )
+               .map_err(|e| anyhow::anyhow!(e))?;
+
+               object::add_orbital_velocity_for_each(
+                    &mut objects,
+                    &vec![bh1.clone()],
+                    units.gravitational_constant(),
+                    object::VelocityDirection::Left
+               );
+
+               objects.push(bh1);
+
+               (objects, units)
+          }
+     };
+
+     // -------------------------------------------------------------------------
+     // Engine configuration
+     // -------------------------------------------------------------------------
+
+     let mut settings = EngineSettings::new(units.parse("10 h", Dimension::Time)?, 50.0, 5);
+     settings.units = units;
+     settings.accretion = Some(AccretionSettings::new(Some(TidalDisruption::new(
+          3.0, 4, 5.0, 15.0
+     )?)));
+     settings.escapers = Some(EscaperDetector::new(
+          units.parse("5 km", Dimension::Length)?
+     )?);
+     configure_engine(&mut settings, &arguments, &units)?;
+     configure_color_mapping(&mut arguments, &settings);
+
+     // launch the engine and the app
+     let receiver = thread::launch_engine_thread(objects.clone(), settings)?;
+
+     show(
+          objects,
+          receiver,
+          units,
+          graphics,
+          viewport,
+          window_size,
+          arguments
+     )
 }
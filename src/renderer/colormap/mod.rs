@@ -0,0 +1,152 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use std::{
+     fmt::{Debug, Display},
+     str::FromStr
+};
+
+use sdl2::pixels::Color;
+
+// =============================================================================
+// Tables
+// =============================================================================
+
+/// Colors evenly spaced along the colormaps of matplotlib, interpolated
+/// linearly in between
+const VIRIDIS: &[u32] = &[
+     0x440154, 0x482878, 0x3e4989, 0x31688e, 0x26828e, 0x1f9e89, 0x35b779, 0x6ece58, 0xb5de2b,
+     0xfde725
+];
+
+const MAGMA: &[u32] = &[
+     0x000004, 0x180f3d, 0x440f76, 0x721f81, 0x9e2f7f, 0xcd4071, 0xf1605d, 0xfd9668, 0xfeca8d,
+     0xfcfdbf
+];
+
+const INFERNO: &[u32] = &[
+     0x000004, 0x1b0c41, 0x4a0c6b, 0x781c6d, 0xa52c60, 0xcf4446, 0xed6925, 0xfb9b06, 0xf7d13d,
+     0xfcffa4
+];
+
+const PLASMA: &[u32] = &[
+     0x0d0887, 0x46039f, 0x7201a8, 0x9c179e, 0xbd3786, 0xd8576b, 0xed7953, 0xfb9f3a, 0xfdca26,
+     0xf0f921
+];
+
+const GREY: &[u32] = &[0x000000, 0xffffff];
+
+// =============================================================================
+// Colormap
+// =============================================================================
+
+/// Colors for the values between 0 and 1, the perceptual ones changing of
+/// lightness evenly
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Colormap {
+     Viridis,
+     Magma,
+     Inferno,
+     Plasma,
+     Grey
+}
+
+impl Colormap {
+     fn table(&self) -> &'static [u32] {
+          match self {
+               Colormap::Viridis => VIRIDIS,
+               Colormap::Magma => MAGMA,
+               Colormap::Inferno => INFERNO,
+               Colormap::Plasma => PLASMA,
+               Colormap::Grey => GREY
+          }
+     }
+
+     /// Color of a value between 0 and 1, the values outside being clamped
+     pub fn sample(&self, t: f64) -> Color {
+          let table = self.table();
+          let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+
+          let position = t * (table.len() - 1) as f64;
+          let i = (position.floor() as usize).min(table.len() - 2);
+          let f = position - i as f64;
+
+          let channel = |c: u32, shift: u32| ((c >> shift) & 0xff) as f64;
+          let mix = |shift| {
+               let (a, b) = (channel(table[i], shift), channel(table[i + 1], shift));
+
+               (a + (b - a) * f).round() as u8
+          };
+
+          Color::RGB(mix(16), mix(8), mix(0))
+     }
+}
+
+impl Display for Colormap {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.write_str(match self {
+               Colormap::Viridis => "viridis",
+               Colormap::Magma => "magma",
+               Colormap::Inferno => "inferno",
+               Colormap::Plasma => "plasma",
+               Colormap::Grey => "grey"
+          })
+     }
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+pub struct ColormapParseError {
+     value: String
+}
+
+impl Debug for ColormapParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.debug_struct("ColormapParseError")
+               .field("value", &self.value)
+               .finish()
+     }
+}
+
+impl Display for ColormapParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          write!(f, "'{}' is not a valid colormap.", self.value)
+     }
+}
+
+impl std::error::Error for ColormapParseError {}
+
+impl FromStr for Colormap {
+     type Err = ColormapParseError;
+
+     fn from_str(s: &str) -> Result<Self, Self::Err> {
+          match s {
+               "viridis" => Ok(Colormap::Viridis),
+               "magma" => Ok(Colormap::Magma),
+               "inferno" => Ok(Colormap::Inferno),
+               "plasma" => Ok(Colormap::Plasma),
+               "grey" => Ok(Colormap::Grey),
+               _ => Err(ColormapParseError {
+                    value: s.to_string()
+               })
+          }
+     }
+}
@@ -0,0 +1,72 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::pixels::Color;
+
+use super::Colormap;
+
+const COLORMAPS: [Colormap; 5] = [
+     Colormap::Viridis,
+     Colormap::Magma,
+     Colormap::Inferno,
+     Colormap::Plasma,
+     Colormap::Grey
+];
+
+/// Perceived lightness of a color, roughly
+fn luma(color: Color) -> f64 {
+     0.299 * color.r as f64 + 0.587 * color.g as f64 + 0.114 * color.b as f64
+}
+
+#[test]
+fn test_colormap_ends() {
+     assert_eq!(Colormap::Viridis.sample(0.0), Color::RGB(0x44, 0x01, 0x54));
+     assert_eq!(Colormap::Viridis.sample(1.0), Color::RGB(0xfd, 0xe7, 0x25));
+     assert_eq!(Colormap::Magma.sample(0.0), Color::RGB(0, 0, 4));
+     assert_eq!(Colormap::Grey.sample(0.5), Color::RGB(128, 128, 128));
+
+     // clamped outside
+     for colormap in COLORMAPS {
+          assert_eq!(colormap.sample(-1.0), colormap.sample(0.0));
+          assert_eq!(colormap.sample(2.0), colormap.sample(1.0));
+          assert_eq!(colormap.sample(f64::NAN), colormap.sample(0.0));
+     }
+}
+
+#[test]
+fn test_colormaps_get_lighter() {
+     for colormap in COLORMAPS {
+          let lightness = (0..=50)
+               .map(|i| luma(colormap.sample(i as f64 / 50.0)))
+               .collect::<Vec<_>>();
+
+          assert!(
+               lightness.windows(2).all(|w| w[1] >= w[0] - 1.0),
+               "{} {:?}",
+               colormap,
+               lightness
+          );
+     }
+}
+
+#[test]
+fn test_colormap_round_trip() {
+     for colormap in COLORMAPS {
+          assert_eq!(colormap.to_string().parse::<Colormap>().unwrap(), colormap);
+     }
+
+     assert!("jet".parse::<Colormap>().is_err());
+}
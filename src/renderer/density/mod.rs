@@ -0,0 +1,407 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use std::{
+     fmt::{Debug, Display},
+     str::FromStr
+};
+
+use super::{
+     colormap::Colormap,
+     target::{BlendMode, Framebuffer, RenderTarget},
+     viewport::Viewport
+};
+use crate::{
+     common::{
+          vec2::{Vec2, Vec2F},
+          vector::Vector
+     },
+     simulation::object::Object
+};
+
+/// The Gaussians are cut at this many standard deviations
+const GAUSSIAN_EXTENT: f64 = 3.0;
+
+/// Narrower splats are deposited on the nearest pixels instead
+const MIN_SIGMA: f64 = 0.3;
+
+// =============================================================================
+// Settings
+// =============================================================================
+
+/// How each object is spread in the density buffer
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Splat {
+     /// On the four nearest pixels, bilinearly
+     Point,
+
+     /// In a Gaussian of the given standard deviation, in pixels
+     Gaussian { sigma: f64 }
+}
+
+/// How the densities are brought between 0 and 1 before the colormap
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ToneMapping {
+     Linear,
+
+     /// `ln(1 + exposure * density)`
+     Log,
+
+     /// `asinh(exposure * density)`, like the log for high densities but
+     /// linear near zero
+     Asinh
+}
+
+impl ToneMapping {
+     /// Map a density between 0 and 1, `max` being mapped to 1
+     pub fn apply(&self, density: f64, max: f64, exposure: f64) -> f64 {
+          if max <= 0.0 {
+               return 0.0;
+          }
+
+          let t = match self {
+               ToneMapping::Linear => density / max,
+               ToneMapping::Log => (exposure * density).ln_1p() / (exposure * max).ln_1p(),
+               ToneMapping::Asinh => (exposure * density).asinh() / (exposure * max).asinh()
+          };
+
+          t.clamp(0.0, 1.0)
+     }
+}
+
+/// Glow around the dense regions, by adding a blurred copy of the densities
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Bloom {
+     /// Standard deviation of the blur, in pixels
+     pub radius: f64,
+
+     /// Weight of the blurred copy
+     pub strength: f64
+}
+
+/// Draw the density of the objects instead of the objects themselves
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DensitySettings {
+     pub splat: Splat,
+     pub tone_mapping: ToneMapping,
+     pub colormap: Colormap,
+
+     /// Inverse of the density above which the log and asinh tone mappings
+     /// compress the values
+     pub exposure: f64,
+
+     pub bloom: Option<Bloom>,
+
+     /// Weight each object by its mass instead of counting it once
+     pub by_mass: bool
+}
+
+impl DensitySettings {
+     pub fn new(tone_mapping: ToneMapping, colormap: Colormap) -> Self {
+          Self {
+               splat: Splat::Gaussian { sigma: 1.0 },
+               tone_mapping,
+               colormap,
+               exposure: 1.0,
+               bloom: None,
+               by_mass: false
+          }
+     }
+}
+
+// =============================================================================
+// Buffer
+// =============================================================================
+
+/// Densities accumulated on a grid of pixels
+pub struct DensityBuffer {
+     width: u32,
+     height: u32,
+     values: Vec<f64>
+}
+
+impl DensityBuffer {
+     pub fn new(width: u32, height: u32) -> Self {
+          Self {
+               width,
+               height,
+               values: vec![0.0; width as usize * height as usize]
+          }
+     }
+
+     /// Density of a pixel, zero outside of the buffer
+     pub fn value(&self, x: i32, y: i32) -> f64 {
+          match self.index(x, y) {
+               Some(i) => self.values[i],
+               None => 0.0
+          }
+     }
+
+     /// Highest density of the buffer
+     pub fn max(&self) -> f64 {
+          self.values.iter().copied().fold(0.0, f64::max)
+     }
+
+     /// Sum of the densities of all the pixels
+     #[cfg(test)]
+     pub fn total(&self) -> f64 {
+          self.values.iter().sum()
+     }
+
+     fn index(&self, x: i32, y: i32) -> Option<usize> {
+          if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+               return None;
+          }
+
+          Some(y as usize * self.width as usize + x as usize)
+     }
+
+     fn add(&mut self, x: i32, y: i32, weight: f64) {
+          if let Some(i) = self.index(x, y) {
+               self.values[i] += weight;
+          }
+     }
+
+     /// Spread a weight around a point of the screen, the pixels being
+     /// sampled at their center
+     pub fn splat(&mut self, point: Vec2F, weight: f64, splat: Splat) {
+          let sigma = match splat {
+               Splat::Gaussian { sigma } if sigma >= MIN_SIGMA => sigma,
+               _ => {
+                    let p = point - Vec2::new(0.5, 0.5);
+                    let (x, y) = (p.x.floor(), p.y.floor());
+                    let (fx, fy) = (p.x - x, p.y - y);
+                    let (x, y) = (x as i32, y as i32);
+
+                    self.add(x, y, weight * (1.0 - fx) * (1.0 - fy));
+                    self.add(x + 1, y, weight * fx * (1.0 - fy));
+                    self.add(x, y + 1, weight * (1.0 - fx) * fy);
+                    self.add(x + 1, y + 1, weight * fx * fy);
+
+                    return;
+               }
+          };
+
+          let extent = GAUSSIAN_EXTENT * sigma;
+          let range = |c: f64| (c - extent).floor() as i32..=(c + extent).ceil() as i32;
+          let gaussian = |x: i32, c: f64| {
+               let d = x as f64 + 0.5 - c;
+
+               (-d * d / (2.0 * sigma * sigma)).exp()
+          };
+
+          // the kernel is separable, and normalized so the whole weight is
+          // deposited
+          let xs = range(point.x)
+               .map(|x| (x, gaussian(x, point.x)))
+               .collect::<Vec<_>>();
+          let ys = range(point.y)
+               .map(|y| (y, gaussian(y, point.y)))
+               .collect::<Vec<_>>();
+          let norm =
+               xs.iter().map(|(_, w)| w).sum::<f64>() * ys.iter().map(|(_, w)| w).sum::<f64>();
+
+          for (y, wy) in ys.iter() {
+               if *y < 0 || *y >= self.height as i32 {
+                    continue;
+               }
+
+               for (x, wx) in xs.iter() {
+                    self.add(*x, *y, weight * wx * wy / norm);
+               }
+          }
+     }
+
+     /// Copy of the buffer blurred by a Gaussian of the given standard
+     /// deviation, the densities outside being zero
+     pub fn blurred(&self, sigma: f64) -> DensityBuffer {
+          if sigma < MIN_SIGMA {
+               return Self {
+                    width: self.width,
+                    height: self.height,
+                    values: self.values.clone()
+               };
+          }
+
+          let extent = (GAUSSIAN_EXTENT * sigma).ceil() as i32;
+          let kernel = (-extent..=extent)
+               .map(|d| (-(d * d) as f64 / (2.0 * sigma * sigma)).exp())
+               .collect::<Vec<_>>();
+          let norm = kernel.iter().sum::<f64>();
+          let kernel = kernel.iter().map(|w| w / norm).collect::<Vec<_>>();
+
+          let pass = |source: &DensityBuffer, horizontal: bool| {
+               let mut result = DensityBuffer::new(source.width, source.height);
+
+               for y in 0..source.height as i32 {
+                    for x in 0..source.width as i32 {
+                         result.values[y as usize * source.width as usize + x as usize] = kernel
+                              .iter()
+                              .zip(-extent..=extent)
+                              .map(|(w, d)| {
+                                   let value = if horizontal {
+                                        source.value(x + d, y)
+                                   } else {
+                                        source.value(x, y + d)
+                                   };
+
+                                   w * value
+                              })
+                              .sum();
+                    }
+               }
+
+               result
+          };
+
+          pass(&pass(self, true), false)
+     }
+
+     /// Add a blurred copy of the buffer to itself
+     pub fn apply_bloom(&mut self, bloom: &Bloom) {
+          let blurred = self.blurred(bloom.radius);
+
+          for (v, b) in self.values.iter_mut().zip(blurred.values) {
+               *v += bloom.strength * b;
+          }
+     }
+
+     /// Color each pixel from its tone mapped density
+     pub fn to_image(&self, settings: &DensitySettings) -> Framebuffer {
+          let max = self.max();
+          let mut image = Framebuffer::new(self.width, self.height);
+
+          for y in 0..self.height as i32 {
+               for x in 0..self.width as i32 {
+                    let t = settings
+                         .tone_mapping
+                         .apply(self.value(x, y), max, settings.exposure);
+
+                    image.set_pixel(x, y, settings.colormap.sample(t));
+               }
+          }
+
+          image
+     }
+}
+
+// =============================================================================
+// Functions
+// =============================================================================
+
+/// Accumulate the objects seen through the viewport in a buffer of the given
+/// size, with the bloom if any
+pub fn compute_density<V: Vector<f64>>(
+     objects: &[Object<f64, V>],
+     settings: &DensitySettings,
+     viewport: &Viewport,
+     size: Vec2<u32>
+) -> DensityBuffer {
+     let mut buffer = DensityBuffer::new(size.x, size.y);
+
+     // the sizes are given in pixels of the drawings
+     let splat = match settings.splat {
+          Splat::Gaussian { sigma } => Splat::Gaussian {
+               sigma: sigma * viewport.pixel_ratio
+          },
+          Splat::Point => Splat::Point
+     };
+
+     for o in objects.iter() {
+          let point = viewport.to_screen(o.location);
+          let weight = if settings.by_mass { o.mass } else { 1.0 };
+
+          buffer.splat(point, weight, splat);
+     }
+
+     if let Some(bloom) = &settings.bloom {
+          buffer.apply_bloom(&Bloom {
+               radius: bloom.radius * viewport.pixel_ratio,
+               strength: bloom.strength
+          });
+     }
+
+     buffer
+}
+
+/// Draw the density of the objects over the whole target
+pub fn draw_density<T: RenderTarget, V: Vector<f64>>(
+     target: &mut T,
+     objects: &[Object<f64, V>],
+     settings: &DensitySettings,
+     viewport: &Viewport
+) -> Result<(), String> {
+     let image = compute_density(objects, settings, viewport, target.size()?).to_image(settings);
+
+     let previous = target.blend_mode();
+     target.set_blend_mode(BlendMode::None);
+     let result = target.draw_image(Vec2::new(0, 0), &image);
+     target.set_blend_mode(previous);
+
+     result
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+impl Display for ToneMapping {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.write_str(match self {
+               ToneMapping::Linear => "linear",
+               ToneMapping::Log => "log",
+               ToneMapping::Asinh => "asinh"
+          })
+     }
+}
+
+pub struct ToneMappingParseError {
+     value: String
+}
+
+impl Debug for ToneMappingParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.debug_struct("ToneMappingParseError")
+               .field("value", &self.value)
+               .finish()
+     }
+}
+
+impl Display for ToneMappingParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          write!(f, "'{}' is not a valid tone mapping.", self.value)
+     }
+}
+
+impl std::error::Error for ToneMappingParseError {}
+
+impl FromStr for ToneMapping {
+     type Err = ToneMappingParseError;
+
+     fn from_str(s: &str) -> Result<Self, Self::Err> {
+          match s {
+               "linear" => Ok(ToneMapping::Linear),
+               "log" => Ok(ToneMapping::Log),
+               "asinh" => Ok(ToneMapping::Asinh),
+               _ => Err(ToneMappingParseError {
+                    value: s.to_string()
+               })
+          }
+     }
+}
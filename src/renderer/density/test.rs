@@ -0,0 +1,145 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::pixels::Color;
+
+use super::{Bloom, DensityBuffer, DensitySettings, Splat, ToneMapping};
+use crate::{
+     common::vec2::Vec2F,
+     renderer::{
+          colormap::Colormap,
+          graphics::{self, radius::RadiusType, Graphics},
+          target::Framebuffer,
+          viewport::Viewport
+     },
+     simulation::object::Object
+};
+
+fn assert_close(value: f64, expected: f64) {
+     assert!(
+          (value - expected).abs() < 1e-9,
+          "{} instead of {}",
+          value,
+          expected
+     );
+}
+
+#[test]
+fn test_splats_keep_the_weight() {
+     for splat in [
+          Splat::Point,
+          Splat::Gaussian { sigma: 0.1 },
+          Splat::Gaussian { sigma: 1.0 },
+          Splat::Gaussian { sigma: 2.5 }
+     ] {
+          let mut buffer = DensityBuffer::new(32, 32);
+          buffer.splat(Vec2F::new(12.3, 17.8), 2.0, splat);
+          buffer.splat(Vec2F::new(16.0, 16.0), 1.0, splat);
+
+          assert_close(buffer.total(), 3.0);
+     }
+
+     // on a pixel center, a point only touches that pixel
+     let mut buffer = DensityBuffer::new(8, 8);
+     buffer.splat(Vec2F::new(3.5, 4.5), 1.0, Splat::Point);
+     assert_close(buffer.value(3, 4), 1.0);
+
+     // and is split between the nearest ones otherwise
+     let mut buffer = DensityBuffer::new(8, 8);
+     buffer.splat(Vec2F::new(4.0, 4.5), 1.0, Splat::Point);
+     assert_close(buffer.value(3, 4), 0.5);
+     assert_close(buffer.value(4, 4), 0.5);
+}
+
+#[test]
+fn test_blur_keeps_the_total() {
+     let mut buffer = DensityBuffer::new(40, 40);
+     buffer.splat(Vec2F::new(20.5, 20.5), 1.0, Splat::Point);
+     buffer.splat(Vec2F::new(12.5, 25.5), 3.0, Splat::Point);
+
+     let blurred = buffer.blurred(2.0);
+     assert_close(blurred.total(), 4.0);
+
+     // spread symmetrically, the peak getting lower
+     assert!(blurred.value(20, 20) < 1.0);
+     assert_close(blurred.value(19, 20), blurred.value(21, 20));
+     assert_close(blurred.value(20, 18), blurred.value(20, 22));
+
+     let mut bloomed = DensityBuffer::new(40, 40);
+     bloomed.splat(Vec2F::new(20.5, 20.5), 1.0, Splat::Point);
+     bloomed.apply_bloom(&Bloom {
+          radius: 2.0,
+          strength: 0.5
+     });
+     assert_close(bloomed.total(), 1.5);
+     assert!(bloomed.value(23, 20) > 0.0);
+}
+
+#[test]
+fn test_tone_mappings() {
+     for mapping in [ToneMapping::Linear, ToneMapping::Log, ToneMapping::Asinh] {
+          let values = (0..=20)
+               .map(|i| mapping.apply(i as f64 * 5.0, 100.0, 0.5))
+               .collect::<Vec<_>>();
+
+          assert_eq!(values[0], 0.0);
+          assert_close(values[20], 1.0);
+          assert!(values.windows(2).all(|w| w[1] > w[0]), "{:?}", values);
+
+          assert_eq!(mapping.apply(1.0, 0.0, 1.0), 0.0);
+          assert_eq!(mapping.to_string().parse::<ToneMapping>().unwrap(), mapping);
+     }
+
+     // the low densities are brought up
+     assert!(ToneMapping::Log.apply(1.0, 100.0, 1.0) > 0.1);
+     assert!(ToneMapping::Asinh.apply(1.0, 100.0, 1.0) > 0.1);
+     assert!("gamma".parse::<ToneMapping>().is_err());
+}
+
+#[test]
+fn test_draw_density() {
+     let viewport = Viewport::new(1.0, Vec2F::new_null());
+     let star = |x, y| {
+          Object::new(
+               1.0,
+               Vec2F::new(x, y),
+               Vec2F::new_null(),
+               Vec2F::new_null(),
+               true,
+               Color::RED
+          )
+          .unwrap()
+     };
+
+     // a cluster and a lone star
+     let mut objects = (0..10)
+          .map(|i| star(16.0 + (i % 3) as f64 * 0.5, 16.0 + (i / 3) as f64 * 0.5))
+          .collect::<Vec<_>>();
+     objects.push(star(4.5, 4.5));
+
+     let settings = DensitySettings::new(ToneMapping::Log, Colormap::Grey);
+     let graphics = Graphics::new(RadiusType::Constant(1.0), None, None).with_density(settings);
+
+     let mut target = Framebuffer::new(32, 32);
+     graphics::draw_objects(&mut target, &objects, None, &graphics, &viewport).unwrap();
+
+     // the whole target is covered, the cluster being the brightest
+     let grey = |x, y| target.pixel(x, y).unwrap().r;
+     assert_eq!(target.pixel(0, 0), Some(Color::RGB(0, 0, 0)));
+     assert!(grey(16, 16) > 200);
+     assert!(grey(4, 4) > 0 && grey(4, 4) < grey(16, 16));
+     assert!(grey(16, 16) > grey(20, 16));
+}
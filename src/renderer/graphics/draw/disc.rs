@@ -0,0 +1,106 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::pixels::Color;
+
+use crate::{
+     common::vec2::{Vec2, Vec2F, VecInto},
+     renderer::target::{BlendMode, RenderTarget}
+};
+
+/// Smaller discs are drawn with this radius, fainter to keep their area
+const MIN_RADIUS: f64 = 0.5;
+
+/// Draw a disc blended over the target, the pixels on its edge being lit
+/// according to how much of them it covers. The blend mode of the target is
+/// left unchanged.
+pub fn draw_disc<T: RenderTarget>(
+     target: &mut T,
+     center: Vec2F,
+     radius: f64,
+     color: Color
+) -> Result<(), String> {
+     if radius.is_nan() || radius <= 0.0 || color.a == 0 {
+          return Ok(());
+     }
+
+     let (radius, alpha) = if radius < MIN_RADIUS {
+          (MIN_RADIUS, color.a as f64 * (radius / MIN_RADIUS).powi(2))
+     } else {
+          (radius, color.a as f64)
+     };
+
+     // only the part of the disc inside the target is rasterized
+     let size: Vec2F = target.size()?.into_vec();
+     let extent = Vec2F::new(radius + 0.5, radius + 0.5);
+     let (x_min, y_min) = (center - extent).convert(f64::floor).into_tuple();
+     let (x_max, y_max) = (center + extent).convert(f64::ceil).into_tuple();
+     let x_range = (x_min.max(0.0) as i32)..(x_max.min(size.x) as i32);
+     let y_range = (y_min.max(0.0) as i32)..(y_max.min(size.y) as i32);
+
+     let previous = target.blend_mode();
+     target.set_blend_mode(BlendMode::Blend);
+
+     for y in y_range {
+          let dy = y as f64 + 0.5 - center.y;
+
+          // the fully covered pixels are drawn in a single span
+          let mut span: Option<i32> = None;
+          for x in x_range.clone() {
+               let dx = x as f64 + 0.5 - center.x;
+               let coverage = (radius + 0.5 - (dx * dx + dy * dy).sqrt()).clamp(0.0, 1.0);
+
+               if coverage == 1.0 {
+                    span.get_or_insert(x);
+
+                    continue;
+               }
+
+               if let Some(start) = span.take() {
+                    fill_span(target, start, x, y, color, alpha)?;
+               }
+               if coverage > 0.0 {
+                    let a = (alpha * coverage).round() as u8;
+                    target.fill_rect(Vec2::new(x, y), 1, 1, with_alpha(color, a))?;
+               }
+          }
+
+          if let Some(start) = span {
+               fill_span(target, start, x_range.end, y, color, alpha)?;
+          }
+     }
+
+     target.set_blend_mode(previous);
+
+     Ok(())
+}
+
+fn fill_span<T: RenderTarget>(
+     target: &mut T,
+     start: i32,
+     end: i32,
+     y: i32,
+     color: Color,
+     alpha: f64
+) -> Result<(), String> {
+     let color = with_alpha(color, alpha.round() as u8);
+
+     target.fill_rect(Vec2::new(start, y), (end - start) as u32, 1, color)
+}
+
+fn with_alpha(color: Color, alpha: u8) -> Color {
+     Color::RGBA(color.r, color.g, color.b, alpha)
+}
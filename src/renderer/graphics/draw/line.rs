@@ -14,24 +14,114 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use sdl2::{pixels::Color, rect::Point, render::Canvas, video::Window};
+use std::f64::consts::PI;
 
-use crate::common::vec2::Vec2;
+use sdl2::pixels::Color;
 
-/// Draw a vector in the canvas
-pub fn draw_line_u32(canvas: &mut Canvas<Window>, p1: Vec2<i32>, p2: Vec2<i32>, color: Color) {
-     // skip empty lines
-     if p1 == p2 {
-          return;
+use crate::{
+     common::vec2::{Vec2, Vec2F, VecInto, VecLength},
+     renderer::target::{BlendMode, RenderTarget}
+};
+
+/// Angle between the shaft of an arrow and each side of its head, in radians
+const ARROWHEAD_ANGLE: f64 = PI / 6.0;
+
+/// Draw a line blended over the target with Xiaolin Wu's anti-aliasing,
+/// generalized to any thickness: each pixel along the line is lit according
+/// to how much of it the line covers across and along its main axis. The
+/// blend mode of the target is left unchanged.
+pub fn draw_line<T: RenderTarget>(
+     target: &mut T,
+     p1: Vec2F,
+     p2: Vec2F,
+     thickness: f64,
+     color: Color
+) -> Result<(), String> {
+     let delta = p2 - p1;
+     if delta.length_f64() == 0.0 || thickness.is_nan() || thickness <= 0.0 || color.a == 0 {
+          return Ok(());
+     }
+
+     // walk along the axis the line is the longest on, `u` being that axis and
+     // `v` the other one
+     let steep = delta.y.abs() > delta.x.abs();
+     let swap = |p: Vec2F| if steep { Vec2::new(p.y, p.x) } else { p };
+     let (mut a, mut b) = (swap(p1), swap(p2));
+     if a.x > b.x {
+          std::mem::swap(&mut a, &mut b);
+     }
+
+     let size = swap(target.size()?.into_vec());
+     let gradient = (b.y - a.y) / (b.x - a.x);
+
+     // width of the line measured along `v`
+     let half_width = 0.5 * thickness * (1.0 + gradient * gradient).sqrt();
+
+     let previous = target.blend_mode();
+     target.set_blend_mode(BlendMode::Blend);
+
+     let u_min = a.x.floor().max(0.0) as i32;
+     let u_max = b.x.ceil().min(size.x) as i32;
+     for u in u_min..u_max {
+          let along = ((u + 1) as f64).min(b.x) - (u as f64).max(a.x);
+          if along <= 0.0 {
+               continue;
+          }
+
+          // the pixels are sampled at their center
+          let center = a.y + gradient * (u as f64 + 0.5 - a.x);
+          let v_min = (center - half_width - 0.5).floor().max(0.0) as i32;
+          let v_max = (center + half_width + 0.5).ceil().min(size.y) as i32;
+
+          for v in v_min..v_max {
+               let across = (half_width + 0.5 - (v as f64 + 0.5 - center).abs()).clamp(0.0, 1.0);
+               let coverage = along.min(1.0) * across.min(thickness);
+               if coverage <= 0.0 {
+                    continue;
+               }
+
+               let alpha = (color.a as f64 * coverage).round() as u8;
+               let pixel = if steep {
+                    Vec2::new(v, u)
+               } else {
+                    Vec2::new(u, v)
+               };
+               target.fill_rect(pixel, 1, 1, Color::RGBA(color.r, color.g, color.b, alpha))?;
+          }
      }
 
-     if canvas.draw_color() != color {
-          canvas.set_draw_color(color);
+     target.set_blend_mode(previous);
+
+     Ok(())
+}
+
+/// Draw an arrow from `p1` to `p2`, its head being `head_size` long and at
+/// most half the arrow
+pub fn draw_arrow<T: RenderTarget>(
+     target: &mut T,
+     p1: Vec2F,
+     p2: Vec2F,
+     thickness: f64,
+     head_size: f64,
+     color: Color
+) -> Result<(), String> {
+     let delta = p2 - p1;
+     let length = delta.length_f64();
+     if length == 0.0 {
+          return Ok(());
      }
 
-     // convert Vec2 to SDL points
-     let point1 = Point::new(p1.x, p1.y);
-     let point2 = Point::new(p2.x, p2.y);
+     // the shaft stops where the head starts so that they do not blend twice
+     let head_size = head_size.min(length / 2.0);
+     let shaft_end = p2 - delta * (head_size * ARROWHEAD_ANGLE.cos() / length);
+     draw_line(target, p1, shaft_end, thickness, color)?;
+
+     let back = delta.y.atan2(delta.x) + PI;
+     for side in [-ARROWHEAD_ANGLE, ARROWHEAD_ANGLE] {
+          let end = p2 + Vec2F::from_angle_value(back + side, head_size);
+
+          draw_line(target, p2, end, thickness, color)?;
+     }
 
-     canvas.draw_line(point1, point2).unwrap();
+     Ok(())
 }
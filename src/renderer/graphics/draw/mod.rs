@@ -14,8 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-mod point;
-pub use point::*;
+#[cfg(test)]
+mod test;
+
+mod disc;
+pub use disc::*;
 
 mod line;
 pub use line::*;
+
+mod text;
+pub use text::*;
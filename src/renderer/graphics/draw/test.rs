@@ -0,0 +1,267 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{env, fs::File, io::BufWriter, path::PathBuf};
+
+use sdl2::pixels::Color;
+
+use super::{draw_arrow, draw_disc, draw_line};
+use crate::{
+     common::vec2::Vec2F,
+     renderer::{
+          headless,
+          target::{BlendMode, Framebuffer, RenderTarget}
+     }
+};
+
+/// Compare an image with the golden one of the same name, or replace it when
+/// `GALAXYX_UPDATE_GOLDEN` is set
+fn assert_golden(name: &str, image: &Framebuffer) {
+     let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+          .join("src/renderer/graphics/draw/golden")
+          .join(format!("{}.png", name));
+
+     if env::var_os("GALAXYX_UPDATE_GOLDEN").is_some() {
+          let file = File::create(&path).unwrap();
+          headless::write_png(BufWriter::new(file), image).unwrap();
+
+          return;
+     }
+
+     let mut reader = png::Decoder::new(File::open(&path).unwrap())
+          .read_info()
+          .unwrap();
+     let mut golden = vec![0; reader.output_buffer_size()];
+     let info = reader.next_frame(&mut golden).unwrap();
+
+     assert_eq!((info.width, info.height), (image.width(), image.height()));
+     if golden != image.pixels() {
+          let actual = env::temp_dir().join(format!("{}.png", name));
+          headless::write_png(BufWriter::new(File::create(&actual).unwrap()), image).unwrap();
+
+          panic!(
+               "{} differs from {}, written to {}",
+               name,
+               path.display(),
+               actual.display()
+          );
+     }
+}
+
+fn black_image(width: u32, height: u32) -> Framebuffer {
+     let mut image = Framebuffer::new(width, height);
+     image.clear(Color::BLACK);
+
+     image
+}
+
+/// Sum of the alpha drawn, weighting each pixel by its red component
+fn drawn_area(image: &Framebuffer) -> f64 {
+     image.pixels()
+          .chunks_exact(4)
+          .map(|p| p[0] as f64 / 255.0)
+          .sum()
+}
+
+#[test]
+fn test_disc_golden_images() {
+     let mut image = black_image(24, 16);
+     draw_disc(&mut image, Vec2F::new(6.0, 8.0), 4.0, Color::WHITE).unwrap();
+     draw_disc(&mut image, Vec2F::new(16.3, 7.6), 2.5, Color::YELLOW).unwrap();
+     draw_disc(&mut image, Vec2F::new(21.5, 2.5), 0.3, Color::WHITE).unwrap();
+     assert_golden("discs", &image);
+
+     // blended over each other
+     let mut image = black_image(16, 12);
+     draw_disc(&mut image, Vec2F::new(6.0, 6.0), 4.5, Color::BLUE).unwrap();
+     draw_disc(
+          &mut image,
+          Vec2F::new(10.0, 6.0),
+          4.5,
+          Color::RGBA(255, 0, 0, 128)
+     )
+     .unwrap();
+     assert_golden("blended_discs", &image);
+}
+
+#[test]
+fn test_disc_is_round_and_covers_its_area() {
+     let mut image = black_image(32, 32);
+     draw_disc(&mut image, Vec2F::new(16.0, 16.0), 8.0, Color::WHITE).unwrap();
+
+     // symmetric around its center
+     for y in 0..32 {
+          for x in 0..32 {
+               assert_eq!(image.pixel(x, y), image.pixel(31 - x, y));
+               assert_eq!(image.pixel(x, y), image.pixel(y, x));
+          }
+     }
+
+     // the corners of the bounding square are left out
+     assert_eq!(image.pixel(9, 9), Some(Color::BLACK));
+     assert_eq!(image.pixel(16, 16), Some(Color::WHITE));
+
+     let area = std::f64::consts::PI * 64.0;
+     assert!((drawn_area(&image) - area).abs() < 0.01 * area);
+
+     // moving it by a fraction of pixel keeps its area
+     let mut image = black_image(32, 32);
+     draw_disc(&mut image, Vec2F::new(16.25, 15.6), 8.0, Color::WHITE).unwrap();
+     assert!((drawn_area(&image) - area).abs() < 0.01 * area);
+}
+
+#[test]
+fn test_disc_keeps_blend_mode() {
+     let mut image = black_image(8, 8);
+     image.set_blend_mode(BlendMode::Add);
+
+     draw_disc(
+          &mut image,
+          Vec2F::new(4.0, 4.0),
+          2.0,
+          Color::RGBA(255, 0, 0, 128)
+     )
+     .unwrap();
+
+     assert_eq!(image.blend_mode(), BlendMode::Add);
+
+     // blended over the black background, not added to it
+     assert_eq!(image.pixel(4, 4), Some(Color::RGBA(128, 0, 0, 255)));
+}
+
+#[test]
+fn test_disc_clipped() {
+     let mut image = black_image(8, 8);
+     draw_disc(&mut image, Vec2F::new(-2.0, 4.0), 3.0, Color::WHITE).unwrap();
+     draw_disc(&mut image, Vec2F::new(4.0, 4.0), 1e9, Color::RED).unwrap();
+
+     assert!((0..8).all(|x| (0..8).all(|y| image.pixel(x, y) == Some(Color::RED))));
+}
+
+#[test]
+fn test_line_golden_images() {
+     let mut image = black_image(24, 16);
+     draw_line(
+          &mut image,
+          Vec2F::new(1.0, 1.5),
+          Vec2F::new(22.0, 3.0),
+          1.0,
+          Color::WHITE
+     )
+     .unwrap();
+     draw_line(
+          &mut image,
+          Vec2F::new(2.0, 14.0),
+          Vec2F::new(12.5, 5.0),
+          1.0,
+          Color::CYAN
+     )
+     .unwrap();
+     draw_line(
+          &mut image,
+          Vec2F::new(14.0, 7.0),
+          Vec2F::new(22.0, 13.0),
+          2.5,
+          Color::YELLOW
+     )
+     .unwrap();
+     assert_golden("lines", &image);
+
+     let mut image = black_image(16, 16);
+     draw_arrow(
+          &mut image,
+          Vec2F::new(2.0, 13.0),
+          Vec2F::new(13.0, 3.0),
+          1.0,
+          5.0,
+          Color::GREEN
+     )
+     .unwrap();
+     assert_golden("arrow", &image);
+}
+
+#[test]
+fn test_line_coverage() {
+     // on the centers of the pixels, a plain line
+     let mut image = black_image(16, 4);
+     draw_line(
+          &mut image,
+          Vec2F::new(2.0, 1.5),
+          Vec2F::new(12.0, 1.5),
+          1.0,
+          Color::WHITE
+     )
+     .unwrap();
+
+     assert!((2..12).all(|x| image.pixel(x, 1) == Some(Color::WHITE)));
+     assert_eq!(image.pixel(1, 1), Some(Color::BLACK));
+     assert_eq!(image.pixel(12, 1), Some(Color::BLACK));
+     assert!((0..16).all(|x| image.pixel(x, 0) == Some(Color::BLACK)));
+
+     // between two rows, both half lit
+     let mut image = black_image(16, 4);
+     draw_line(
+          &mut image,
+          Vec2F::new(2.0, 2.0),
+          Vec2F::new(12.0, 2.0),
+          1.0,
+          Color::WHITE
+     )
+     .unwrap();
+
+     let half = Some(Color::RGBA(128, 128, 128, 255));
+     assert!((2..12).all(|x| image.pixel(x, 1) == half && image.pixel(x, 2) == half));
+
+     // the light drawn is the area of the line, whatever its direction
+     for (end, thickness) in [
+          (Vec2F::new(28.0, 20.0), 1.0),
+          (Vec2F::new(10.0, 28.3), 1.0),
+          (Vec2F::new(27.0, 9.0), 3.0)
+     ] {
+          let start = Vec2F::new(4.0, 4.0);
+          let mut image = black_image(32, 32);
+          draw_line(&mut image, start, end, thickness, Color::WHITE).unwrap();
+
+          let delta = end - start;
+          let area = (delta.x * delta.x + delta.y * delta.y).sqrt() * thickness;
+          let drawn = drawn_area(&image);
+          assert!(
+               (drawn - area).abs() < 0.03 * area,
+               "{} instead of {}",
+               drawn,
+               area
+          );
+     }
+}
+
+#[test]
+fn test_line_keeps_blend_mode() {
+     let mut image = black_image(8, 8);
+     image.set_blend_mode(BlendMode::Add);
+
+     draw_arrow(
+          &mut image,
+          Vec2F::new(0.0, 4.5),
+          Vec2F::new(8.0, 4.5),
+          1.0,
+          3.0,
+          Color::RGBA(0, 255, 0, 128)
+     )
+     .unwrap();
+
+     assert_eq!(image.blend_mode(), BlendMode::Add);
+     assert_eq!(image.pixel(2, 4), Some(Color::RGBA(0, 128, 0, 255)));
+}
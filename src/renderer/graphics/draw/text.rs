@@ -0,0 +1,87 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::pixels::Color;
+
+use crate::{common::vec2::Vec2, renderer::target::RenderTarget};
+
+/// Width and height of the glyphs, in pixels before scaling
+pub const GLYPH_SIZE: Vec2<u32> = Vec2 { x: 3, y: 5 };
+
+/// Rows of the glyphs from the top, the most significant of the 3 bits being
+/// the left pixel
+fn glyph(c: char) -> Option<[u8; 5]> {
+     Some(match c {
+          '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+          '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+          '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+          '3' => [0b111, 0b001, 0b011, 0b001, 0b111],
+          '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+          '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+          '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+          '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+          '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+          '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+          '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+          '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+          '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+          'e' => [0b000, 0b111, 0b111, 0b100, 0b111],
+          ' ' => [0; 5],
+          _ => return None
+     })
+}
+
+/// Width of a text drawn with `draw_text`, in pixels
+pub fn text_width(text: &str, scale: u32) -> u32 {
+     let count = text.chars().count() as u32;
+
+     (count * (GLYPH_SIZE.x + 1)).saturating_sub(1) * scale
+}
+
+/// Draw a number with a small pixel font, its top left corner at `origin`
+/// and each pixel of the font being a square of `scale` pixels. Only digits,
+/// `.`, `-`, `+` and `e` are drawn.
+pub fn draw_text<T: RenderTarget>(
+     target: &mut T,
+     origin: Vec2<i32>,
+     text: &str,
+     scale: u32,
+     color: Color
+) -> Result<(), String> {
+     let advance = ((GLYPH_SIZE.x + 1) * scale) as i32;
+
+     for (i, c) in text.chars().enumerate() {
+          let rows = match glyph(c) {
+               Some(rows) => rows,
+               None => continue
+          };
+
+          for (y, row) in rows.iter().enumerate() {
+               for x in 0..GLYPH_SIZE.x {
+                    if row >> (GLYPH_SIZE.x - 1 - x) & 1 == 1 {
+                         let pixel = Vec2::new(
+                              origin.x + i as i32 * advance + (x * scale) as i32,
+                              origin.y + (y as u32 * scale) as i32
+                         );
+
+                         target.fill_rect(pixel, scale, scale, color)?;
+                    }
+               }
+          }
+     }
+
+     Ok(())
+}
@@ -0,0 +1,316 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+     fmt::{Debug, Display},
+     str::FromStr
+};
+
+use sdl2::pixels::Color;
+
+use crate::{
+     common::{constants::G, vector::Vector},
+     renderer::colormap::Colormap,
+     simulation::{object::Object, physics}
+};
+
+/// Fraction of the values left out at each end of the automatic ranges, so a
+/// few extreme objects don't flatten the colors of the others
+const AUTO_RANGE_OUTLIERS: f64 = 0.01;
+
+// =============================================================================
+// Enums
+// =============================================================================
+
+/// What the colors of the objects show
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Quantity {
+     Speed,
+     Acceleration,
+
+     /// Kinetic plus gravitational potential energy, per unit of mass
+     SpecificEnergy,
+
+     Mass,
+
+     /// Distance to the center of mass of all the objects
+     RadialDistance,
+
+     /// The generator the object was created by, the ones created otherwise
+     /// being in the same group
+     Group
+}
+
+/// The values mapped to the ends of the colormap
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColorRange {
+     /// From the values of each frame
+     Auto,
+
+     Fixed {
+          min: f64,
+          max: f64
+     }
+}
+
+// =============================================================================
+// Type
+// =============================================================================
+
+/// Paint the objects according to a physical quantity
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorMapping {
+     pub quantity: Quantity,
+     pub colormap: Colormap,
+     pub range: ColorRange,
+
+     /// Map the logarithm of the values, the others than positive ones being
+     /// put at the start of the colormap
+     pub logarithmic: bool,
+
+     /// Draw the colormap and its range on the side of the screen
+     pub legend: bool,
+
+     /// Gravitational constant in the units of the objects, for the energy
+     gravitational_constant: f64,
+
+     /// Force smoothings of the engine, so the energy is the one it simulates
+     force_smoothings: f64
+}
+
+impl ColorMapping {
+     pub fn new(quantity: Quantity, colormap: Colormap) -> Self {
+          Self {
+               quantity,
+               colormap,
+               range: ColorRange::Auto,
+               logarithmic: false,
+               legend: true,
+               gravitational_constant: G,
+               force_smoothings: 0.0
+          }
+     }
+
+     /// Use another gravitational constant than the one in SI
+     pub fn with_gravitational_constant(mut self, g: f64) -> Self {
+          self.gravitational_constant = g;
+
+          self
+     }
+
+     /// Smooth the potential energy like the forces of the engine
+     pub fn with_force_smoothings(mut self, force_smoothings: f64) -> Self {
+          self.force_smoothings = force_smoothings;
+
+          self
+     }
+
+     /// Value of the quantity for each object, to compute once per snapshot
+     /// as the energy takes a time quadratic in the number of objects. The
+     /// acceleration of the massless objects isn't known from their force, so
+     /// it's NaN, left out of the ranges.
+     pub fn values<V: Vector<f64>>(&self, objects: &[Object<f64, V>]) -> Vec<f64> {
+          let length = |v: V| v.dot(v).sqrt();
+
+          match self.quantity {
+               Quantity::Speed => objects.iter().map(|o| length(o.velocity)).collect(),
+               Quantity::Acceleration => objects
+                    .iter()
+                    .map(|o| {
+                         if o.mass > 0.0 {
+                              length(o.force) / o.mass
+                         } else {
+                              f64::NAN
+                         }
+                    })
+                    .collect(),
+               Quantity::SpecificEnergy => self.compute_specific_energies(objects),
+               Quantity::Mass => objects.iter().map(|o| o.mass).collect(),
+               Quantity::RadialDistance => {
+                    let total = objects.iter().map(|o| o.mass).sum::<f64>();
+                    let center = objects.iter().map(|o| o.location * o.mass).sum::<V>() / total;
+
+                    objects
+                         .iter()
+                         .map(|o| length(o.location - center))
+                         .collect()
+               },
+               Quantity::Group => {
+                    let mut groups = objects.iter().map(|o| o.generator).collect::<Vec<_>>();
+                    groups.sort_unstable();
+                    groups.dedup();
+
+                    objects
+                         .iter()
+                         .map(|o| groups.binary_search(&o.generator).unwrap() as f64)
+                         .collect()
+               }
+          }
+     }
+
+     /// The values mapped to the ends of the colormap
+     pub fn resolve_range(&self, values: &[f64]) -> (f64, f64) {
+          if self.quantity == Quantity::Group {
+               let max = values.iter().copied().fold(0.0, f64::max);
+
+               return (0.0, max);
+          }
+
+          if let ColorRange::Fixed { min, max } = self.range {
+               return (min, max);
+          }
+
+          let mut sorted = values
+               .iter()
+               .copied()
+               .filter(|v| v.is_finite() && (!self.logarithmic || *v > 0.0))
+               .collect::<Vec<_>>();
+          if sorted.is_empty() {
+               return (0.0, 1.0);
+          }
+          sorted.sort_by(f64::total_cmp);
+
+          let last = (sorted.len() - 1) as f64;
+          let at = |f: f64| sorted[(last * f).round() as usize];
+
+          (at(AUTO_RANGE_OUTLIERS), at(1.0 - AUTO_RANGE_OUTLIERS))
+     }
+
+     /// Position of a value in the colormap, between 0 and 1, the unknown
+     /// values being at the start
+     pub fn normalize(&self, value: f64, (min, max): (f64, f64)) -> f64 {
+          if value.is_nan() {
+               return 0.0;
+          }
+
+          let (value, min, max) = if self.logarithmic && self.quantity != Quantity::Group {
+               if value <= 0.0 || min <= 0.0 || max <= 0.0 {
+                    return 0.0;
+               }
+
+               (value.ln(), min.ln(), max.ln())
+          } else {
+               (value, min, max)
+          };
+
+          if max > min {
+               ((value - min) / (max - min)).clamp(0.0, 1.0)
+          } else {
+               0.5
+          }
+     }
+
+     /// Color of each object from its value, with the range used
+     pub fn colors(&self, values: &[f64]) -> (Vec<Color>, (f64, f64)) {
+          let range = self.resolve_range(values);
+
+          let colors = values
+               .iter()
+               .map(|v| self.colormap.sample(self.normalize(*v, range)))
+               .collect();
+
+          (colors, range)
+     }
+
+     /// Kinetic plus potential energy of each object per unit of mass, the
+     /// potential being the one of the objects exerting gravity
+     fn compute_specific_energies<V: Vector<f64>>(&self, objects: &[Object<f64, V>]) -> Vec<f64> {
+          let mut energies = objects
+               .iter()
+               .map(|o| 0.5 * o.velocity.dot(o.velocity))
+               .collect::<Vec<_>>();
+
+          // each pair once
+          for (i, a) in objects.iter().enumerate() {
+               for (j, b) in objects.iter().enumerate().skip(i + 1) {
+                    let r = b.location - a.location;
+                    let potential = self.gravitational_constant
+                         * physics::compute_pair_potential(r.dot(r).sqrt(), self.force_smoothings);
+                    if !potential.is_finite() {
+                         continue;
+                    }
+
+                    if b.kind.exerts_gravity() {
+                         energies[i] += potential * b.mass;
+                    }
+                    if a.kind.exerts_gravity() {
+                         energies[j] += potential * a.mass;
+                    }
+               }
+          }
+
+          energies
+     }
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+impl Display for Quantity {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.write_str(match self {
+               Quantity::Speed => "speed",
+               Quantity::Acceleration => "acceleration",
+               Quantity::SpecificEnergy => "energy",
+               Quantity::Mass => "mass",
+               Quantity::RadialDistance => "radius",
+               Quantity::Group => "group"
+          })
+     }
+}
+
+pub struct QuantityParseError {
+     value: String
+}
+
+impl Debug for QuantityParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.debug_struct("QuantityParseError")
+               .field("value", &self.value)
+               .finish()
+     }
+}
+
+impl Display for QuantityParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          write!(
+               f,
+               "'{}' is not a quantity the objects can be colored by.",
+               self.value
+          )
+     }
+}
+
+impl std::error::Error for QuantityParseError {}
+
+impl FromStr for Quantity {
+     type Err = QuantityParseError;
+
+     fn from_str(s: &str) -> Result<Self, Self::Err> {
+          match s {
+               "speed" => Ok(Quantity::Speed),
+               "acceleration" => Ok(Quantity::Acceleration),
+               "energy" => Ok(Quantity::SpecificEnergy),
+               "mass" => Ok(Quantity::Mass),
+               "radius" => Ok(Quantity::RadialDistance),
+               "group" => Ok(Quantity::Group),
+               _ => Err(QuantityParseError {
+                    value: s.to_string()
+               })
+          }
+     }
+}
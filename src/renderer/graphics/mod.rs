@@ -14,25 +14,45 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+#[cfg(test)]
+mod test;
+
 mod draw;
+pub mod mapping;
 pub mod radius;
+pub mod trails;
 pub mod vectors;
 
-use sdl2::{pixels::Color, render::Canvas, video::Window};
+use sdl2::pixels::Color;
 
 use self::{
+     mapping::{ColorMapping, Quantity},
      radius::RadiusType,
-     vectors::{ForceLengthType, VelocityLengthType}
+     vectors::{ForceLengthType, VectorStyle, VelocityLengthType}
+};
+use super::{
+     density::{self, DensitySettings},
+     target::{BlendMode, RenderTarget},
+     viewport::Viewport
 };
-use super::viewport::Viewport;
 use crate::{
      common::{
           maths,
-          vec2::{Vec2F, VecLength}
+          vec2::{Vec2, Vec2F, VecLength},
+          vector::Vector
      },
      simulation::object::Object
 };
 
+/// Width of the color bar, in pixels
+const COLOR_BAR_WIDTH: f64 = 12.0;
+
+/// Distance between the color bar and the edges of the screen, in pixels
+const COLOR_BAR_MARGIN: f64 = 16.0;
+
+/// Part of the height of the screen the color bar takes
+const COLOR_BAR_HEIGHT: f64 = 0.4;
+
 // =============================================================================
 // Type
 // =============================================================================
@@ -40,7 +60,16 @@ use crate::{
 pub struct Graphics {
      radius_type: RadiusType,
      velocity: Option<VelocityLengthType>,
-     force: Option<ForceLengthType>
+     force: Option<ForceLengthType>,
+     velocity_style: VectorStyle,
+     force_style: VectorStyle,
+
+     /// Whether the vectors are drawn, when they have a length type
+     show_velocity: bool,
+     show_force: bool,
+
+     color_mapping: Option<ColorMapping>,
+     density: Option<DensitySettings>
 }
 
 impl Graphics {
@@ -52,9 +81,66 @@ impl Graphics {
           Self {
                radius_type,
                velocity,
-               force
+               force,
+               velocity_style: VectorStyle::default(),
+               force_style: VectorStyle::default(),
+               show_velocity: true,
+               show_force: true,
+               color_mapping: None,
+               density: None
           }
      }
+
+     /// Change how the velocities are drawn
+     pub fn with_velocity_style(mut self, style: VectorStyle) -> Self {
+          self.velocity_style = style;
+
+          self
+     }
+
+     /// Change how the forces are drawn
+     pub fn with_force_style(mut self, style: VectorStyle) -> Self {
+          self.force_style = style;
+
+          self
+     }
+
+     /// Paint the objects according to a quantity instead of their color
+     pub fn with_color_mapping(mut self, mapping: ColorMapping) -> Self {
+          self.color_mapping = Some(mapping);
+
+          self
+     }
+
+     /// Values of the color mapping for each object, none without mapping or
+     /// when the density is drawn
+     pub fn mapped_values<V: Vector<f64>>(&self, objects: &[Object<f64, V>]) -> Option<Vec<f64>> {
+          match (&self.color_mapping, &self.density) {
+               (Some(mapping), None) => Some(mapping.values(objects)),
+               _ => None
+          }
+     }
+
+     /// Show or hide the velocities, returning whether they are shown
+     pub fn toggle_velocity(&mut self) -> bool {
+          self.show_velocity = !self.show_velocity;
+
+          self.show_velocity
+     }
+
+     /// Show or hide the forces, returning whether they are shown
+     pub fn toggle_force(&mut self) -> bool {
+          self.show_force = !self.show_force;
+
+          self.show_force
+     }
+
+     /// Draw the density of the objects instead of the objects
+     pub fn with_density(mut self, density: DensitySettings) -> Self {
+          self.density = Some(density);
+
+          self
+     }
 }
 
 // =============================================================================
@@ -63,15 +149,14 @@ impl Graphics {
 
 macro_rules! draw_vector_option {
      (
-          $canvas: ident,
+          $target: ident,
           $settings: ident,
           $attribute: ident,
-          $factor: expr,
+          $style: ident,
           $enum: ident,
           $object: ident,
           $object_attribute: ident,
           $object_origin: ident,
-          $color: expr,
           $viewport: ident
     ) => {
           if let Some(v) = &$settings.$attribute {
@@ -85,29 +170,31 @@ macro_rules! draw_vector_option {
                     };
 
                     let f_vector = $object.location + p2;
-                    let f_vector_scaled = f_vector * $viewport.scale + $viewport.shift;
+                    let f_vector_scaled = $viewport.world_to_screen(f_vector);
 
-                    // draw it's force
-                    draw::draw_line_u32(
-                         $canvas,
+                    // draw it's arrow
+                    let style = &$settings.$style;
+                    draw::draw_arrow(
+                         $target,
                          $object_origin,
-                         f_vector_scaled.convert(|v| v as i32),
-                         $color
-                    );
+                         f_vector_scaled,
+                         style.thickness * $viewport.pixel_ratio,
+                         style.head_size * $viewport.pixel_ratio,
+                         style.color
+                    )?;
                }
           }
      };
 }
 
-/// Draw an object in the canvas
-pub fn draw_object(
-     canvas: &mut Canvas<Window>,
+/// Draw an object in the target
+pub fn draw_object<T: RenderTarget>(
+     target: &mut T,
      object: &Object,
      settings: &Graphics,
      viewport: &Viewport
-) {
-     let location = object.location * viewport.scale + viewport.shift;
-     let p = location.convert(|v| v as i32);
+) -> Result<(), String> {
+     let location = viewport.world_to_screen(object.location);
 
      // compute the radius
      let r = match &settings.radius_type {
@@ -117,33 +204,159 @@ pub fn draw_object(
      let radius = viewport.scale * r;
 
      // draw the object
-     draw::draw_point_f64_radius_f64(canvas, location, radius, object.color);
+     draw::draw_disc(target, location, radius, object.color)?;
 
      // draw it's force if requested
-     draw_vector_option!(
-          canvas,
-          settings,
-          force,
-          10.0e8,
-          ForceLengthType,
-          object,
-          force,
-          p,
-          Color::WHITE,
-          viewport
-     );
+     if settings.show_force {
+          draw_vector_option!(
+               target,
+               settings,
+               force,
+               force_style,
+               ForceLengthType,
+               object,
+               force,
+               location,
+               viewport
+          );
+     }
 
      // draw it's velocity if requested
-     draw_vector_option!(
-          canvas,
-          settings,
-          velocity,
-          10.0e5,
-          VelocityLengthType,
-          object,
-          velocity,
-          p,
-          Color::WHITE,
-          viewport
-     );
+     if settings.show_velocity {
+          draw_vector_option!(
+               target,
+               settings,
+               velocity,
+               velocity_style,
+               VelocityLengthType,
+               object,
+               velocity,
+               location,
+               viewport
+          );
+     }
+
+     Ok(())
+}
+
+/// Draw all the objects in the target, from the farthest to the nearest, with
+/// the legend of their colors if any, or their density if asked. The colors
+/// are mapped from `values`, given by [`Graphics::mapped_values`].
+pub fn draw_objects<T: RenderTarget, V: Vector<f64>>(
+     target: &mut T,
+     objects: &[Object<f64, V>],
+     values: Option<&[f64]>,
+     settings: &Graphics,
+     viewport: &Viewport
+) -> Result<(), String> {
+     if let Some(density) = &settings.density {
+          return density::draw_density(target, objects, density, viewport);
+     }
+
+     let mapped = settings
+          .color_mapping
+          .as_ref()
+          .zip(values)
+          .map(|(mapping, values)| (mapping, mapping.colors(values)));
+
+     let mut projected = objects
+          .iter()
+          .enumerate()
+          .map(|(i, o)| {
+               let (depth, mut projected) = viewport.project(o);
+               if let Some((_, (colors, _))) = &mapped {
+                    projected.color = colors[i];
+               }
+
+               (depth, projected)
+          })
+          .collect::<Vec<_>>();
+     projected.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+     for (_, o) in projected.iter() {
+          draw_object(target, o, settings, viewport)?;
+     }
+
+     if let Some((mapping, (_, range))) = mapped {
+          if mapping.legend {
+               draw_color_bar(target, mapping, range, viewport)?;
+          }
+     }
+
+     Ok(())
+}
+
+/// Draw the colormap on the right of the target, with the values of its ends
+/// unless it shows groups
+pub fn draw_color_bar<T: RenderTarget>(
+     target: &mut T,
+     mapping: &ColorMapping,
+     range: (f64, f64),
+     viewport: &Viewport
+) -> Result<(), String> {
+     let size = target.size()?;
+     let ratio = viewport.pixel_ratio;
+
+     let width = (COLOR_BAR_WIDTH * ratio).round() as u32;
+     let height = (size.y as f64 * COLOR_BAR_HEIGHT).round() as u32;
+     let margin = (COLOR_BAR_MARGIN * ratio).round() as i32;
+     let corner = Vec2::new(size.x as i32 - margin - width as i32, margin);
+     if height < 2 {
+          return Ok(());
+     }
+
+     let previous = target.blend_mode();
+     target.set_blend_mode(BlendMode::None);
+
+     // the highest values at the top
+     let groups = (mapping.quantity == Quantity::Group).then(|| range.1.round() as u32 + 1);
+     for row in 0..height {
+          let t = 1.0 - row as f64 / (height - 1) as f64;
+          let t = match groups {
+               Some(groups) => {
+                    let group = (t * groups as f64).floor().min((groups - 1) as f64);
+
+                    mapping.normalize(group, range)
+               },
+               None => t
+          };
+
+          target.fill_rect(
+               Vec2::new(corner.x, corner.y + row as i32),
+               width,
+               1,
+               mapping.colormap.sample(t)
+          )?;
+     }
+
+     if groups.is_none() {
+          let scale = (2.0 * ratio).round().max(1.0) as u32;
+          let gap = (4.0 * ratio).round() as i32;
+          let label_height = (draw::GLYPH_SIZE.y * scale) as i32;
+
+          for (value, y) in [
+               (range.1, corner.y),
+               (range.0, corner.y + height as i32 - label_height)
+          ] {
+               let text = format_value(value);
+               let x = corner.x - gap - draw::text_width(&text, scale) as i32;
+
+               draw::draw_text(target, Vec2::new(x, y), &text, scale, Color::WHITE)?;
+          }
+     }
+
+     target.set_blend_mode(previous);
+
+     Ok(())
+}
+
+/// Write a value with few characters, in scientific notation if needed
+fn format_value(value: f64) -> String {
+     if value == 0.0 || (1e-2..1e4).contains(&value.abs()) {
+          let text = format!("{:.2}", value);
+
+          text.trim_end_matches('0').trim_end_matches('.').to_string()
+     } else {
+          format!("{:.1e}", value)
+     }
 }
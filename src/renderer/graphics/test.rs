@@ -0,0 +1,291 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::pixels::Color;
+
+use super::{
+     draw_objects,
+     format_value,
+     mapping::{ColorMapping, ColorRange, Quantity},
+     radius::RadiusType,
+     trails::{self, Trails},
+     vectors::VelocityLengthType,
+     Graphics
+};
+use crate::{
+     common::{constants::G, vec2::Vec2F, vec3::Vec3F},
+     renderer::{
+          colormap::Colormap,
+          target::{Framebuffer, RenderTarget},
+          viewport::Viewport
+     },
+     simulation::object::{GeneratorId, Object, ObjectKind}
+};
+
+fn object(mass: f64, location: Vec2F, velocity: Vec2F, color: Color) -> Object {
+     Object::new(
+          mass,
+          location,
+          Vec2F::new(0.0, mass * 3.0),
+          velocity,
+          true,
+          color
+     )
+     .unwrap()
+}
+
+fn assert_close(values: &[f64], expected: &[f64]) {
+     assert_eq!(values.len(), expected.len());
+
+     for (v, e) in values.iter().zip(expected) {
+          assert!(
+               (v - e).abs() <= 1e-9 * e.abs().max(1.0),
+               "{:?} instead of {:?}",
+               values,
+               expected
+          );
+     }
+}
+
+#[test]
+fn test_mapped_values() {
+     let generator = GeneratorId::generate();
+     let objects = [
+          object(1.0, Vec2F::new(0.0, 0.0), Vec2F::new(3.0, 4.0), Color::RED)
+               .with_generator(generator),
+          object(3.0, Vec2F::new(4.0, 0.0), Vec2F::new(0.0, 1.0), Color::RED),
+          object(2.0, Vec2F::new(8.0, 0.0), Vec2F::new_null(), Color::CYAN)
+               .with_generator(generator)
+     ];
+     let values = |quantity| ColorMapping::new(quantity, Colormap::Viridis).values(&objects);
+
+     assert_close(&values(Quantity::Speed), &[5.0, 1.0, 0.0]);
+     assert_close(&values(Quantity::Acceleration), &[3.0, 3.0, 3.0]);
+     assert_close(&values(Quantity::Mass), &[1.0, 3.0, 2.0]);
+
+     // unknown for the massless objects, left out of the ranges
+     let tracer = Object::new_inactive_of_kind(
+          ObjectKind::TestParticle,
+          0.0,
+          Vec2F::new_null(),
+          true,
+          Color::WHITE
+     )
+     .unwrap();
+     let mapping = ColorMapping::new(Quantity::Acceleration, Colormap::Viridis);
+     let accelerations = mapping.values(&[objects[0].clone(), tracer]);
+     assert!(accelerations[1].is_nan());
+     assert_eq!(mapping.resolve_range(&accelerations), (3.0, 3.0));
+     assert_eq!(mapping.normalize(accelerations[1], (0.0, 3.0)), 0.0);
+
+     // around the center of mass at x = 4.67
+     let center = (4.0 * 3.0 + 8.0 * 2.0) / 6.0;
+     assert_close(
+          &values(Quantity::RadialDistance),
+          &[center, center - 4.0, 8.0 - center]
+     );
+
+     // by generator whatever their color, the objects without one first
+     assert_close(&values(Quantity::Group), &[1.0, 0.0, 1.0]);
+
+     // in the units of the mapping
+     let energy = ColorMapping::new(Quantity::SpecificEnergy, Colormap::Viridis)
+          .with_gravitational_constant(2.0)
+          .values(&objects);
+     assert_close(
+          &energy,
+          &[
+               12.5 - 2.0 * (3.0 / 4.0 + 2.0 / 8.0),
+               0.5 - 2.0 * (1.0 / 4.0 + 2.0 / 4.0),
+               -2.0 * (1.0 / 8.0 + 3.0 / 4.0)
+          ]
+     );
+     let si = ColorMapping::new(Quantity::SpecificEnergy, Colormap::Viridis).values(&objects);
+     assert_close(&si[2..], &[-G * (1.0 / 8.0 + 3.0 / 4.0)]);
+
+     // smoothed like the forces of the engine
+     let smoothed = ColorMapping::new(Quantity::SpecificEnergy, Colormap::Viridis)
+          .with_gravitational_constant(2.0)
+          .with_force_smoothings(9.0)
+          .values(&objects);
+     assert_close(
+          &smoothed[2..],
+          &[-2.0 * ((3.0 / 8.0_f64).atan() / 3.0 + 3.0 * (3.0 / 4.0_f64).atan() / 3.0)]
+     );
+
+     // in space as well
+     let objects = [Object::new(
+          1.0,
+          Vec3F::new(0.0, 0.0, 1.0),
+          Vec3F::new_null(),
+          Vec3F::new(1.0, 2.0, 2.0),
+          true,
+          Color::RED
+     )
+     .unwrap()];
+     assert_close(
+          &ColorMapping::new(Quantity::Speed, Colormap::Viridis).values(&objects),
+          &[3.0]
+     );
+}
+
+#[test]
+fn test_ranges() {
+     let mut mapping = ColorMapping::new(Quantity::Mass, Colormap::Viridis);
+
+     // the extreme values are left out
+     let mut values = (1..=199).map(|v| v as f64).collect::<Vec<_>>();
+     values.push(1e9);
+     values.push(f64::NAN);
+     assert_eq!(mapping.resolve_range(&values), (3.0, 198.0));
+     assert_eq!(mapping.normalize(101.0, (1.0, 201.0)), 0.5);
+     assert_eq!(mapping.normalize(1e9, (1.0, 201.0)), 1.0);
+
+     mapping.logarithmic = true;
+     assert_eq!(mapping.normalize(10.0, (1.0, 100.0)), 0.5);
+     assert_eq!(mapping.normalize(-10.0, (1.0, 100.0)), 0.0);
+     assert_eq!(mapping.resolve_range(&[-1.0, 0.0, 2.0, 8.0]), (2.0, 8.0));
+
+     mapping.range = ColorRange::Fixed {
+          min: 10.0,
+          max: 1000.0
+     };
+     assert_eq!(mapping.resolve_range(&values), (10.0, 1000.0));
+
+     // a single value in the middle
+     assert_eq!(mapping.normalize(5.0, (5.0, 5.0)), 0.5);
+}
+
+#[test]
+fn test_draw_mapped_objects() {
+     let objects = [
+          object(1.0, Vec2F::new(10.0, 10.0), Vec2F::new_null(), Color::RED),
+          object(
+               1.0,
+               Vec2F::new(30.0, 10.0),
+               Vec2F::new(2.0, 0.0),
+               Color::RED
+          )
+     ];
+
+     let mut mapping = ColorMapping::new(Quantity::Speed, Colormap::Magma);
+     mapping.range = ColorRange::Fixed { min: 0.0, max: 2.0 };
+     let graphics =
+          Graphics::new(RadiusType::Constant(3.0), None, None).with_color_mapping(mapping);
+     let viewport = Viewport::new(1.0, Vec2F::new_null());
+
+     let mut target = Framebuffer::new(100, 50);
+     target.clear(Color::BLACK);
+     let values = graphics.mapped_values(&objects);
+     draw_objects(
+          &mut target,
+          &objects,
+          values.as_deref(),
+          &graphics,
+          &viewport
+     )
+     .unwrap();
+
+     assert_eq!(target.pixel(10, 10), Some(Colormap::Magma.sample(0.0)));
+     assert_eq!(target.pixel(30, 10), Some(Colormap::Magma.sample(1.0)));
+
+     // the color bar on the right, the highest values at the top
+     let (x, top, bottom) = (100 - 16 - 6, 16, 16 + 20 - 1);
+     assert_eq!(target.pixel(x, top), Some(Colormap::Magma.sample(1.0)));
+     assert_eq!(target.pixel(x, bottom), Some(Colormap::Magma.sample(0.0)));
+     assert_eq!(target.pixel(x, bottom + 1), Some(Color::BLACK));
+
+     // with the values of its ends on its left
+     let labels = (0..(100 - 16 - 12)).filter(|x| target.pixel(*x, top) == Some(Color::WHITE));
+     assert!(labels.count() > 0);
+}
+
+#[test]
+fn test_format_value() {
+     assert_eq!(format_value(0.0), "0");
+     assert_eq!(format_value(2.5), "2.5");
+     assert_eq!(format_value(-120.0), "-120");
+     assert_eq!(format_value(1.5e7), "1.5e7");
+     assert_eq!(format_value(-3.2e-5), "-3.2e-5");
+}
+
+#[test]
+fn test_trails() {
+     let star = |x: f64, color| object(1.0, Vec2F::new(x, 8.5), Vec2F::new_null(), color);
+     let mut trails = Trails::new(3);
+
+     let (mut a, mut b) = (star(2.0, Color::RED), star(2.0, Color::CYAN));
+     for x in [2.0, 6.0, 10.0, 14.0] {
+          a.location.x = x;
+          b.location.x = x;
+          trails.record(&[a.clone(), b.clone()]);
+     }
+
+     // only the last locations are kept
+     let locations = trails.locations(a.id).unwrap();
+     assert_eq!(
+          locations.iter().map(|l| l.x).collect::<Vec<_>>(),
+          [6.0, 10.0, 14.0]
+     );
+
+     let mut target = Framebuffer::new(20, 16);
+     target.clear(Color::BLACK);
+     trails::draw_trails(
+          &mut target,
+          &trails,
+          &[a.clone()],
+          &Viewport::new(1.0, Vec2F::new_null())
+     )
+     .unwrap();
+
+     // fading toward the oldest location
+     let red = |x| target.pixel(x, 8).unwrap().r;
+     assert!(red(13) > red(8) && red(8) > 0, "{} {}", red(13), red(8));
+     assert_eq!(red(4), 0);
+
+     // and the objects gone are forgotten
+     trails.record(&[a.clone()]);
+     assert!(trails.locations(b.id).is_none());
+}
+
+#[test]
+fn test_toggled_vectors() {
+     let mut graphics = Graphics::new(
+          RadiusType::Constant(1.0),
+          Some(VelocityLengthType::Constant(10.0)),
+          None
+     );
+     let objects = [object(
+          1.0,
+          Vec2F::new(4.5, 8.5),
+          Vec2F::new(3.0, 0.0),
+          Color::RED
+     )];
+     let viewport = Viewport::new(1.0, Vec2F::new_null());
+
+     let draw = |graphics: &Graphics| {
+          let mut target = Framebuffer::new(32, 16);
+          draw_objects(&mut target, &objects, None, graphics, &viewport).unwrap();
+
+          target.pixel(10, 8).unwrap().a
+     };
+
+     assert!(draw(&graphics) > 0);
+     assert!(!graphics.toggle_velocity());
+     assert_eq!(draw(&graphics), 0);
+     assert!(graphics.toggle_velocity());
+     assert!(draw(&graphics) > 0);
+}
@@ -0,0 +1,108 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::draw;
+use crate::{
+     common::{vec2::Vec2F, vector::Vector},
+     renderer::{target::RenderTarget, viewport::Viewport},
+     simulation::object::{Object, ObjectId}
+};
+
+/// Thickness of the trails, in pixels of the drawings
+const TRAIL_THICKNESS: f64 = 1.0;
+
+/// Opacity of the newest part of the trails, the oldest being transparent
+const TRAIL_ALPHA: f64 = 0.6;
+
+/// The last locations of the objects, drawn behind them
+pub struct Trails<V: Vector<f64>> {
+     /// Number of locations kept for each object
+     length: usize,
+
+     locations: HashMap<ObjectId, VecDeque<V>>
+}
+
+impl<V: Vector<f64>> Trails<V> {
+     pub fn new(length: usize) -> Self {
+          Self {
+               length,
+               locations: HashMap::new()
+          }
+     }
+
+     /// Add the current locations of the objects, forgetting the ones which
+     /// don't exist anymore
+     pub fn record(&mut self, objects: &[Object<f64, V>]) {
+          let ids = objects.iter().map(|o| o.id).collect::<HashSet<_>>();
+          self.locations.retain(|id, _| ids.contains(id));
+
+          for o in objects.iter() {
+               let locations = self.locations.entry(o.id).or_default();
+
+               locations.push_back(o.location);
+               while locations.len() > self.length {
+                    locations.pop_front();
+               }
+          }
+     }
+
+     /// Locations of an object, from the oldest
+     pub fn locations(&self, id: ObjectId) -> Option<&VecDeque<V>> {
+          self.locations.get(&id)
+     }
+
+     pub fn clear(&mut self) {
+          self.locations.clear();
+     }
+}
+
+/// Draw the trails of the objects with their colors, fading with their age
+pub fn draw_trails<T: RenderTarget, V: Vector<f64>>(
+     target: &mut T,
+     trails: &Trails<V>,
+     objects: &[Object<f64, V>],
+     viewport: &Viewport
+) -> Result<(), String> {
+     for o in objects.iter() {
+          let locations = match trails.locations(o.id) {
+               Some(l) if l.len() > 1 => l,
+               _ => continue
+          };
+
+          let points = locations
+               .iter()
+               .map(|l| viewport.to_screen(*l))
+               .collect::<Vec<Vec2F>>();
+          let count = points.len() - 1;
+
+          for (i, segment) in points.windows(2).enumerate() {
+               let mut color = o.color;
+               color.a = (TRAIL_ALPHA * 255.0 * (i + 1) as f64 / count as f64).round() as u8;
+
+               draw::draw_line(
+                    target,
+                    segment[0],
+                    segment[1],
+                    TRAIL_THICKNESS * viewport.pixel_ratio,
+                    color
+               )?;
+          }
+     }
+
+     Ok(())
+}
@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use sdl2::pixels::Color;
+
 // =============================================================================
 // Enums
 // =============================================================================
@@ -31,3 +33,33 @@ pub enum ForceLengthType {
      #[allow(unused)]
      FromValueFactor(f64)
 }
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/// How a vector is drawn, the sizes being in pixels
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct VectorStyle {
+     pub color: Color,
+     pub thickness: f64,
+
+     /// Length of the sides of the arrowhead, no head being drawn if zero
+     pub head_size: f64
+}
+
+impl VectorStyle {
+     pub fn new(color: Color, thickness: f64, head_size: f64) -> Self {
+          Self {
+               color,
+               thickness,
+               head_size
+          }
+     }
+}
+
+impl Default for VectorStyle {
+     fn default() -> Self {
+          Self::new(Color::WHITE, 1.0, 4.0)
+     }
+}
@@ -0,0 +1,222 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use std::{
+     fs::{self, File},
+     io::{BufWriter, Write},
+     path::{Path, PathBuf},
+     sync::mpsc::Receiver
+};
+
+use anyhow::Context;
+use sdl2::pixels::Color;
+
+use super::{
+     graphics::{self, Graphics},
+     target::{Framebuffer, RenderTarget},
+     video::{self, VideoFormat, VideoSettings, VideoWriter},
+     viewport::Viewport
+};
+use crate::{
+     common::{units::UnitSystem, vec2::Vec2, vector::Vector},
+     simulation::{object::Object, thread::Snapshot}
+};
+
+// =============================================================================
+// Settings
+// =============================================================================
+
+/// How the frames are rendered without window
+pub struct HeadlessSettings {
+     /// Video file the frames are written to if it ends with `.gif` or `.y4m`,
+     /// else directory of the PNG images, created if needed
+     pub output: PathBuf,
+
+     /// Size of the images, in pixels
+     pub size: Vec2<u32>,
+
+     /// Number of frames to render
+     pub frames: usize,
+
+     /// The frames are drawn this many times larger, then shrunk to smooth
+     /// the edges
+     pub supersampling: u32,
+
+     /// Number of snapshots of the engine between two frames
+     pub frame_interval: usize,
+
+     /// How the frames are encoded when written in a video
+     pub video: VideoSettings
+}
+
+impl HeadlessSettings {
+     pub fn new(output: PathBuf, size: Vec2<u32>, frames: usize, video: VideoSettings) -> Self {
+          Self {
+               output,
+               size,
+               frames,
+               supersampling: 1,
+               frame_interval: 1,
+               video
+          }
+     }
+}
+
+// =============================================================================
+// Functions
+// =============================================================================
+
+/// Draw the objects in an image the same way the window does, `values` being
+/// the ones of the color mapping
+pub fn render_frame<V: Vector<f64>>(
+     objects: &[Object<f64, V>],
+     values: Option<&[f64]>,
+     graphics: &Graphics,
+     viewport: &Viewport,
+     size: Vec2<u32>,
+     supersampling: u32
+) -> anyhow::Result<Framebuffer> {
+     let supersampling = supersampling.max(1);
+     let viewport = viewport.scaled(supersampling as f64);
+
+     let mut target = Framebuffer::new(size.x * supersampling, size.y * supersampling);
+     target.clear(Color::BLACK);
+
+     graphics::draw_objects(&mut target, objects, values, graphics, &viewport)
+          .map_err(|e| anyhow::anyhow!(e))?;
+
+     Ok(target.downsample(supersampling))
+}
+
+/// Encode an image as an RGBA PNG
+pub fn write_png<W: Write>(writer: W, image: &Framebuffer) -> anyhow::Result<()> {
+     let mut encoder = png::Encoder::new(writer, image.width(), image.height());
+     encoder.set_color(png::ColorType::Rgba);
+     encoder.set_depth(png::BitDepth::Eight);
+
+     let mut writer = encoder.write_header()?;
+     writer.write_image_data(image.pixels())?;
+
+     Ok(())
+}
+
+/// Path of the numbered frame in the directory
+pub fn frame_path(directory: &Path, index: usize) -> PathBuf {
+     directory.join(format!("frame_{:05}.png", index))
+}
+
+/// Where the frames are written
+enum Output {
+     Images(PathBuf),
+     Video(VideoWriter<BufWriter<File>>)
+}
+
+impl Output {
+     fn create(settings: &HeadlessSettings) -> anyhow::Result<Self> {
+          let path = &settings.output;
+
+          if VideoFormat::from_path(path).is_some() {
+               return Ok(Output::Video(video::create(
+                    path,
+                    settings.size,
+                    settings.video
+               )?));
+          }
+
+          fs::create_dir_all(path)
+               .with_context(|| format!("Couldn't create the directory {}.", path.display()))?;
+
+          Ok(Output::Images(path.clone()))
+     }
+
+     fn write(&mut self, image: &Framebuffer, index: usize) -> anyhow::Result<()> {
+          match self {
+               Output::Images(directory) => {
+                    let path = frame_path(directory, index);
+                    let file = File::create(&path)
+                         .with_context(|| format!("Couldn't create {}.", path.display()))?;
+
+                    write_png(BufWriter::new(file), image)
+                         .with_context(|| format!("Couldn't write {}.", path.display()))
+               },
+               Output::Video(writer) => writer.write_frame(image)
+          }
+     }
+
+     fn finish(self) -> anyhow::Result<()> {
+          if let Output::Video(writer) = self {
+               writer.finish()?;
+          }
+
+          Ok(())
+     }
+}
+
+/// Render the initial objects then the snapshots sent by the engine thread
+/// through `receiver` as numbered PNG images or as a video, the events being
+/// printed in `units`. Stops the engine once all the frames are written.
+pub fn run<V: Vector<f64>>(
+     objects: Vec<Object<f64, V>>,
+     receiver: Receiver<Snapshot<V>>,
+     units: UnitSystem,
+     graphics: &Graphics,
+     viewport: &Viewport,
+     settings: &HeadlessSettings
+) -> anyhow::Result<()> {
+     let render = |objects: &[Object<f64, V>]| {
+          render_frame(
+               objects,
+               graphics.mapped_values(objects).as_deref(),
+               graphics,
+               viewport,
+               settings.size,
+               settings.supersampling
+          )
+     };
+
+     if settings.frames == 0 {
+          return Ok(());
+     }
+
+     let mut output = Output::create(settings)?;
+     output.write(&render(&objects)?, 0)?;
+
+     let interval = settings.frame_interval.max(1);
+     let mut index = 1;
+     for (i, snapshot) in receiver.iter().enumerate() {
+          for event in snapshot.events.iter() {
+               println!("{}", event.display(&units));
+          }
+
+          if (i + 1) % interval != 0 {
+               continue;
+          }
+          if index == settings.frames {
+               break;
+          }
+
+          output.write(&render(&snapshot.objects)?, index)?;
+          index += 1;
+     }
+     output.finish()?;
+
+     println!("{} frames written to {}.", index, settings.output.display());
+
+     Ok(())
+}
@@ -0,0 +1,191 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{env, fs, fs::File, sync::mpsc};
+
+use sdl2::pixels::Color;
+
+use super::{frame_path, render_frame, run, write_png, HeadlessSettings};
+use crate::{
+     common::{
+          units::UnitSystem,
+          vec2::{Vec2, Vec2F}
+     },
+     renderer::{
+          graphics::{radius::RadiusType, Graphics},
+          video::{Palette, VideoSettings},
+          viewport::Viewport
+     },
+     simulation::{object::Object, thread::Snapshot}
+};
+
+fn star(location: Vec2F) -> Object {
+     Object::new(
+          1.0,
+          location,
+          Vec2F::new_null(),
+          Vec2F::new_null(),
+          true,
+          Color::WHITE
+     )
+     .unwrap()
+}
+
+#[test]
+fn test_supersampling() {
+     let graphics = Graphics::new(RadiusType::Constant(1.0), None, None);
+     let viewport = Viewport::new(1.0, Vec2F::new_null());
+     let objects = [star(Vec2F::new(8.5, 8.5))];
+
+     let image = render_frame(&objects, None, &graphics, &viewport, Vec2::new(16, 16), 1).unwrap();
+     let smooth = render_frame(&objects, None, &graphics, &viewport, Vec2::new(16, 16), 4).unwrap();
+
+     // the same region is drawn, the pixels half covered being half lit
+     for image in [image, smooth] {
+          assert_eq!(image.width(), 16);
+          assert_eq!(image.height(), 16);
+          assert_eq!(image.pixel(0, 0), Some(Color::BLACK));
+          assert_eq!(image.pixel(8, 8), Some(Color::WHITE));
+
+          for (x, y) in [(7, 8), (9, 8), (8, 7), (8, 9)] {
+               let edge = image.pixel(x, y).unwrap();
+               assert!((64..192).contains(&edge.r), "{:?} at ({}, {})", edge, x, y);
+          }
+     }
+}
+
+#[test]
+fn test_png_round_trip() {
+     let graphics = Graphics::new(RadiusType::Constant(2.0), None, None);
+     let viewport = Viewport::new(1.0, Vec2F::new_null());
+     let image = render_frame(
+          &[star(Vec2F::new(5.0, 3.0))],
+          None,
+          &graphics,
+          &viewport,
+          Vec2::new(12, 7),
+          2
+     )
+     .unwrap();
+
+     let mut bytes = Vec::new();
+     write_png(&mut bytes, &image).unwrap();
+
+     let mut reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+     let mut buffer = vec![0; reader.output_buffer_size()];
+     let info = reader.next_frame(&mut buffer).unwrap();
+
+     assert_eq!((info.width, info.height), (12, 7));
+     assert_eq!(info.color_type, png::ColorType::Rgba);
+     assert_eq!(&buffer[..info.buffer_size()], image.pixels());
+}
+
+#[test]
+fn test_frame_interval() {
+     let directory = env::temp_dir().join(format!("galaxyx_frames_{}", std::process::id()));
+     let _ = fs::remove_dir_all(&directory);
+
+     let graphics = Graphics::new(RadiusType::Constant(1.0), None, None);
+     let viewport = Viewport::new(1.0, Vec2F::new_null());
+
+     // one object moving by one pixel per snapshot
+     let (sender, receiver) = mpsc::channel();
+     for i in 1..=10 {
+          sender
+               .send(Snapshot {
+                    objects: vec![star(Vec2F::new(i as f64 + 0.5, 2.5))],
+                    events: Vec::new()
+               })
+               .unwrap();
+     }
+     drop(sender);
+
+     let video = VideoSettings::new(25, Palette::WebSafe);
+     let mut settings = HeadlessSettings::new(directory.clone(), Vec2::new(16, 4), 3, video);
+     settings.frame_interval = 4;
+     run(
+          vec![star(Vec2F::new(0.5, 2.5))],
+          receiver,
+          UnitSystem::Si,
+          &graphics,
+          &viewport,
+          &settings
+     )
+     .unwrap();
+
+     // the initial state, then every 4 snapshots
+     for (index, x) in [(0, 0), (1, 4), (2, 8)] {
+          let decoder = png::Decoder::new(File::open(frame_path(&directory, index)).unwrap());
+          let mut reader = decoder.read_info().unwrap();
+          let mut buffer = vec![0; reader.output_buffer_size()];
+          reader.next_frame(&mut buffer).unwrap();
+
+          let i = (2 * 16 + x) * 4;
+          assert_eq!(
+               &buffer[i..(i + 4)],
+               &[255, 255, 255, 255],
+               "frame {}",
+               index
+          );
+     }
+     assert!(!frame_path(&directory, 3).exists());
+
+     fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_video_output() {
+     let path = env::temp_dir().join(format!("galaxyx_run_{}.gif", std::process::id()));
+
+     let graphics = Graphics::new(RadiusType::Constant(1.0), None, None);
+     let viewport = Viewport::new(1.0, Vec2F::new_null());
+
+     let (sender, receiver) = mpsc::channel();
+     for i in 1..=4 {
+          sender
+               .send(Snapshot {
+                    objects: vec![star(Vec2F::new(i as f64 + 0.5, 2.5))],
+                    events: Vec::new()
+               })
+               .unwrap();
+     }
+     drop(sender);
+
+     let video = VideoSettings::new(10, Palette::Adaptive { colors: 256 });
+     let settings = HeadlessSettings::new(path.clone(), Vec2::new(16, 4), 10, video);
+     run(
+          vec![star(Vec2F::new(0.5, 2.5))],
+          receiver,
+          UnitSystem::Si,
+          &graphics,
+          &viewport,
+          &settings
+     )
+     .unwrap();
+
+     // the engine stopped before all the frames were rendered
+     let mut decoder = gif::DecodeOptions::new()
+          .read_info(File::open(&path).unwrap())
+          .unwrap();
+     let mut frames = 0;
+     while let Some(frame) = decoder.read_next_frame().unwrap() {
+          assert_eq!(frame.delay, 10);
+          frames += 1;
+     }
+     assert_eq!(frames, 5);
+
+     fs::remove_file(&path).unwrap();
+}
@@ -14,7 +14,12 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+pub mod colormap;
+pub mod density;
 pub mod graphics;
+pub mod headless;
+pub mod target;
+pub mod video;
 pub mod viewport;
 pub mod window;
 
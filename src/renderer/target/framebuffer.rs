@@ -0,0 +1,202 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::pixels::Color;
+
+use super::{BlendMode, RenderTarget};
+use crate::common::vec2::Vec2;
+
+/// An RGBA image in memory, drawn into without any window
+pub struct Framebuffer {
+     width: u32,
+     height: u32,
+
+     /// Four bytes per pixel, row after row from the top
+     pixels: Vec<u8>,
+
+     blend_mode: BlendMode
+}
+
+impl Framebuffer {
+     /// Create a transparent black image
+     pub fn new(width: u32, height: u32) -> Self {
+          Self {
+               width,
+               height,
+               pixels: vec![0; width as usize * height as usize * 4],
+               blend_mode: BlendMode::None
+          }
+     }
+
+     /// The pixels, as RGBA bytes row after row from the top
+     pub fn pixels(&self) -> &[u8] {
+          &self.pixels
+     }
+
+     /// Get the color of a pixel, `None` if outside the image
+     pub fn pixel(&self, x: i32, y: i32) -> Option<Color> {
+          let i = self.index(x, y)?;
+          let p = &self.pixels[i..(i + 4)];
+
+          Some(Color::RGBA(p[0], p[1], p[2], p[3]))
+     }
+
+     /// Replace the color of a pixel, whatever the blend mode, nothing being
+     /// done outside the image
+     pub fn set_pixel(&mut self, x: i32, y: i32, color: Color) {
+          if let Some(i) = self.index(x, y) {
+               self.pixels[i..(i + 4)].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+          }
+     }
+
+     /// Width of the image
+     pub fn width(&self) -> u32 {
+          self.width
+     }
+
+     /// Height of the image
+     pub fn height(&self) -> u32 {
+          self.height
+     }
+
+     /// Shrink the image by an integer factor, each pixel being the average
+     /// of the `factor * factor` ones it covers
+     pub fn downsample(&self, factor: u32) -> Framebuffer {
+          let factor = factor.max(1);
+          let mut result = Framebuffer::new(self.width / factor, self.height / factor);
+          let count = factor * factor;
+
+          for y in 0..result.height {
+               for x in 0..result.width {
+                    let mut sum = [0u32; 4];
+                    for sy in (y * factor)..((y + 1) * factor) {
+                         for sx in (x * factor)..((x + 1) * factor) {
+                              let i = (sy as usize * self.width as usize + sx as usize) * 4;
+                              for (s, p) in sum.iter_mut().zip(&self.pixels[i..(i + 4)]) {
+                                   *s += *p as u32;
+                              }
+                         }
+                    }
+
+                    let i = (y as usize * result.width as usize + x as usize) * 4;
+                    for (p, s) in result.pixels[i..(i + 4)].iter_mut().zip(sum) {
+                         *p = ((s + count / 2) / count) as u8;
+                    }
+               }
+          }
+
+          result
+     }
+
+     fn index(&self, x: i32, y: i32) -> Option<usize> {
+          if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+               return None;
+          }
+
+          Some((y as usize * self.width as usize + x as usize) * 4)
+     }
+
+     /// Combine a color with a pixel according to the blend mode, the same
+     /// way as SDL
+     fn blend_pixel(&mut self, x: i32, y: i32, color: Color) {
+          let i = match self.index(x, y) {
+               Some(i) => i,
+               None => return
+          };
+
+          let p = &mut self.pixels[i..(i + 4)];
+          let source = [color.r, color.g, color.b];
+          let alpha = color.a as u32;
+
+          match self.blend_mode {
+               BlendMode::None => p.copy_from_slice(&[color.r, color.g, color.b, color.a]),
+               BlendMode::Blend => {
+                    for (d, s) in p[..3].iter_mut().zip(source) {
+                         *d = ((s as u32 * alpha + *d as u32 * (255 - alpha) + 127) / 255) as u8;
+                    }
+                    p[3] = (alpha + (p[3] as u32 * (255 - alpha) + 127) / 255) as u8;
+               },
+               BlendMode::Add => {
+                    for (d, s) in p[..3].iter_mut().zip(source) {
+                         *d = (*d as u32 + (s as u32 * alpha + 127) / 255).min(255) as u8;
+                    }
+               },
+               BlendMode::Mod => {
+                    for (d, s) in p[..3].iter_mut().zip(source) {
+                         *d = ((s as u32 * *d as u32 + 127) / 255) as u8;
+                    }
+               },
+               BlendMode::Mul => {
+                    for (d, s) in p[..3].iter_mut().zip(source) {
+                         let d32 = *d as u32;
+                         *d = ((s as u32 * d32 + d32 * (255 - alpha) + 127) / 255).min(255) as u8;
+                    }
+               },
+          }
+     }
+}
+
+impl RenderTarget for Framebuffer {
+     fn size(&self) -> Result<Vec2<u32>, String> {
+          Ok(Vec2::new(self.width, self.height))
+     }
+
+     fn blend_mode(&self) -> BlendMode {
+          self.blend_mode
+     }
+
+     fn set_blend_mode(&mut self, mode: BlendMode) {
+          self.blend_mode = mode;
+     }
+
+     fn clear(&mut self, color: Color) {
+          for p in self.pixels.chunks_exact_mut(4) {
+               p.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+          }
+     }
+
+     fn fill_rect(
+          &mut self,
+          origin: Vec2<i32>,
+          width: u32,
+          height: u32,
+          color: Color
+     ) -> Result<(), String> {
+          // empty sizes are drawn as 1, like SDL does
+          let x_end = (origin.x + width.max(1) as i32).min(self.width as i32);
+          let y_end = (origin.y + height.max(1) as i32).min(self.height as i32);
+
+          for y in origin.y.max(0)..y_end {
+               for x in origin.x.max(0)..x_end {
+                    self.blend_pixel(x, y, color);
+               }
+          }
+
+          Ok(())
+     }
+
+     fn draw_image(&mut self, origin: Vec2<i32>, image: &Framebuffer) -> Result<(), String> {
+          for y in 0..image.height as i32 {
+               for x in 0..image.width as i32 {
+                    if let Some(color) = image.pixel(x, y) {
+                         self.blend_pixel(origin.x + x, origin.y + y, color);
+                    }
+               }
+          }
+
+          Ok(())
+     }
+}
@@ -0,0 +1,79 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+mod framebuffer;
+mod sdl;
+
+pub use framebuffer::Framebuffer;
+pub use sdl::WindowTarget;
+use sdl2::pixels::Color;
+
+use crate::common::vec2::Vec2;
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/// How the drawn colors are combined with the ones already in the target
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BlendMode {
+     /// The color replaces the previous one
+     None,
+
+     /// The color is drawn over the previous one according to its alpha
+     Blend,
+
+     /// The color, weighted by its alpha, is added to the previous one
+     Add,
+
+     /// The previous color is multiplied by the color
+     Mod,
+
+     /// The previous color is multiplied by the color, and blended with
+     /// itself according to the alpha of the color
+     Mul
+}
+
+/// Something the objects can be drawn into, a window or an image in memory
+pub trait RenderTarget {
+     /// Size of the target, in pixels
+     fn size(&self) -> Result<Vec2<u32>, String>;
+
+     /// How the drawings are currently combined with the target
+     fn blend_mode(&self) -> BlendMode;
+
+     /// Change how the next drawings are combined with the target
+     fn set_blend_mode(&mut self, mode: BlendMode);
+
+     /// Fill the whole target with a color, whatever the blend mode
+     fn clear(&mut self, color: Color);
+
+     /// Fill a rectangle of the given top left corner and size
+     fn fill_rect(
+          &mut self,
+          origin: Vec2<i32>,
+          width: u32,
+          height: u32,
+          color: Color
+     ) -> Result<(), String>;
+
+     /// Draw an image with its top left corner at `origin`, combined with the
+     /// target according to the blend mode
+     fn draw_image(&mut self, origin: Vec2<i32>, image: &Framebuffer) -> Result<(), String>;
+}
@@ -0,0 +1,138 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::{
+     pixels::{Color, PixelFormatEnum},
+     rect::Rect,
+     render::{self, Canvas, Texture, TextureCreator},
+     video::{Window, WindowContext}
+};
+
+use super::{BlendMode, Framebuffer, RenderTarget};
+use crate::common::vec2::Vec2;
+
+// =============================================================================
+// Type
+// =============================================================================
+
+/// The canvas of the window, with the texture the images are drawn through
+/// kept from one frame to the next
+pub struct WindowTarget<'a> {
+     pub canvas: Canvas<Window>,
+     creator: &'a TextureCreator<WindowContext>,
+
+     /// Only recreated when the size of the images changes
+     texture: Option<Texture<'a>>
+}
+
+impl<'a> WindowTarget<'a> {
+     /// Draw on `canvas`, the textures being created by `creator`
+     pub fn new(canvas: Canvas<Window>, creator: &'a TextureCreator<WindowContext>) -> Self {
+          Self {
+               canvas,
+               creator,
+               texture: None
+          }
+     }
+
+     /// Get a streaming texture of the given size
+     fn texture(&mut self, width: u32, height: u32) -> Result<&mut Texture<'a>, String> {
+          let reusable = self.texture.as_ref().is_some_and(|texture| {
+               let query = texture.query();
+
+               query.width == width && query.height == height
+          });
+
+          if !reusable {
+               let texture = self
+                    .creator
+                    .create_texture_streaming(PixelFormatEnum::RGBA32, width, height)
+                    .map_err(|e| e.to_string())?;
+
+               self.texture = Some(texture);
+          }
+
+          Ok(self.texture.as_mut().unwrap())
+     }
+}
+
+impl RenderTarget for WindowTarget<'_> {
+     fn size(&self) -> Result<Vec2<u32>, String> {
+          let (width, height) = self.canvas.output_size()?;
+
+          Ok(Vec2::new(width, height))
+     }
+
+     fn blend_mode(&self) -> BlendMode {
+          match self.canvas.blend_mode() {
+               render::BlendMode::None => BlendMode::None,
+               render::BlendMode::Blend => BlendMode::Blend,
+               render::BlendMode::Add => BlendMode::Add,
+               render::BlendMode::Mod => BlendMode::Mod,
+               render::BlendMode::Mul => BlendMode::Mul,
+               render::BlendMode::Invalid => {
+                    unreachable!("the blend mode is only changed to valid ones")
+               }
+          }
+     }
+
+     fn set_blend_mode(&mut self, mode: BlendMode) {
+          self.canvas.set_blend_mode(match mode {
+               BlendMode::None => render::BlendMode::None,
+               BlendMode::Blend => render::BlendMode::Blend,
+               BlendMode::Add => render::BlendMode::Add,
+               BlendMode::Mod => render::BlendMode::Mod,
+               BlendMode::Mul => render::BlendMode::Mul
+          });
+     }
+
+     fn clear(&mut self, color: Color) {
+          self.canvas.set_draw_color(color);
+          self.canvas.clear();
+     }
+
+     fn fill_rect(
+          &mut self,
+          origin: Vec2<i32>,
+          width: u32,
+          height: u32,
+          color: Color
+     ) -> Result<(), String> {
+          if self.canvas.draw_color() != color {
+               self.canvas.set_draw_color(color);
+          }
+
+          self.canvas
+               .fill_rect(Rect::new(origin.x, origin.y, width, height))
+     }
+
+     fn draw_image(&mut self, origin: Vec2<i32>, image: &Framebuffer) -> Result<(), String> {
+          if image.width() == 0 || image.height() == 0 {
+               return Ok(());
+          }
+
+          let blend_mode = self.canvas.blend_mode();
+          let texture = self.texture(image.width(), image.height())?;
+          texture
+               .update(None, image.pixels(), image.width() as usize * 4)
+               .map_err(|e| e.to_string())?;
+          texture.set_blend_mode(blend_mode);
+
+          let destination = Rect::new(origin.x, origin.y, image.width(), image.height());
+          let texture = self.texture.as_ref().unwrap();
+          self.canvas.copy(texture, None, destination)
+     }
+}
@@ -0,0 +1,148 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::pixels::Color;
+
+use super::{BlendMode, Framebuffer, RenderTarget};
+use crate::{
+     common::vec2::{Vec2, Vec2F},
+     renderer::{
+          graphics::{
+               self,
+               radius::RadiusType,
+               vectors::{VectorStyle, VelocityLengthType},
+               Graphics
+          },
+          viewport::Viewport
+     },
+     simulation::object::Object
+};
+
+#[test]
+fn test_blend_modes() {
+     let mut target = Framebuffer::new(6, 1);
+     target.clear(Color::RGBA(100, 100, 100, 255));
+
+     let color = Color::RGBA(200, 0, 50, 128);
+
+     target.fill_rect(Vec2::new(0, 0), 1, 1, color).unwrap();
+     assert_eq!(target.pixel(0, 0), Some(color));
+
+     target.set_blend_mode(BlendMode::Blend);
+     target.fill_rect(Vec2::new(1, 0), 1, 1, color).unwrap();
+     assert_eq!(target.pixel(1, 0), Some(Color::RGBA(150, 50, 75, 255)));
+
+     target.set_blend_mode(BlendMode::Add);
+     target.fill_rect(Vec2::new(2, 0), 1, 1, color).unwrap();
+     assert_eq!(target.pixel(2, 0), Some(Color::RGBA(200, 100, 125, 255)));
+
+     target.set_blend_mode(BlendMode::Mod);
+     target.fill_rect(Vec2::new(3, 0), 1, 1, color).unwrap();
+     assert_eq!(target.pixel(3, 0), Some(Color::RGBA(78, 0, 20, 255)));
+
+     target.set_blend_mode(BlendMode::Mul);
+     target.fill_rect(Vec2::new(4, 0), 1, 1, color).unwrap();
+     assert_eq!(target.pixel(4, 0), Some(Color::RGBA(128, 50, 69, 255)));
+
+     // untouched, and nothing drawn outside
+     assert_eq!(target.pixel(5, 0), Some(Color::RGBA(100, 100, 100, 255)));
+     assert_eq!(target.pixel(6, 0), None);
+}
+
+#[test]
+fn test_draw_image() {
+     let mut image = Framebuffer::new(2, 2);
+     image.fill_rect(Vec2::new(0, 0), 2, 1, Color::RGBA(255, 0, 0, 128))
+          .unwrap();
+
+     let mut target = Framebuffer::new(4, 4);
+     target.clear(Color::BLUE);
+     target.set_blend_mode(BlendMode::Blend);
+     target.draw_image(Vec2::new(3, 2), &image).unwrap();
+
+     // blended, clipped, and transparent pixels leave the target untouched
+     assert_eq!(target.pixel(3, 2), Some(Color::RGBA(128, 0, 127, 255)));
+     assert_eq!(target.pixel(3, 3), Some(Color::BLUE));
+     assert_eq!(target.pixel(2, 2), Some(Color::BLUE));
+}
+
+#[test]
+fn test_draw_object_headless() {
+     let graphics = Graphics::new(RadiusType::Constant(2.0), None, None);
+     let viewport = Viewport::new(1.0, Vec2F::new_null());
+     let star = |location| {
+          Object::new(
+               1.0,
+               location,
+               Vec2F::new_null(),
+               Vec2F::new_null(),
+               true,
+               Color::RED
+          )
+          .unwrap()
+     };
+
+     // a disc, lighter on its edge
+     let mut target = Framebuffer::new(32, 32);
+     graphics::draw_object(
+          &mut target,
+          &star(Vec2F::new(10.0, 10.0)),
+          &graphics,
+          &viewport
+     )
+     .unwrap();
+
+     assert_eq!(target.pixel(9, 9), Some(Color::RED));
+     assert_eq!(target.pixel(10, 10), Some(Color::RED));
+     assert_eq!(target.pixel(7, 7), Some(Color::RGBA(0, 0, 0, 0)));
+     assert_eq!(target.pixel(13, 13), Some(Color::RGBA(0, 0, 0, 0)));
+
+     let edge = target.pixel(11, 11).unwrap();
+     assert!(edge.a > 0 && edge.a < 255, "{:?}", edge);
+}
+
+#[test]
+fn test_draw_object_vectors() {
+     let graphics = Graphics::new(
+          RadiusType::Constant(1.0),
+          Some(VelocityLengthType::Constant(10.0)),
+          None
+     )
+     .with_velocity_style(VectorStyle::new(Color::GREEN, 1.0, 3.0));
+     let viewport = Viewport::new(1.0, Vec2F::new_null());
+
+     let star = Object::new(
+          1.0,
+          Vec2F::new(4.0, 8.5),
+          Vec2F::new_null(),
+          Vec2F::new(3.0, 0.0),
+          true,
+          Color::RED
+     )
+     .unwrap();
+
+     let mut target = Framebuffer::new(32, 16);
+     graphics::draw_object(&mut target, &star, &graphics, &viewport).unwrap();
+
+     // the shaft of the velocity stops where its head starts, the head
+     // reaching the tip
+     assert!((6..11).all(|x| target.pixel(x, 8) == Some(Color::GREEN)));
+     assert!(target.pixel(12, 8).unwrap().g < 255);
+     assert!(target.pixel(13, 8).unwrap().g > 0);
+     assert_eq!(target.pixel(14, 8), Some(Color::RGBA(0, 0, 0, 0)));
+     assert!(target.pixel(12, 7).unwrap().g > 0);
+     assert!(target.pixel(12, 9).unwrap().g > 0);
+}
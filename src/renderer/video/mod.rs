@@ -0,0 +1,369 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use std::{
+     borrow::Cow,
+     collections::HashMap,
+     fmt::{Debug, Display},
+     fs::File,
+     io::{BufWriter, Write},
+     path::Path,
+     str::FromStr
+};
+
+use anyhow::{bail, Context};
+use color_quant::NeuQuant;
+
+use super::target::Framebuffer;
+use crate::common::vec2::Vec2;
+
+/// Sampling factor of the color quantization, 1 being the slowest and best
+const QUANTIZATION_SPEED: i32 = 10;
+
+/// Levels of each component in the web safe palette
+const WEB_SAFE_LEVELS: u32 = 6;
+
+// =============================================================================
+// Settings
+// =============================================================================
+
+/// The colors a GIF is drawn with
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Palette {
+     /// Colors chosen for each frame, at most 256
+     Adaptive { colors: usize },
+
+     /// The 216 colors of the web, the same for every frame
+     WebSafe
+}
+
+/// How the videos are encoded
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct VideoSettings {
+     /// Number of frames per second
+     pub frame_rate: u32,
+
+     /// Only used by the GIFs
+     pub palette: Palette
+}
+
+impl VideoSettings {
+     pub fn new(frame_rate: u32, palette: Palette) -> Self {
+          Self {
+               frame_rate,
+               palette
+          }
+     }
+}
+
+/// The file formats the videos can be written in
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VideoFormat {
+     /// Animated GIF, with at most 256 colors per frame
+     Gif,
+
+     /// Uncompressed YUV4MPEG2, read by most video tools
+     Y4m
+}
+
+impl VideoFormat {
+     /// Get the format from the extension of a path, `None` if it isn't a
+     /// video
+     pub fn from_path(path: &Path) -> Option<Self> {
+          match path.extension()?.to_str()? {
+               "gif" => Some(VideoFormat::Gif),
+               "y4m" => Some(VideoFormat::Y4m),
+               _ => None
+          }
+     }
+}
+
+// =============================================================================
+// Writer
+// =============================================================================
+
+enum Encoder<W: Write> {
+     Gif(gif::Encoder<W>),
+     Y4m(W)
+}
+
+/// Encode frames of the same size one after the other in a video
+pub struct VideoWriter<W: Write> {
+     encoder: Encoder<W>,
+     size: Vec2<u32>,
+     settings: VideoSettings
+}
+
+impl<W: Write> VideoWriter<W> {
+     pub fn new(
+          mut writer: W,
+          format: VideoFormat,
+          size: Vec2<u32>,
+          settings: VideoSettings
+     ) -> anyhow::Result<Self> {
+          if settings.frame_rate == 0 {
+               bail!("The frame rate must be positive.");
+          }
+
+          let encoder = match format {
+               VideoFormat::Gif => {
+                    let (width, height) = match (u16::try_from(size.x), u16::try_from(size.y)) {
+                         (Ok(width), Ok(height)) => (width, height),
+                         _ => bail!("GIFs are at most 65535 pixels wide and high.")
+                    };
+
+                    let palette = match settings.palette {
+                         Palette::Adaptive { .. } => Vec::new(),
+                         Palette::WebSafe => web_safe_palette()
+                    };
+
+                    let mut encoder = gif::Encoder::new(writer, width, height, &palette)?;
+                    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+                    Encoder::Gif(encoder)
+               },
+               VideoFormat::Y4m => {
+                    writeln!(
+                         writer,
+                         "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444",
+                         size.x, size.y, settings.frame_rate
+                    )?;
+
+                    Encoder::Y4m(writer)
+               }
+          };
+
+          Ok(Self {
+               encoder,
+               size,
+               settings
+          })
+     }
+
+     /// Add a frame, of the size of the video
+     pub fn write_frame(&mut self, image: &Framebuffer) -> anyhow::Result<()> {
+          if image.width() != self.size.x || image.height() != self.size.y {
+               bail!(
+                    "The frame is {}x{} instead of {}x{}.",
+                    image.width(),
+                    image.height(),
+                    self.size.x,
+                    self.size.y
+               );
+          }
+
+          match &mut self.encoder {
+               Encoder::Gif(encoder) => {
+                    let (indices, palette) = match self.settings.palette {
+                         Palette::Adaptive { colors } => {
+                              let (indices, palette) = adaptive_palette(image.pixels(), colors);
+
+                              (indices, Some(palette))
+                         },
+                         Palette::WebSafe => {
+                              let indices = image
+                                   .pixels()
+                                   .chunks_exact(4)
+                                   .map(web_safe_index)
+                                   .collect::<Vec<_>>();
+
+                              (indices, None)
+                         }
+                    };
+
+                    let frame = gif::Frame {
+                         width: self.size.x as u16,
+                         height: self.size.y as u16,
+                         delay: (100.0 / self.settings.frame_rate as f64).round() as u16,
+                         buffer: Cow::Owned(indices),
+                         palette,
+                         ..Default::default()
+                    };
+
+                    encoder.write_frame(&frame)?;
+               },
+               Encoder::Y4m(writer) => {
+                    writer.write_all(b"FRAME\n")?;
+                    writer.write_all(&rgba_to_yuv444(image.pixels()))?;
+               }
+          }
+
+          Ok(())
+     }
+
+     /// End the video, giving back the writer
+     pub fn finish(self) -> anyhow::Result<W> {
+          let mut writer = match self.encoder {
+               Encoder::Gif(encoder) => encoder.into_inner()?,
+               Encoder::Y4m(writer) => writer
+          };
+          writer.flush()?;
+
+          Ok(writer)
+     }
+}
+
+/// Create a video file, in the format given by its extension
+pub fn create(
+     path: &Path,
+     size: Vec2<u32>,
+     settings: VideoSettings
+) -> anyhow::Result<VideoWriter<BufWriter<File>>> {
+     let format = VideoFormat::from_path(path)
+          .with_context(|| format!("{} isn't a .gif or .y4m file.", path.display()))?;
+     let file =
+          File::create(path).with_context(|| format!("Couldn't create {}.", path.display()))?;
+
+     VideoWriter::new(BufWriter::new(file), format, size, settings)
+}
+
+// =============================================================================
+// Colors
+// =============================================================================
+
+fn web_safe_palette() -> Vec<u8> {
+     let step = 255 / (WEB_SAFE_LEVELS - 1);
+     let mut palette = Vec::new();
+
+     for r in 0..WEB_SAFE_LEVELS {
+          for g in 0..WEB_SAFE_LEVELS {
+               for b in 0..WEB_SAFE_LEVELS {
+                    palette.extend([r, g, b].map(|l| (l * step) as u8));
+               }
+          }
+     }
+
+     palette
+}
+
+/// Indices of the pixels in a palette of at most `colors` colors, exact if
+/// the image has few enough colors, as the stars are often too rare to be kept
+/// by the quantization
+fn adaptive_palette(pixels: &[u8], colors: usize) -> (Vec<u8>, Vec<u8>) {
+     let mut palette: Vec<[u8; 3]> = Vec::new();
+     let mut lookup = HashMap::new();
+     let mut indices = Vec::with_capacity(pixels.len() / 4);
+
+     for p in pixels.chunks_exact(4) {
+          let color = [p[0], p[1], p[2]];
+          let index = match lookup.get(&color) {
+               Some(index) => *index,
+               None if palette.len() < colors => {
+                    lookup.insert(color, palette.len());
+                    palette.push(color);
+
+                    palette.len() - 1
+               },
+               None => {
+                    let quantization = NeuQuant::new(QUANTIZATION_SPEED, colors, pixels);
+                    let indices = pixels
+                         .chunks_exact(4)
+                         .map(|p| quantization.index_of(p) as u8)
+                         .collect();
+
+                    return (indices, quantization.color_map_rgb());
+               }
+          };
+
+          indices.push(index as u8);
+     }
+
+     (indices, palette.concat())
+}
+
+/// Index of the nearest color of the web safe palette
+fn web_safe_index(pixel: &[u8]) -> u8 {
+     let step = 255 / (WEB_SAFE_LEVELS - 1);
+     let level = |v: u8| (v as u32 + step / 2) / step;
+
+     (level(pixel[0]) * WEB_SAFE_LEVELS * WEB_SAFE_LEVELS
+          + level(pixel[1]) * WEB_SAFE_LEVELS
+          + level(pixel[2])) as u8
+}
+
+/// Convert RGBA pixels into the Y, U and V planes of BT.601, in studio range
+fn rgba_to_yuv444(pixels: &[u8]) -> Vec<u8> {
+     let count = pixels.len() / 4;
+     let mut planes = vec![0; count * 3];
+
+     for (i, p) in pixels.chunks_exact(4).enumerate() {
+          let (r, g, b) = (p[0] as i32, p[1] as i32, p[2] as i32);
+
+          planes[i] = (16 + ((66 * r + 129 * g + 25 * b + 128) >> 8)) as u8;
+          planes[count + i] = (128 + ((-38 * r - 74 * g + 112 * b + 128) >> 8)) as u8;
+          planes[2 * count + i] = (128 + ((112 * r - 94 * g - 18 * b + 128) >> 8)) as u8;
+     }
+
+     planes
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+impl Display for Palette {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          match self {
+               Palette::Adaptive { colors } => write!(f, "adaptive:{}", colors),
+               Palette::WebSafe => f.write_str("web_safe")
+          }
+     }
+}
+
+pub struct PaletteParseError {
+     value: String
+}
+
+impl Debug for PaletteParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.debug_struct("PaletteParseError")
+               .field("value", &self.value)
+               .finish()
+     }
+}
+
+impl Display for PaletteParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          write!(f, "'{}' is not a valid palette.", self.value)
+     }
+}
+
+impl std::error::Error for PaletteParseError {}
+
+impl FromStr for Palette {
+     type Err = PaletteParseError;
+
+     /// Parse `web_safe`, `adaptive` or `adaptive:<colors>`, with between 2
+     /// and 256 colors
+     fn from_str(s: &str) -> Result<Self, Self::Err> {
+          let error = || PaletteParseError {
+               value: s.to_string()
+          };
+
+          match s.split(':').collect::<Vec<_>>().as_slice() {
+               ["web_safe"] => Ok(Palette::WebSafe),
+               ["adaptive"] => Ok(Palette::Adaptive { colors: 256 }),
+               ["adaptive", colors] => match colors.parse::<usize>() {
+                    Ok(colors) if (2..=256).contains(&colors) => Ok(Palette::Adaptive { colors }),
+                    _ => Err(error())
+               },
+               _ => Err(error())
+          }
+     }
+}
@@ -0,0 +1,164 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::path::Path;
+
+use sdl2::pixels::Color;
+
+use super::{Palette, VideoFormat, VideoSettings, VideoWriter};
+use crate::{
+     common::vec2::Vec2,
+     renderer::target::{Framebuffer, RenderTarget}
+};
+
+/// A black image with a colored pixel at `x`
+fn frame(x: i32, color: Color) -> Framebuffer {
+     let mut image = Framebuffer::new(8, 4);
+     image.clear(Color::BLACK);
+     image.fill_rect(Vec2::new(x, 1), 1, 1, color).unwrap();
+
+     image
+}
+
+fn decode_gif(bytes: &[u8]) -> Vec<(u16, Vec<u8>)> {
+     let mut options = gif::DecodeOptions::new();
+     options.set_color_output(gif::ColorOutput::RGBA);
+
+     let mut decoder = options.read_info(bytes).unwrap();
+     let mut frames = Vec::new();
+     while let Some(frame) = decoder.read_next_frame().unwrap() {
+          frames.push((frame.delay, frame.buffer.to_vec()));
+     }
+
+     frames
+}
+
+#[test]
+fn test_gif() {
+     let orange = Color::RGB(255, 102, 0);
+
+     for palette in [Palette::Adaptive { colors: 16 }, Palette::WebSafe] {
+          let settings = VideoSettings::new(25, palette);
+          let mut writer =
+               VideoWriter::new(Vec::new(), VideoFormat::Gif, Vec2::new(8, 4), settings).unwrap();
+          for x in 0..3 {
+               writer.write_frame(&frame(x, orange)).unwrap();
+          }
+
+          let frames = decode_gif(&writer.finish().unwrap());
+          assert_eq!(frames.len(), 3, "{}", palette);
+
+          for (x, (delay, pixels)) in frames.iter().enumerate() {
+               assert_eq!(*delay, 4);
+
+               let i = (8 + x) * 4;
+               assert_eq!(&pixels[i..(i + 4)], &[255, 102, 0, 255], "{}", palette);
+               assert_eq!(&pixels[0..4], &[0, 0, 0, 255], "{}", palette);
+          }
+     }
+}
+
+#[test]
+fn test_gif_quantization() {
+     // more colors than the palette can hold
+     let mut image = Framebuffer::new(64, 64);
+     for y in 0..64 {
+          for x in 0..64 {
+               let color = Color::RGB(x as u8 * 4, y as u8 * 4, 128);
+               image.fill_rect(Vec2::new(x, y), 1, 1, color).unwrap();
+          }
+     }
+
+     let settings = VideoSettings::new(25, Palette::Adaptive { colors: 16 });
+     let mut writer =
+          VideoWriter::new(Vec::new(), VideoFormat::Gif, Vec2::new(64, 64), settings).unwrap();
+     writer.write_frame(&image).unwrap();
+
+     let frames = decode_gif(&writer.finish().unwrap());
+     let pixels = &frames[0].1;
+
+     let mut colors = pixels.chunks_exact(4).collect::<Vec<_>>();
+     colors.sort();
+     colors.dedup();
+     assert!(colors.len() <= 16);
+     // the pixels stay near their color on average
+     let error = pixels
+          .iter()
+          .zip(image.pixels())
+          .map(|(d, o)| (*d as i32 - *o as i32).abs())
+          .sum::<i32>() as f64
+          / pixels.len() as f64;
+     assert!(error < 16.0, "{}", error);
+}
+
+#[test]
+fn test_gif_frame_size() {
+     let settings = VideoSettings::new(10, Palette::WebSafe);
+     let mut writer =
+          VideoWriter::new(Vec::new(), VideoFormat::Gif, Vec2::new(4, 4), settings).unwrap();
+
+     assert!(writer.write_frame(&frame(0, Color::WHITE)).is_err());
+}
+
+#[test]
+fn test_y4m() {
+     let settings = VideoSettings::new(30, Palette::WebSafe);
+     let mut writer =
+          VideoWriter::new(Vec::new(), VideoFormat::Y4m, Vec2::new(8, 4), settings).unwrap();
+     writer.write_frame(&frame(2, Color::WHITE)).unwrap();
+     writer.write_frame(&frame(3, Color::WHITE)).unwrap();
+     let bytes = writer.finish().unwrap();
+
+     let header = b"YUV4MPEG2 W8 H4 F30:1 Ip A1:1 C444\n";
+     assert_eq!(&bytes[..header.len()], header);
+
+     let frame_size = 6 + 8 * 4 * 3;
+     assert_eq!(bytes.len(), header.len() + 2 * frame_size);
+
+     for (f, x) in [(0, 2), (1, 3)] {
+          let frame = &bytes[(header.len() + f * frame_size)..][..frame_size];
+          assert_eq!(&frame[..6], b"FRAME\n");
+
+          // white and black, in studio range and without color
+          let (y, u, v) = (&frame[6..38], &frame[38..70], &frame[70..102]);
+          assert_eq!(y[8 + x], 235);
+          assert_eq!(y[0], 16);
+          assert!(u.iter().chain(v).all(|c| *c == 128));
+     }
+}
+
+#[test]
+fn test_format_and_palette_parsing() {
+     assert_eq!(
+          VideoFormat::from_path(Path::new("run.gif")),
+          Some(VideoFormat::Gif)
+     );
+     assert_eq!(
+          VideoFormat::from_path(Path::new("out/run.y4m")),
+          Some(VideoFormat::Y4m)
+     );
+     assert_eq!(VideoFormat::from_path(Path::new("frames")), None);
+
+     for palette in [Palette::Adaptive { colors: 64 }, Palette::WebSafe] {
+          assert_eq!(palette.to_string().parse::<Palette>().unwrap(), palette);
+     }
+     assert_eq!(
+          "adaptive".parse::<Palette>().unwrap(),
+          Palette::Adaptive { colors: 256 }
+     );
+     assert!("adaptive:300".parse::<Palette>().is_err());
+     assert!("grey".parse::<Palette>().is_err());
+}
@@ -0,0 +1,412 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+     common::{
+          vec2::{Vec2, Vec2F},
+          vec3::Vec3F,
+          vector::Vector
+     },
+     simulation::{object::Object, settings::InvalidSettingError}
+};
+
+/// Part of the screen the objects take after a zoom to fit
+const FIT_MARGIN: f64 = 0.9;
+
+/// Ratio between the scale and its target under which a smooth zoom ends
+const ZOOM_END: f64 = 1e-3;
+
+// =============================================================================
+// Settings
+// =============================================================================
+
+/// How the viewport zooms
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ZoomSettings {
+     /// Factor the scale is multiplied by for each step, like a notch of the
+     /// mouse wheel
+     pub rate: f64,
+
+     /// Limits of the scale, relative to the one the viewport was created with
+     pub min: f64,
+     pub max: f64,
+
+     /// Time the zoom takes to get most of the way to its target, in seconds,
+     /// zero to zoom at once
+     pub smoothing: f64
+}
+
+impl ZoomSettings {
+     /// Create the settings if the rate and the limits are positive, the
+     /// limits in order, and the smoothing positive or null. Else return an
+     /// error.
+     pub fn new(
+          rate: f64,
+          min: f64,
+          max: f64,
+          smoothing: f64
+     ) -> Result<Self, InvalidSettingError> {
+          if !(rate.is_finite() && rate > 0.0) {
+               return Err(InvalidSettingError::new("zoom rate", rate, "positive"));
+          }
+          if !(min.is_finite() && min > 0.0) {
+               return Err(InvalidSettingError::new("minimum zoom", min, "positive"));
+          }
+          if !(max.is_finite() && max >= min) {
+               return Err(InvalidSettingError::new(
+                    "maximum zoom",
+                    max,
+                    "at least the minimum one"
+               ));
+          }
+          if !(smoothing.is_finite() && smoothing >= 0.0) {
+               return Err(InvalidSettingError::new(
+                    "zoom smoothing",
+                    smoothing,
+                    "positive or null"
+               ));
+          }
+
+          Ok(Self {
+               rate,
+               min,
+               max,
+               smoothing
+          })
+     }
+}
+
+impl Default for ZoomSettings {
+     fn default() -> Self {
+          Self {
+               rate: 1.15,
+               min: 1e-3,
+               max: 1e3,
+               smoothing: 0.08
+          }
+     }
+}
+
+/// Where a smooth zoom is going
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct ZoomTarget {
+     scale: f64,
+
+     /// Point of the screen staying on the same location during the zoom
+     anchor: Vec2F
+}
+
+// =============================================================================
+// Type
+// =============================================================================
+
+/// How the simulation is seen on the screen, a point of the plane of the
+/// camera being drawn at `location * scale + shift`
+#[derive(Clone, Debug)]
+pub struct Viewport {
+     original_scale: f64,
+     pub scale: f64,
+
+     /// Pixels of the screen per pixel of the drawings, like the thickness of
+     /// the lines, above 1 when supersampling
+     pub pixel_ratio: f64,
+
+     pub shift: Vec2F,
+     original_shift: Vec2F,
+
+     zoom_settings: ZoomSettings,
+     zoom_target: Option<ZoomTarget>,
+
+     /// Rotation of the camera around the vertical axis of the screen
+     yaw: f64,
+
+     /// Rotation of the camera around the horizontal axis of the screen
+     pitch: f64
+}
+
+impl Viewport {
+     pub fn new(scale: f64, shift: Vec2F) -> Self {
+          Self {
+               scale,
+               shift,
+               original_shift: shift,
+               original_scale: scale,
+               pixel_ratio: 1.0,
+               zoom_settings: ZoomSettings::default(),
+               zoom_target: None,
+               yaw: 0.0,
+               pitch: 0.0
+          }
+     }
+
+     pub fn with_zoom_settings(mut self, settings: ZoomSettings) -> Self {
+          self.zoom_settings = settings;
+
+          self
+     }
+
+     // -------------------------------------------------------------------------
+     // Coordinates
+     // -------------------------------------------------------------------------
+
+     /// Get the point of the screen where a location of the plane of the
+     /// camera is drawn
+     pub fn world_to_screen(&self, location: Vec2F) -> Vec2F {
+          location * self.scale + self.shift
+     }
+
+     /// Get the location in the plane of the camera drawn at a point of the
+     /// screen
+     pub fn screen_to_world(&self, point: Vec2F) -> Vec2F {
+          (point - self.shift) / self.scale
+     }
+
+     /// Corners of the region of the plane of the camera seen on a screen of
+     /// the given size, the lowest coordinates first
+     #[cfg(test)]
+     pub fn visible_region(&self, size: Vec2<u32>) -> (Vec2F, Vec2F) {
+          (
+               self.screen_to_world(Vec2F::new_null()),
+               self.screen_to_world(size.convert_as_to_type())
+          )
+     }
+
+     /// Get the point of the screen where a location of the simulation is
+     /// drawn, in the plane or in space
+     pub fn to_screen<V: Vector<f64>>(&self, location: V) -> Vec2F {
+          let v = self.to_camera(location.to_vec3());
+
+          self.world_to_screen(Vec2::new(v.x, v.y))
+     }
+
+     // -------------------------------------------------------------------------
+     // Moves
+     // -------------------------------------------------------------------------
+
+     /// Move the drawings on the screen, in pixels
+     pub fn pan(&mut self, delta: Vec2F) {
+          self.shift += delta;
+     }
+
+     /// Zoom by `steps` times the rate, in if positive, the location under
+     /// `anchor` on the screen staying there. The zoom is animated by
+     /// `update` if it is smooth.
+     pub fn zoom(&mut self, steps: f64, anchor: Vec2F) {
+          let settings = &self.zoom_settings;
+          let current = self.zoom_target.map_or(self.scale, |t| t.scale);
+          let scale = (current * settings.rate.powf(steps)).clamp(
+               self.original_scale * settings.min,
+               self.original_scale * settings.max
+          );
+
+          if settings.smoothing > 0.0 {
+               self.zoom_target = Some(ZoomTarget { scale, anchor });
+          } else {
+               self.set_scale(scale, anchor);
+          }
+     }
+
+     /// Change the scale, the location under `anchor` staying there
+     pub fn set_scale(&mut self, scale: f64, anchor: Vec2F) {
+          let location = self.screen_to_world(anchor);
+
+          self.scale = scale;
+          self.shift = anchor - location * scale;
+     }
+
+     /// Bring the scale toward the target of the zoom, `delta_t` seconds after
+     /// the previous update
+     pub fn update(&mut self, delta_t: f64) {
+          let target = match self.zoom_target {
+               Some(t) => t,
+               None => return
+          };
+
+          // the same ratio is covered in the same time, far out or close in
+          let progress = 1.0 - (-delta_t / self.zoom_settings.smoothing).exp();
+          let ratio = (target.scale / self.scale).powf(progress);
+
+          if (target.scale / (self.scale * ratio)).ln().abs() < ZOOM_END {
+               self.set_scale(target.scale, target.anchor);
+               self.zoom_target = None;
+          } else {
+               self.set_scale(self.scale * ratio, target.anchor);
+          }
+     }
+
+     /// Whether a smooth zoom is running
+     #[cfg(test)]
+     pub fn zooming(&self) -> bool {
+          self.zoom_target.is_some()
+     }
+
+     /// Move the viewport so `location` is drawn at `center`
+     pub fn focus(&mut self, location: Vec2F, center: Vec2F) {
+          self.shift = center - location * self.scale;
+     }
+
+     /// Go back to the view the viewport was created with
+     pub fn reset(&mut self) {
+          self.scale = self.original_scale;
+          self.shift = self.original_shift;
+          self.zoom_target = None;
+          self.yaw = 0.0;
+          self.pitch = 0.0;
+     }
+
+     /// Zoom and move the viewport so all the objects are seen on a screen of
+     /// the given size, as seen by the camera
+     pub fn fit<V: Vector<f64>>(&mut self, objects: &[Object<f64, V>], size: Vec2<u32>) {
+          let mut locations = objects.iter().map(|o| self.project(o).1.location);
+          let first = match locations.next() {
+               Some(l) => l,
+               None => return
+          };
+          let (min, max) = locations.fold((first, first), |(min, max), l| {
+               (
+                    Vec2::new(min.x.min(l.x), min.y.min(l.y)),
+                    Vec2::new(max.x.max(l.x), max.y.max(l.y))
+               )
+          });
+
+          // a single point is only centered
+          let extent = max - min;
+          let size: Vec2F = size.convert_as_to_type();
+          let scale = (size.x / extent.x).min(size.y / extent.y) * FIT_MARGIN;
+          if scale.is_finite() {
+               self.scale = scale.clamp(
+                    self.original_scale * self.zoom_settings.min,
+                    self.original_scale * self.zoom_settings.max
+               );
+          }
+          self.zoom_target = None;
+
+          self.focus((min + max) / 2.0, size / 2.0);
+     }
+
+     /// Adapt the viewport to a screen whose size changed, the region seen
+     /// before staying centered and seen entirely
+     pub fn resize(&mut self, old: Vec2<u32>, new: Vec2<u32>) {
+          let (old, new): (Vec2F, Vec2F) = (old.convert_as_to_type(), new.convert_as_to_type());
+          let factor = (new.x / old.x).min(new.y / old.y);
+
+          // like when the window is minimized
+          if !factor.is_finite() || factor <= 0.0 {
+               return;
+          }
+
+          let (old_center, new_center) = (old / 2.0, new / 2.0);
+          let resize = |scale: f64, shift: Vec2F| {
+               let center = (old_center - shift) / scale;
+
+               (scale * factor, new_center - center * (scale * factor))
+          };
+
+          (self.scale, self.shift) = resize(self.scale, self.shift);
+          (self.original_scale, self.original_shift) =
+               resize(self.original_scale, self.original_shift);
+          if let Some(target) = &mut self.zoom_target {
+               target.scale *= factor;
+               target.anchor = (target.anchor - old_center) * factor + new_center;
+          }
+     }
+
+     /// Rotate the camera, `yaw` and `pitch` being in radians
+     pub fn rotate(&mut self, yaw: f64, pitch: f64) {
+          self.yaw += yaw;
+          self.pitch += pitch;
+     }
+
+     // -------------------------------------------------------------------------
+     // Projection
+     // -------------------------------------------------------------------------
+
+     /// Get a vector in the space of the camera, `x` and `y` being along the
+     /// screen and `z` toward the viewer. Without rotation, the plane of the
+     /// simulation is the one of the screen.
+     pub fn to_camera(&self, v: Vec3F) -> Vec3F {
+          let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+          let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+
+          let x = v.x * cos_yaw + v.z * sin_yaw;
+          let z = -v.x * sin_yaw + v.z * cos_yaw;
+
+          Vec3F::new(
+               x,
+               v.y * cos_pitch - z * sin_pitch,
+               v.y * sin_pitch + z * cos_pitch
+          )
+     }
+
+     /// Project an object on the plane of the screen, with its depth. Only
+     /// what is drawn is kept, the name and the parents being left out so
+     /// they aren't cloned every frame.
+     pub fn project<V: Vector<f64>>(&self, object: &Object<f64, V>) -> (f64, Object) {
+          let project = |v: V| {
+               let v = self.to_camera(v.to_vec3());
+
+               Vec2::new(v.x, v.y)
+          };
+
+          let depth = self.to_camera(object.location.to_vec3()).z;
+          let projected = Object {
+               id: object.id,
+               name: None,
+               parents: Vec::new(),
+               generator: None,
+               mass: object.mass,
+               location: project(object.location),
+               force: project(object.force),
+               velocity: project(object.velocity),
+               can_move: object.can_move,
+               kind: object.kind,
+               color: object.color
+          };
+
+          (depth, projected)
+     }
+
+     /// Project the objects on the screen, sorted from the farthest to the
+     /// nearest
+     pub fn project_all<V: Vector<f64>>(&self, objects: &[Object<f64, V>]) -> Vec<Object> {
+          let mut projected = objects.iter().map(|o| self.project(o)).collect::<Vec<_>>();
+          projected.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+          projected.into_iter().map(|(_, o)| o).collect()
+     }
+
+     /// Get the viewport showing the same region on a screen `factor` times
+     /// larger
+     pub fn scaled(&self, factor: f64) -> Self {
+          Self {
+               original_scale: self.original_scale * factor,
+               scale: self.scale * factor,
+               pixel_ratio: self.pixel_ratio * factor,
+               shift: self.shift * factor,
+               original_shift: self.original_shift * factor,
+               zoom_settings: self.zoom_settings,
+               zoom_target: self.zoom_target.map(|t| ZoomTarget {
+                    scale: t.scale * factor,
+                    anchor: t.anchor * factor
+               }),
+               yaw: self.yaw,
+               pitch: self.pitch
+          }
+     }
+}
@@ -0,0 +1,252 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::f64::consts::PI;
+
+use sdl2::pixels::Color;
+
+use super::{Viewport, ZoomSettings};
+use crate::{
+     common::{
+          vec2::{Vec2, Vec2F},
+          vec3::Vec3F
+     },
+     simulation::object::Object
+};
+
+fn assert_close(value: Vec2F, expected: Vec2F) {
+     assert!(
+          (value.x - expected.x).abs() < 1e-9 && (value.y - expected.y).abs() < 1e-9,
+          "{:?} instead of {:?}",
+          value,
+          expected
+     );
+}
+
+fn instant(viewport: Viewport) -> Viewport {
+     viewport.with_zoom_settings(ZoomSettings::new(2.0, 0.25, 8.0, 0.0).unwrap())
+}
+
+#[test]
+fn test_world_and_screen() {
+     let viewport = Viewport::new(2.0, Vec2F::new(100.0, 50.0));
+
+     assert_close(
+          viewport.world_to_screen(Vec2F::new(3.0, -4.0)),
+          Vec2F::new(106.0, 42.0)
+     );
+     assert_close(
+          viewport.screen_to_world(Vec2F::new(106.0, 42.0)),
+          Vec2F::new(3.0, -4.0)
+     );
+
+     // in space, through the camera
+     let mut viewport = viewport;
+     assert_close(
+          viewport.to_screen(Vec3F::new(3.0, -4.0, 7.0)),
+          Vec2F::new(106.0, 42.0)
+     );
+     viewport.rotate(PI / 2.0, 0.0);
+     assert_close(
+          viewport.to_screen(Vec3F::new(3.0, -4.0, 7.0)),
+          Vec2F::new(114.0, 42.0)
+     );
+}
+
+#[test]
+fn test_zoom_is_multiplicative() {
+     let mut viewport = instant(Viewport::new(1.0, Vec2F::new_null()));
+     let anchor = Vec2F::new(40.0, 30.0);
+
+     viewport.zoom(1.0, anchor);
+     assert_eq!(viewport.scale, 2.0);
+     viewport.zoom(2.0, anchor);
+     assert_eq!(viewport.scale, 8.0);
+     viewport.zoom(-3.0, anchor);
+     assert_eq!(viewport.scale, 1.0);
+
+     // within the limits, relative to the initial scale
+     viewport.zoom(10.0, anchor);
+     assert_eq!(viewport.scale, 8.0);
+     viewport.zoom(-10.0, anchor);
+     assert_eq!(viewport.scale, 0.25);
+}
+
+#[test]
+fn test_zoom_keeps_the_anchor() {
+     let mut viewport = instant(Viewport::new(1.5, Vec2F::new(10.0, -20.0)));
+     let anchor = Vec2F::new(250.0, 120.0);
+     let location = viewport.screen_to_world(anchor);
+
+     for steps in [1.0, 0.5, -2.0, 3.0] {
+          viewport.zoom(steps, anchor);
+
+          assert_close(viewport.world_to_screen(location), anchor);
+     }
+}
+
+#[test]
+fn test_smooth_zoom() {
+     let mut viewport = Viewport::new(1.0, Vec2F::new_null())
+          .with_zoom_settings(ZoomSettings::new(2.0, 0.1, 10.0, 0.1).unwrap());
+     let anchor = Vec2F::new(60.0, 20.0);
+     let location = viewport.screen_to_world(anchor);
+
+     // the steps add up before the scale moves
+     viewport.zoom(1.0, anchor);
+     viewport.zoom(1.0, anchor);
+     assert_eq!(viewport.scale, 1.0);
+     assert!(viewport.zooming());
+
+     viewport.update(0.05);
+     let halfway = viewport.scale;
+     assert!(halfway > 1.0 && halfway < 4.0, "{}", halfway);
+     assert_close(viewport.world_to_screen(location), anchor);
+
+     // and get there in the end
+     for _ in 0..100 {
+          viewport.update(0.016);
+     }
+     assert_eq!(viewport.scale, 4.0);
+     assert!(!viewport.zooming());
+     assert_close(viewport.world_to_screen(location), anchor);
+}
+
+#[test]
+fn test_reset_and_fit() {
+     let mut viewport = instant(Viewport::new(1.0, Vec2F::new(5.0, 5.0)));
+     viewport.zoom(2.0, Vec2F::new(30.0, 30.0));
+     viewport.pan(Vec2F::new(17.0, -4.0));
+     viewport.rotate(0.3, 0.1);
+
+     viewport.reset();
+     assert_eq!(viewport.scale, 1.0);
+     assert_close(viewport.shift, Vec2F::new(5.0, 5.0));
+
+     let star = |x, y| {
+          Object::new(
+               1.0,
+               Vec2F::new(x, y),
+               Vec2F::new_null(),
+               Vec2F::new_null(),
+               true,
+               Color::RED
+          )
+          .unwrap()
+     };
+
+     // the objects fill the screen on their widest side, centered
+     let mut viewport =
+          viewport.with_zoom_settings(ZoomSettings::new(2.0, 0.25, 100.0, 0.0).unwrap());
+     let objects = [star(-10.0, 0.0), star(10.0, 5.0), star(0.0, -5.0)];
+     viewport.fit(&objects, Vec2::new(200, 100));
+     assert!((viewport.scale - 9.0).abs() < 1e-9, "{}", viewport.scale);
+     assert_close(
+          viewport.world_to_screen(Vec2F::new(0.0, 0.0)),
+          Vec2F::new(100.0, 50.0)
+     );
+
+     // within the limits
+     let mut viewport = instant(viewport);
+     viewport.fit(&objects, Vec2::new(200, 100));
+     assert_eq!(viewport.scale, 8.0);
+}
+
+#[test]
+fn test_invalid_zoom_settings() {
+     assert!(ZoomSettings::new(1.5, 0.5, 2.0, 0.0).is_ok());
+     assert!(ZoomSettings::new(1.5, 1.0, 1.0, 0.1).is_ok());
+
+     for (rate, min, max, smoothing) in [
+          (0.0, 0.5, 2.0, 0.0),
+          (f64::NAN, 0.5, 2.0, 0.0),
+          (1.5, 0.0, 2.0, 0.0),
+          (1.5, 2.0, 0.5, 0.0),
+          (1.5, 0.5, f64::NAN, 0.0),
+          (1.5, 0.5, 2.0, -1.0)
+     ] {
+          assert!(ZoomSettings::new(rate, min, max, smoothing).is_err());
+     }
+}
+
+#[test]
+fn test_scaled() {
+     let mut viewport = Viewport::new(2.0, Vec2F::new(10.0, 20.0));
+     viewport.rotate(0.4, 0.2);
+     let scaled = viewport.scaled(3.0);
+
+     // the same drawing, three times larger
+     let location = Vec3F::new(1.0, 2.0, 3.0);
+     assert_close(
+          scaled.to_screen(location),
+          viewport.to_screen(location) * 3.0
+     );
+     assert_eq!(scaled.pixel_ratio, 3.0);
+}
+
+#[test]
+fn test_resize_keeps_the_region() {
+     let mut viewport = instant(Viewport::new(2.0, Vec2F::new(30.0, -10.0)));
+     let old = Vec2::new(400, 300);
+     let (min, max) = viewport.visible_region(old);
+
+     // wider: the same height, more seen on the sides
+     let new = Vec2::new(800, 300);
+     viewport.resize(old, new);
+     let (new_min, new_max) = viewport.visible_region(new);
+     assert_eq!(viewport.scale, 2.0);
+     assert_close(new_min + new_max, min + max);
+     assert_close(Vec2F::new(new_min.y, new_max.y), Vec2F::new(min.y, max.y));
+     assert!(new_min.x < min.x && new_max.x > max.x);
+
+     // twice larger on high DPI: the same region, drawn twice larger
+     let mut viewport = instant(Viewport::new(2.0, Vec2F::new(30.0, -10.0)));
+     viewport.resize(old, Vec2::new(800, 600));
+     assert_eq!(viewport.scale, 4.0);
+     let (new_min, new_max) = viewport.visible_region(Vec2::new(800, 600));
+     assert_close(new_min, min);
+     assert_close(new_max, max);
+
+     // back to the initial view after a reset, and within the same limits
+     viewport.reset();
+     assert_eq!(viewport.scale, 4.0);
+     viewport.zoom(10.0, Vec2F::new_null());
+     assert_eq!(viewport.scale, 32.0);
+
+     // an empty window, when minimized, changes nothing
+     let before = viewport.clone();
+     viewport.resize(Vec2::new(800, 600), Vec2::new(0, 0));
+     assert_eq!(viewport.scale, before.scale);
+     assert_close(viewport.shift, before.shift);
+}
+
+#[test]
+fn test_resize_during_a_zoom() {
+     let mut viewport = Viewport::new(1.0, Vec2F::new_null())
+          .with_zoom_settings(ZoomSettings::new(2.0, 0.1, 10.0, 0.1).unwrap());
+     let anchor = Vec2F::new(100.0, 100.0);
+     let location = viewport.screen_to_world(anchor);
+
+     viewport.zoom(1.0, anchor);
+     viewport.resize(Vec2::new(200, 200), Vec2::new(400, 400));
+     for _ in 0..100 {
+          viewport.update(0.016);
+     }
+
+     // the anchor followed the resize
+     assert_eq!(viewport.scale, 4.0);
+     assert_close(viewport.world_to_screen(location), Vec2F::new(200.0, 200.0));
+}
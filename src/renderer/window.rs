@@ -14,21 +14,33 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
-use sdl2::{render::Canvas, video::Window, VideoSubsystem};
+use sdl2::{
+     render::Canvas,
+     video::{FullscreenType, Window},
+     VideoSubsystem
+};
 
-/// Create a window with it's canvas
+use crate::common::vec2::Vec2;
+
+/// Create a window with it's canvas, the size being in logical pixels. With
+/// `high_dpi`, the canvas has all the pixels of the screen, so it can be
+/// larger than the window.
 pub fn create(
      video: VideoSubsystem,
      width: u32,
      height: u32,
      title: &str,
-     resizable: bool
+     resizable: bool,
+     high_dpi: bool
 ) -> Result<Canvas<Window>, String> {
      // create and configure the window builder
      let mut window_builder = video.window(title, width, height);
      if resizable {
           window_builder.resizable();
      }
+     if high_dpi {
+          window_builder.allow_highdpi();
+     }
 
      // build the window
      let window = window_builder.build().map_err(|e| e.to_string())?;
@@ -38,3 +50,28 @@ pub fn create(
 
      Ok(canvas)
 }
+
+/// Size of the canvas in physical pixels, and the number of them per logical
+/// pixel of the window
+pub fn drawable_size(canvas: &Canvas<Window>) -> (Vec2<u32>, f64) {
+     let (width, height) = canvas.window().drawable_size();
+     let (logical_width, _) = canvas.window().size();
+
+     let ratio = if logical_width > 0 {
+          width as f64 / logical_width as f64
+     } else {
+          1.0
+     };
+
+     (Vec2::new(width, height), ratio)
+}
+
+/// Switch between the window and fullscreen
+pub fn toggle_fullscreen(canvas: &mut Canvas<Window>) -> Result<(), String> {
+     let window = canvas.window_mut();
+
+     window.set_fullscreen(match window.fullscreen_state() {
+          FullscreenType::Off => FullscreenType::Desktop,
+          _ => FullscreenType::Off
+     })
+}
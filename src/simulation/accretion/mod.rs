@@ -0,0 +1,279 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use super::{
+     events::Event,
+     object::{Object, ObjectId, ObjectKind},
+     particles::Particles,
+     settings::InvalidSettingError
+};
+use crate::common::{
+     maths,
+     vec2::{Real, Vec2}
+};
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/// Settings of the tidal disruption of the objects passing near a black hole
+pub struct TidalDisruption {
+     /// Factor applied to the accretion radius to get the tidal radius
+     radius_factor: f64,
+
+     /// Number of fragments an object is torn into
+     fragments: u32,
+
+     /// Fragments can't be lighter than this, lighter objects aren't disrupted
+     min_mass: f64,
+
+     /// Length of the stream the fragments are spread on
+     spread: f64
+}
+
+impl TidalDisruption {
+     pub fn new(
+          radius_factor: f64,
+          fragments: u32,
+          min_mass: f64,
+          spread: f64
+     ) -> Result<Self, InvalidSettingError> {
+          if !(radius_factor > 0.0 && radius_factor.is_finite()) {
+               return Err(InvalidSettingError::new(
+                    "tidal radius factor",
+                    radius_factor,
+                    "positive"
+               ));
+          }
+          if fragments < 2 {
+               return Err(InvalidSettingError::new(
+                    "number of fragments",
+                    fragments,
+                    "at least 2"
+               ));
+          }
+          if !(min_mass >= 0.0 && min_mass.is_finite()) {
+               return Err(InvalidSettingError::new(
+                    "fragment minimum mass",
+                    min_mass,
+                    "positive or null"
+               ));
+          }
+          if !(spread >= 0.0 && spread.is_finite()) {
+               return Err(InvalidSettingError::new(
+                    "fragment spread",
+                    spread,
+                    "positive or null"
+               ));
+          }
+
+          Ok(Self {
+               radius_factor,
+               fragments,
+               min_mass,
+               spread
+          })
+     }
+}
+
+/// Settings of the accretion by the black holes.
+///
+/// Objects inside the accretion radius of a black hole are absorbed by it, if
+/// enabled the ones inside its tidal radius are torn into fragments.
+pub struct AccretionSettings {
+     disruption: Option<TidalDisruption>
+}
+
+impl AccretionSettings {
+     pub fn new(disruption: Option<TidalDisruption>) -> Self {
+          Self { disruption }
+     }
+}
+
+// =============================================================================
+// Functions
+// =============================================================================
+
+/// Tear the particle at index `i` into fragments spread along the direction of
+/// the black hole, conserving mass, momentum and center of mass.
+///
+/// The fragments are put in `fragments`, to be added to the particles once
+/// every one of them has been processed.
+fn disrupt<F: Real>(
+     particles: &mut Particles<F>,
+     i: usize,
+     black_hole: usize,
+     disruption: &TidalDisruption,
+     time: f64,
+     fragments: &mut Vec<Object<F>>
+) -> Event {
+     let object = particles.object(i);
+     let n = disruption.fragments;
+     let n_f = F::from(n).unwrap();
+
+     let direction = object.location - particles.location[black_hole];
+     let d = maths::compute_distance(Vec2::new_null(), direction);
+     let direction = if d == F::zero() {
+          Vec2::new(F::one(), F::zero())
+     } else {
+          direction / d
+     };
+
+     let mut ids = Vec::with_capacity(n as usize);
+     for k in 0..n {
+          // centered offsets, so the center of mass doesn't move
+          let offset = F::from((f64::from(k) + 0.5) / f64::from(n) - 0.5).unwrap();
+
+          let mut fragment = object.clone();
+          fragment.id = ObjectId::generate();
+          fragment.name = None;
+          fragment.parents = vec![object.id];
+          fragment.kind = ObjectKind::Fragment;
+          fragment.mass = object.mass / n_f;
+          fragment.force = object.force / n_f;
+          fragment.location =
+               object.location + direction * (offset * F::from(disruption.spread).unwrap());
+
+          ids.push(fragment.id);
+          fragments.push(fragment);
+     }
+
+     let black_hole = particles.ids[black_hole];
+     particles.swap_remove(i);
+
+     Event::TidalDisruption {
+          time,
+          black_hole,
+          object: object.cast(),
+          fragments: ids
+     }
+}
+
+/// Make the black hole with the given id absorb or disrupt the particles around
+fn apply_accretion<F: Real>(
+     particles: &mut Particles<F>,
+     black_hole: ObjectId,
+     settings: &AccretionSettings,
+     time: f64,
+     events: &mut Vec<Event>
+) {
+     // the black hole could have been absorbed by another one
+     let mut bh = match particles.ids.iter().position(|id| *id == black_hole) {
+          Some(bh) => bh,
+          None => return
+     };
+     let accretion_radius = match particles.kind[bh].accretion_radius() {
+          Some(r) => F::from(r).unwrap(),
+          None => return
+     };
+
+     let mut fragments = Vec::new();
+
+     let mut i = 0;
+     while i < particles.len() {
+          // anchors are never absorbed
+          if i == bh || particles.kind[i] == ObjectKind::Anchor {
+               i += 1;
+               continue;
+          }
+
+          let d = maths::compute_distance(particles.location[bh], particles.location[i]);
+
+          if d < accretion_radius {
+               let object = particles.object(i);
+
+               // when two black holes meet, the heaviest one absorbs the other
+               if particles.kind[i].accretion_radius().is_some() {
+                    let absorbed = particles.object(bh);
+                    let survivor = particles.merge(bh, i);
+
+                    if particles.ids[survivor] != black_hole {
+                         events.push(Event::Accretion {
+                              time,
+                              black_hole: object.id,
+                              object: absorbed.cast()
+                         });
+
+                         break;
+                    }
+
+                    bh = survivor;
+               } else {
+                    bh = particles.absorb(bh, i);
+               }
+
+               events.push(Event::Accretion {
+                    time,
+                    black_hole,
+                    object: object.cast()
+               });
+
+               // the last particle took the place of the absorbed one
+               continue;
+          }
+
+          if let Some(disruption) = &settings.disruption {
+               let can_be_disrupted = particles.kind[i] == ObjectKind::Star
+                    && particles.mass[i] / F::from(disruption.fragments).unwrap()
+                         >= F::from(disruption.min_mass).unwrap();
+
+               if can_be_disrupted
+                    && d < accretion_radius * F::from(disruption.radius_factor).unwrap()
+               {
+                    events.push(disrupt(particles, i, bh, disruption, time, &mut fragments));
+
+                    // the black hole could have been the last particle
+                    if bh == particles.len() {
+                         bh = i;
+                    }
+
+                    continue;
+               }
+          }
+
+          i += 1;
+     }
+
+     for fragment in fragments.iter() {
+          particles.push(fragment);
+     }
+}
+
+/// Apply the accretion of every black hole and return what happened
+pub fn apply_accretion_for_each<F: Real>(
+     particles: &mut Particles<F>,
+     settings: &AccretionSettings,
+     time: f64
+) -> Vec<Event> {
+     let mut events = Vec::new();
+
+     let black_holes = particles
+          .ids
+          .iter()
+          .zip(particles.kind.iter())
+          .filter(|(_, kind)| kind.accretion_radius().is_some())
+          .map(|(id, _)| *id)
+          .collect::<Vec<_>>();
+
+     for black_hole in black_holes {
+          apply_accretion(particles, black_hole, settings, time, &mut events);
+     }
+
+     events
+}
@@ -0,0 +1,153 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::pixels::Color;
+
+use super::{apply_accretion_for_each, AccretionSettings, TidalDisruption};
+use crate::{
+     common::vec2::Vec2F,
+     simulation::{
+          events::Event,
+          object::{Object, ObjectKind},
+          particles::Particles
+     }
+};
+
+fn black_hole() -> Object {
+     Object::new_of_kind(
+          ObjectKind::BlackHole {
+               accretion_radius: 10.0
+          },
+          1000.0,
+          Vec2F::new(0.0, 0.0),
+          Vec2F::new_null(),
+          Vec2F::new(1.0, 0.0),
+          true,
+          Color::RED
+     )
+     .unwrap()
+}
+
+fn star(mass: f64, location: Vec2F, velocity: Vec2F) -> Object {
+     Object::new(
+          mass,
+          location,
+          Vec2F::new_null(),
+          velocity,
+          true,
+          Color::CYAN
+     )
+     .unwrap()
+}
+
+fn total_momentum(particles: &Particles) -> Vec2F {
+     particles
+          .velocity
+          .iter()
+          .zip(particles.mass.iter())
+          .map(|(v, m)| *v * *m)
+          .sum()
+}
+
+#[test]
+fn test_accretion_conserves_mass_and_momentum() {
+     let bh = black_hole();
+     let bh_id = bh.id;
+     let inside = star(100.0, Vec2F::new(5.0, 0.0), Vec2F::new(0.0, 10.0));
+     let inside_id = inside.id;
+     let outside = star(100.0, Vec2F::new(50.0, 0.0), Vec2F::new(0.0, 10.0));
+
+     let mut particles = Particles::from_objects(&[inside, bh, outside]);
+     let momentum = total_momentum(&particles);
+
+     let events = apply_accretion_for_each(&mut particles, &AccretionSettings::new(None), 1.0);
+
+     assert_eq!(particles.len(), 2);
+     assert_eq!(particles.mass.iter().sum::<f64>(), 1200.0);
+     assert_eq!(total_momentum(&particles), momentum);
+
+     let bh = particles.ids.iter().position(|id| *id == bh_id).unwrap();
+     assert_eq!(particles.parents[bh], vec![inside_id]);
+
+     assert_eq!(events.len(), 1);
+     match &events[0] {
+          Event::Accretion {
+               black_hole, object, ..
+          } => {
+               assert_eq!(*black_hole, bh_id);
+               assert_eq!(object.id, inside_id);
+          },
+          _ => panic!("expected an accretion event")
+     }
+}
+
+#[test]
+fn test_tidal_disruption_fragments() {
+     let disruption = TidalDisruption::new(3.0, 4, 5.0, 8.0).unwrap();
+     let settings = AccretionSettings::new(Some(disruption));
+
+     let victim = star(100.0, Vec2F::new(20.0, 0.0), Vec2F::new(0.0, 10.0));
+     let victim_id = victim.id;
+     let too_light = star(10.0, Vec2F::new(-20.0, 0.0), Vec2F::new(0.0, 10.0));
+
+     let mut particles = Particles::from_objects(&[black_hole(), victim, too_light]);
+     let momentum = total_momentum(&particles);
+
+     let events = apply_accretion_for_each(&mut particles, &settings, 1.0);
+
+     // the black hole, the light star and 4 fragments
+     assert_eq!(particles.len(), 6);
+     assert_eq!(particles.mass.iter().sum::<f64>(), 1110.0);
+     assert_eq!(total_momentum(&particles), momentum);
+
+     let fragments = (0..particles.len())
+          .filter(|i| particles.parents[*i] == vec![victim_id])
+          .collect::<Vec<_>>();
+     assert_eq!(fragments.len(), 4);
+
+     // the center of mass of the fragments is where the star was
+     let center = fragments
+          .iter()
+          .map(|i| particles.location[*i] * particles.mass[*i])
+          .sum::<Vec2F>()
+          / 100.0;
+     assert_eq!(center, Vec2F::new(20.0, 0.0));
+
+     assert_eq!(events.len(), 1);
+     assert!(matches!(events[0], Event::TidalDisruption { .. }));
+
+     // heavy enough to be torn, but never torn again
+     assert!(fragments
+          .iter()
+          .all(|i| particles.kind[*i] == ObjectKind::Fragment));
+     let events = apply_accretion_for_each(&mut particles, &settings, 2.0);
+     assert!(events.is_empty());
+     assert_eq!(particles.len(), 6);
+}
+
+#[test]
+fn test_invalid_disruptions() {
+     assert!(TidalDisruption::new(3.0, 2, 5.0, 8.0).is_ok());
+
+     // the whole mass would vanish without fragments
+     assert!(TidalDisruption::new(3.0, 0, 5.0, 8.0).is_err());
+     assert!(TidalDisruption::new(3.0, 1, 5.0, 8.0).is_err());
+
+     assert!(TidalDisruption::new(0.0, 4, 5.0, 8.0).is_err());
+     assert!(TidalDisruption::new(f64::NAN, 4, 5.0, 8.0).is_err());
+     assert!(TidalDisruption::new(3.0, 4, -1.0, 8.0).is_err());
+     assert!(TidalDisruption::new(3.0, 4, 5.0, -8.0).is_err());
+}
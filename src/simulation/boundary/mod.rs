@@ -0,0 +1,284 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use anyhow::Context;
+
+use super::{
+     events::Event,
+     particles::Particles,
+     physics::compute_pair_potential,
+     settings::InvalidSettingError
+};
+use crate::common::{
+     maths,
+     units::{Dimension, UnitSystem},
+     vec2::{Real, Vec2, Vec2F}
+};
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/// The limits of the simulated domain, built by the functions checking them
+pub enum Boundary {
+     /// Remove the objects going farther than `radius` from `center`
+     Remove { center: Vec2F, radius: f64 },
+
+     /// Make the objects bounce on the edges of the box
+     Reflect { min: Vec2F, max: Vec2F },
+
+     /// Make the objects leaving the box come back by the opposite edge
+     Periodic { min: Vec2F, max: Vec2F }
+}
+
+impl Boundary {
+     pub fn remove(center: Vec2F, radius: f64) -> Result<Self, InvalidSettingError> {
+          if !(radius > 0.0 && radius.is_finite()) {
+               return Err(InvalidSettingError::new(
+                    "boundary radius",
+                    radius,
+                    "positive"
+               ));
+          }
+
+          Ok(Boundary::Remove { center, radius })
+     }
+
+     pub fn reflect(min: Vec2F, max: Vec2F) -> Result<Self, InvalidSettingError> {
+          check_box(min, max)?;
+
+          Ok(Boundary::Reflect { min, max })
+     }
+
+     pub fn periodic(min: Vec2F, max: Vec2F) -> Result<Self, InvalidSettingError> {
+          check_box(min, max)?;
+
+          Ok(Boundary::Periodic { min, max })
+     }
+
+     /// Parse `remove:<radius>` around the origin, `reflect:<min>:<max>` or
+     /// `periodic:<min>:<max>`, the corners of the boxes being written
+     /// `<x>,<y>`. The lengths are in `units` unless followed by their unit.
+     pub fn parse(s: &str, units: &UnitSystem) -> anyhow::Result<Self> {
+          let length = |v: &str| units.parse(v, Dimension::Length);
+          let corner = |v: &str| -> anyhow::Result<Vec2F> {
+               let (x, y) = v
+                    .split_once(',')
+                    .with_context(|| format!("'{}' is not a valid corner.", v))?;
+
+               Ok(Vec2F::new(length(x)?, length(y)?))
+          };
+
+          let boundary = match s.split(':').collect::<Vec<_>>().as_slice() {
+               ["remove", radius] => Boundary::remove(Vec2F::new_null(), length(radius)?)?,
+               ["reflect", min, max] => Boundary::reflect(corner(min)?, corner(max)?)?,
+               ["periodic", min, max] => Boundary::periodic(corner(min)?, corner(max)?)?,
+               _ => anyhow::bail!("'{}' is not a valid boundary.", s)
+          };
+
+          Ok(boundary)
+     }
+}
+
+/// Check the box has a size in both directions
+fn check_box(min: Vec2F, max: Vec2F) -> Result<(), InvalidSettingError> {
+     let valid = |min: f64, max: f64| min.is_finite() && max.is_finite() && min < max;
+
+     if valid(min.x, max.x) && valid(min.y, max.y) {
+          Ok(())
+     } else {
+          Err(InvalidSettingError::new(
+               "boundary box",
+               format!("{:?} to {:?}", min, max),
+               "larger than zero in both directions"
+          ))
+     }
+}
+
+/// Detect the objects escaping from the system, being unbound from it.
+///
+/// Only objects farther than `min_distance` from the center of mass are
+/// checked, so the ones passing quickly through the core aren't removed.
+pub struct EscaperDetector {
+     min_distance: f64
+}
+
+impl EscaperDetector {
+     pub fn new(min_distance: f64) -> Result<Self, InvalidSettingError> {
+          if !(min_distance >= 0.0 && min_distance.is_finite()) {
+               return Err(InvalidSettingError::new(
+                    "escaper distance",
+                    min_distance,
+                    "positive or null"
+               ));
+          }
+
+          Ok(Self { min_distance })
+     }
+}
+
+// =============================================================================
+// Functions
+// =============================================================================
+
+/// Reflect a coordinate and its velocity on the edges `min` and `max`
+fn reflect<F: Real>(x: &mut F, v: &mut F, min: F, max: F) {
+     if *x < min {
+          *x = min + min - *x;
+          *v = -*v;
+     } else if *x > max {
+          *x = max + max - *x;
+          *v = -*v;
+     }
+}
+
+/// Wrap a coordinate between `min` and `max`
+fn wrap<F: Real>(x: F, min: F, max: F) -> F {
+     let size = max - min;
+     let r = (x - min) % size;
+
+     if r < F::zero() {
+          min + r + size
+     } else {
+          min + r
+     }
+}
+
+/// Apply the boundary to every particle and return the removed ones
+pub fn apply_boundary_for_each<F: Real>(
+     particles: &mut Particles<F>,
+     boundary: &Boundary,
+     time: f64
+) -> Vec<Event> {
+     let mut events = Vec::new();
+
+     let mut i = 0;
+     while i < particles.len() {
+          // fixed particles stay where they are, whatever the boundary
+          if !particles.can_move[i] {
+               i += 1;
+               continue;
+          }
+
+          match boundary {
+               Boundary::Remove { center, radius } => {
+                    let d = maths::compute_distance(center.cast(), particles.location[i]);
+
+                    if d > F::from(*radius).unwrap() {
+                         events.push(Event::OutOfBounds {
+                              time,
+                              object: particles.swap_remove(i).cast()
+                         });
+
+                         // the last particle took the place of the removed one
+                         continue;
+                    }
+               },
+               Boundary::Reflect { min, max } => {
+                    let (min, max) = (min.cast::<F>(), max.cast::<F>());
+                    let (location, velocity) =
+                         (&mut particles.location[i], &mut particles.velocity[i]);
+
+                    reflect(&mut location.x, &mut velocity.x, min.x, max.x);
+                    reflect(&mut location.y, &mut velocity.y, min.y, max.y);
+               },
+               Boundary::Periodic { min, max } => {
+                    let (min, max) = (min.cast::<F>(), max.cast::<F>());
+                    let location = &mut particles.location[i];
+
+                    location.x = wrap(location.x, min.x, max.x);
+                    location.y = wrap(location.y, min.y, max.y);
+               }
+          }
+
+          i += 1;
+     }
+
+     events
+}
+
+/// Compute the specific energy of the particle at index `i` relative to the
+/// system, with `center_velocity` the velocity of its center of mass
+fn compute_specific_energy<F: Real>(
+     particles: &Particles<F>,
+     i: usize,
+     center_velocity: Vec2<F>,
+     g: F,
+     force_smoothings: F
+) -> F {
+     let v = particles.velocity[i] - center_velocity;
+     let kinetic = (v.x * v.x + v.y * v.y) / F::from(2.0).unwrap();
+
+     let mut potential = F::zero();
+     for j in 0..particles.len() {
+          if j == i || !particles.exerts_gravity[j] {
+               continue;
+          }
+
+          let d = maths::compute_distance(particles.location[i], particles.location[j]);
+          potential += g * particles.mass[j] * compute_pair_potential(d, force_smoothings);
+     }
+
+     kinetic + potential
+}
+
+/// Remove the particles unbound from the system and far enough from it, `g`
+/// being the gravitational constant
+pub fn remove_escapers<F: Real>(
+     particles: &mut Particles<F>,
+     detector: &EscaperDetector,
+     g: F,
+     force_smoothings: F,
+     time: f64
+) -> Vec<Event> {
+     let total_mass = particles.mass.iter().fold(F::zero(), |a, b| a + *b);
+     if total_mass == F::zero() {
+          return Vec::new();
+     }
+
+     let center = (0..particles.len())
+          .map(|i| particles.location[i] * particles.mass[i])
+          .sum::<Vec2<F>>()
+          / total_mass;
+     let center_velocity = (0..particles.len())
+          .map(|i| particles.velocity[i] * particles.mass[i])
+          .sum::<Vec2<F>>()
+          / total_mass;
+     let min_distance = F::from(detector.min_distance).unwrap();
+
+     // find them all before removing any, so the energies are consistent
+     let escapers = (0..particles.len())
+          .filter(|i| {
+               particles.can_move[*i]
+                    && maths::compute_distance(center, particles.location[*i]) > min_distance
+                    && compute_specific_energy(particles, *i, center_velocity, g, force_smoothings)
+                         > F::zero()
+          })
+          .collect::<Vec<_>>();
+
+     // remove from the end so the indices stay valid
+     escapers
+          .into_iter()
+          .rev()
+          .map(|i| Event::Escape {
+               time,
+               object: particles.swap_remove(i).cast()
+          })
+          .collect()
+}
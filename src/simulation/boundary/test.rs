@@ -0,0 +1,137 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::pixels::Color;
+
+use super::{apply_boundary_for_each, remove_escapers, Boundary, EscaperDetector};
+use crate::{
+     common::{constants::G, units::UnitSystem, vec2::Vec2F},
+     simulation::{events::Event, object::Object, particles::Particles}
+};
+
+fn star(mass: f64, location: Vec2F, velocity: Vec2F) -> Object {
+     Object::new(
+          mass,
+          location,
+          Vec2F::new_null(),
+          velocity,
+          true,
+          Color::CYAN
+     )
+     .unwrap()
+}
+
+#[test]
+fn test_remove_boundary() {
+     let inside = star(1.0, Vec2F::new(5.0, 0.0), Vec2F::new_null());
+     let outside = star(1.0, Vec2F::new(0.0, -15.0), Vec2F::new_null());
+     let outside_id = outside.id;
+
+     let mut particles = Particles::from_objects(&[outside, inside]);
+     let boundary = Boundary::remove(Vec2F::new_null(), 10.0).unwrap();
+
+     let events = apply_boundary_for_each(&mut particles, &boundary, 0.0);
+
+     assert_eq!(particles.len(), 1);
+     assert_eq!(events.len(), 1);
+     assert!(matches!(&events[0], Event::OutOfBounds { object, .. } if object.id == outside_id));
+}
+
+#[test]
+fn test_reflect_and_periodic_boundaries() {
+     let min = Vec2F::new(-10.0, -10.0);
+     let max = Vec2F::new(10.0, 10.0);
+
+     let mut particles =
+          Particles::from_objects(&[star(1.0, Vec2F::new(12.0, -11.0), Vec2F::new(1.0, -2.0))]);
+     apply_boundary_for_each(&mut particles, &Boundary::reflect(min, max).unwrap(), 0.0);
+
+     assert_eq!(particles.location[0], Vec2F::new(8.0, -9.0));
+     assert_eq!(particles.velocity[0], Vec2F::new(-1.0, 2.0));
+
+     let mut particles =
+          Particles::from_objects(&[star(1.0, Vec2F::new(12.0, -11.0), Vec2F::new(1.0, -2.0))]);
+     apply_boundary_for_each(&mut particles, &Boundary::periodic(min, max).unwrap(), 0.0);
+
+     assert_eq!(particles.location[0], Vec2F::new(-8.0, 9.0));
+     assert_eq!(particles.velocity[0], Vec2F::new(1.0, -2.0));
+}
+
+#[test]
+fn test_invalid_boundaries() {
+     let (min, max) = (Vec2F::new(-10.0, -10.0), Vec2F::new(10.0, 10.0));
+
+     assert!(Boundary::remove(min, 0.0).is_err());
+     assert!(Boundary::remove(min, f64::INFINITY).is_err());
+
+     // wrapping in an empty box would divide by zero
+     assert!(Boundary::periodic(min, Vec2F::new(10.0, -10.0)).is_err());
+     assert!(Boundary::reflect(max, min).is_err());
+     assert!(Boundary::periodic(min, Vec2F::new(f64::NAN, 10.0)).is_err());
+
+     assert!(EscaperDetector::new(0.0).is_ok());
+     assert!(EscaperDetector::new(-1.0).is_err());
+     assert!(EscaperDetector::new(f64::NAN).is_err());
+}
+
+#[test]
+fn test_parse_boundaries() {
+     let units = UnitSystem::Si;
+
+     match Boundary::parse("remove:2 km", &units).unwrap() {
+          Boundary::Remove { center, radius } => {
+               assert_eq!(center, Vec2F::new_null());
+               assert_eq!(radius, 2000.0);
+          },
+          _ => panic!("expected a removing boundary")
+     }
+     match Boundary::parse("periodic:-5,-1 km:5,1 km", &units).unwrap() {
+          Boundary::Periodic { min, max } => {
+               assert_eq!(min, Vec2F::new(-5.0, -1000.0));
+               assert_eq!(max, Vec2F::new(5.0, 1000.0));
+          },
+          _ => panic!("expected a periodic boundary")
+     }
+
+     assert!(Boundary::parse("reflect:0,0:0,1", &units).is_err());
+     assert!(Boundary::parse("reflect:0,0", &units).is_err());
+     assert!(Boundary::parse("bounce:10", &units).is_err());
+}
+
+#[test]
+fn test_escapers_detection() {
+     let core = star(1e12, Vec2F::new_null(), Vec2F::new_null());
+     // far and fast enough to be unbound
+     let escaper = star(1.0, Vec2F::new(1000.0, 0.0), Vec2F::new(100.0, 0.0));
+     let escaper_id = escaper.id;
+     // far but slow, still bound
+     let bound = star(1.0, Vec2F::new(-1000.0, 0.0), Vec2F::new(0.0, 0.1));
+     // fast but near, ignored
+     let near = star(1.0, Vec2F::new(10.0, 0.0), Vec2F::new(100.0, 0.0));
+
+     let mut particles = Particles::from_objects(&[core, escaper, bound, near]);
+     let events = remove_escapers(
+          &mut particles,
+          &EscaperDetector::new(500.0).unwrap(),
+          G,
+          0.0,
+          0.0
+     );
+
+     assert_eq!(particles.len(), 3);
+     assert_eq!(events.len(), 1);
+     assert!(matches!(&events[0], Event::Escape { object, .. } if object.id == escaper_id));
+}
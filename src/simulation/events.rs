@@ -0,0 +1,127 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt::Display;
+
+use super::object::{Object, ObjectId};
+use crate::common::units::{Dimension, UnitSystem};
+
+// =============================================================================
+// Type
+// =============================================================================
+
+/// Something that happened to the objects during the simulation.
+///
+/// The objects are recorded in the state they were in just before the event.
+#[derive(Clone)]
+pub enum Event {
+     /// An object fell inside the accretion radius of a black hole
+     Accretion {
+          time: f64,
+          black_hole: ObjectId,
+          object: Object
+     },
+
+     /// An object has been torn apart by the tides of a black hole
+     TidalDisruption {
+          time: f64,
+          black_hole: ObjectId,
+          object: Object,
+          fragments: Vec<ObjectId>
+     },
+
+     /// An object unbound from the system has been removed
+     Escape { time: f64, object: Object },
+
+     /// An object has been removed for leaving the simulation boundary
+     OutOfBounds { time: f64, object: Object }
+}
+
+impl Event {
+     /// Describe the event, the values being in the given units
+     pub fn display<'a>(&'a self, units: &'a UnitSystem) -> EventDisplay<'a> {
+          EventDisplay { event: self, units }
+     }
+}
+
+/// Description of an event with its values labelled, see [`Event::display`]
+pub struct EventDisplay<'a> {
+     event: &'a Event,
+     units: &'a UnitSystem
+}
+
+impl Display for EventDisplay<'_> {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          let units = self.units;
+          let time = |t: &f64| format!("{:e} {}", t, units.symbol(Dimension::Time));
+
+          match self.event {
+               Event::Accretion {
+                    time: t,
+                    black_hole,
+                    object
+               } => write!(
+                    f,
+                    "[t={}] accretion: {} absorbed {} (mass {})",
+                    time(t),
+                    black_hole,
+                    object.label(),
+                    units.format(object.mass, Dimension::Mass)
+               ),
+               Event::TidalDisruption {
+                    time: t,
+                    black_hole,
+                    object,
+                    fragments
+               } => write!(
+                    f,
+                    "[t={}] tidal disruption: {} tore {} (mass {}) into {} fragments",
+                    time(t),
+                    black_hole,
+                    object.label(),
+                    units.format(object.mass, Dimension::Mass),
+                    fragments.len()
+               ),
+               Event::Escape { time: t, object } => {
+                    write!(f, "[t={}] escape: removed ", time(t))?;
+                    write_final_state(f, object, units)
+               },
+               Event::OutOfBounds { time: t, object } => {
+                    write!(f, "[t={}] out of bounds: removed ", time(t))?;
+                    write_final_state(f, object, units)
+               }
+          }
+     }
+}
+
+fn write_final_state(
+     f: &mut std::fmt::Formatter<'_>,
+     object: &Object,
+     units: &UnitSystem
+) -> std::fmt::Result {
+     write!(
+          f,
+          "{} (mass {}, location ({}, {}) {}, velocity ({}, {}) {})",
+          object.label(),
+          units.format(object.mass, Dimension::Mass),
+          object.location.x,
+          object.location.y,
+          units.symbol(Dimension::Length),
+          object.velocity.x,
+          object.velocity.y,
+          units.symbol(Dimension::Velocity)
+     )
+}
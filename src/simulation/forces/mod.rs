@@ -0,0 +1,221 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use std::f64::consts::PI;
+
+use anyhow::Context;
+
+use super::{particles::Particles, settings::InvalidSettingError};
+use crate::common::{
+     maths,
+     units::{Dimension, UnitSystem},
+     vec2::Real,
+     vector::Vector
+};
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/// Chandrasekhar dynamical friction against a background which isn't
+/// resolved by the particles, uniform and at rest, with a Maxwellian
+/// distribution of velocities.
+pub struct DynamicalFriction {
+     /// Mass density of the background
+     density: f64,
+
+     /// One-dimensional velocity dispersion of the background
+     dispersion: f64,
+
+     /// Logarithm of the ratio of the largest and smallest impact parameters
+     coulomb_logarithm: f64,
+
+     /// Lighter objects, usually the ones making the resolved background,
+     /// aren't slowed down
+     min_mass: f64
+}
+
+impl DynamicalFriction {
+     pub fn new(
+          density: f64,
+          dispersion: f64,
+          coulomb_logarithm: f64,
+          min_mass: f64
+     ) -> Result<Self, InvalidSettingError> {
+          let non_negative = |v: f64| v.is_finite() && v >= 0.0;
+
+          if !(non_negative(density)
+               && non_negative(dispersion)
+               && dispersion > 0.0
+               && non_negative(coulomb_logarithm)
+               && non_negative(min_mass))
+          {
+               return Err(InvalidSettingError::new(
+                    "dynamical friction",
+                    format!(
+                         "density {}, dispersion {}, Coulomb logarithm {}, min mass {}",
+                         density, dispersion, coulomb_logarithm, min_mass
+                    ),
+                    "made of finite positive values, with a dispersion larger than zero"
+               ));
+          }
+
+          Ok(Self {
+               density,
+               dispersion,
+               coulomb_logarithm,
+               min_mass
+          })
+     }
+
+     /// Parse `<density>:<dispersion>:<Coulomb logarithm>:<min mass>`. The
+     /// dispersion and the mass are in `units` unless followed by their unit,
+     /// the density always being in `units`.
+     pub fn parse(s: &str, units: &UnitSystem) -> anyhow::Result<Self> {
+          let number = |v: &str| -> anyhow::Result<f64> {
+               v.parse()
+                    .with_context(|| format!("'{}' is not a valid number.", v))
+          };
+
+          match s.split(':').collect::<Vec<_>>().as_slice() {
+               [density, dispersion, coulomb_logarithm, min_mass] => Ok(Self::new(
+                    number(density)?,
+                    units.parse(dispersion, Dimension::Velocity)?,
+                    number(coulomb_logarithm)?,
+                    units.parse(min_mass, Dimension::Mass)?
+               )?),
+               _ => anyhow::bail!("'{}' is not a valid dynamical friction.", s)
+          }
+     }
+}
+
+/// Drag slowing every moving object down whatever its mass
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Drag {
+     /// `a = -rate v`. The product of the rate and the time step must stay
+     /// well below 1.
+     Linear { rate: f64 },
+
+     /// `a = -coefficient |v| v`
+     Quadratic { coefficient: f64 }
+}
+
+impl Drag {
+     /// Parse `linear:<rate>` or `quadratic:<coefficient>`, in the units of
+     /// the simulation
+     pub fn parse(s: &str) -> anyhow::Result<Self> {
+          let number = |v: &str| -> anyhow::Result<f64> {
+               v.parse()
+                    .with_context(|| format!("'{}' is not a valid number.", v))
+          };
+
+          let (drag, value) = match s.split_once(':') {
+               Some(("linear", rate)) => {
+                    let rate = number(rate)?;
+                    (Drag::Linear { rate }, rate)
+               },
+               Some(("quadratic", coefficient)) => {
+                    let coefficient = number(coefficient)?;
+                    (Drag::Quadratic { coefficient }, coefficient)
+               },
+               _ => anyhow::bail!("'{}' is not a valid drag.", s)
+          };
+
+          if !(value.is_finite() && value >= 0.0) {
+               return Err(InvalidSettingError::new("drag", s, "finite and positive").into());
+          }
+
+          Ok(drag)
+     }
+}
+
+/// A force added to the gravity at every step
+pub enum ForceModule {
+     DynamicalFriction(DynamicalFriction),
+     Drag(Drag)
+}
+
+// =============================================================================
+// Functions
+// =============================================================================
+
+/// Compute the deceleration of an object of mass `mass` moving at `velocity`
+/// through the background
+fn compute_dynamical_friction<F: Real, V: Vector<F>>(
+     mass: F,
+     velocity: V,
+     g: F,
+     friction: &DynamicalFriction
+) -> V {
+     let k = |value: f64| F::from(value).unwrap();
+
+     let v2 = velocity.dot(velocity);
+     if v2 == F::zero() {
+          return V::null();
+     }
+     let v = v2.sqrt();
+
+     // fraction of the background slower than the object
+     let x = v / (k(2.0_f64.sqrt()) * k(friction.dispersion));
+     let slower = maths::erf(x) - k(2.0 / PI.sqrt()) * x * (-x * x).exp();
+
+     let strength =
+          k(4.0 * PI * friction.density * friction.coulomb_logarithm) * g * g * mass * slower
+               / (v2 * v);
+
+     velocity * -strength
+}
+
+/// Compute the acceleration of the drag on an object moving at `velocity`
+fn compute_drag<F: Real, V: Vector<F>>(velocity: V, drag: &Drag) -> V {
+     match *drag {
+          Drag::Linear { rate } => velocity * -F::from(rate).unwrap(),
+          Drag::Quadratic { coefficient } => {
+               velocity * -(F::from(coefficient).unwrap() * velocity.dot(velocity).sqrt())
+          },
+     }
+}
+
+/// Add the accelerations of the force modules to the ones computed by the
+/// gravity solver, `g` being the gravitational constant
+pub fn apply_force_modules_for_each<F: Real, V: Vector<F>>(
+     particles: &mut Particles<F, V>,
+     g: F,
+     modules: &[ForceModule]
+) {
+     for module in modules {
+          for i in 0..particles.len() {
+               if !particles.can_move[i] {
+                    continue;
+               }
+
+               let velocity = particles.velocity[i];
+               particles.acceleration[i] += match module {
+                    ForceModule::DynamicalFriction(friction) => {
+                         if particles.mass[i] < F::from(friction.min_mass).unwrap() {
+                              continue;
+                         }
+
+                         compute_dynamical_friction(particles.mass[i], velocity, g, friction)
+                    },
+                    ForceModule::Drag(drag) => compute_drag(velocity, drag)
+               };
+          }
+     }
+}
@@ -0,0 +1,124 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::f64::consts::PI;
+
+use sdl2::pixels::Color;
+
+use super::{apply_force_modules_for_each, Drag, DynamicalFriction, ForceModule};
+use crate::{
+     common::{
+          constants::G,
+          units::UnitSystem,
+          vec2::{Vec2F, VecLength},
+          vec3::Vec3F
+     },
+     simulation::{object::Object, particles::Particles}
+};
+
+fn moving(mass: f64, velocity: Vec2F) -> Object {
+     Object::new(
+          mass,
+          Vec2F::new_null(),
+          Vec2F::new_null(),
+          velocity,
+          true,
+          Color::WHITE
+     )
+     .unwrap()
+}
+
+#[test]
+fn test_drag() {
+     let mut particles = Particles::from_objects(&[moving(1.0, Vec2F::new(3.0, 4.0))]);
+
+     apply_force_modules_for_each(
+          &mut particles,
+          G,
+          &[ForceModule::Drag(Drag::Linear { rate: 0.5 })]
+     );
+     assert_eq!(particles.acceleration[0], Vec2F::new(-1.5, -2.0));
+
+     particles.acceleration[0] = Vec2F::new_null();
+     apply_force_modules_for_each(
+          &mut particles,
+          G,
+          &[ForceModule::Drag(Drag::Quadratic { coefficient: 0.1 })]
+     );
+     assert!((particles.acceleration[0] - Vec2F::new(-1.5, -2.0)).length_f64() < 1e-12);
+}
+
+#[test]
+fn test_dynamical_friction() {
+     let (density, dispersion, coulomb_logarithm) = (1e3, 10.0, 5.0);
+     let modules = [ForceModule::DynamicalFriction(
+          DynamicalFriction::new(density, dispersion, coulomb_logarithm, 1e6).unwrap()
+     )];
+
+     // moving at sqrt(2) times the dispersion
+     let v = 2.0_f64.sqrt() * dispersion;
+     let mut particles = Particles::from_objects(&[
+          moving(1e9, Vec2F::new(v, 0.0)),
+          moving(1.0, Vec2F::new(v, 0.0))
+     ]);
+     apply_force_modules_for_each(&mut particles, G, &modules);
+
+     let slower = 0.842_700_792_9 - 2.0 / PI.sqrt() * (-1.0_f64).exp();
+     let expected = 4.0 * PI * G * G * 1e9 * density * coulomb_logarithm * slower / (v * v);
+
+     let a = particles.acceleration[0];
+     assert!(a.x < 0.0 && a.y == 0.0);
+     assert!((-a.x - expected).abs() < 1e-5 * expected);
+
+     // too light to be slowed down
+     assert_eq!(particles.acceleration[1], Vec2F::new_null());
+
+     // the same in space
+     let mut particles = Particles::from_objects(&[Object::new(
+          1e9,
+          Vec3F::new_null(),
+          Vec3F::new_null(),
+          Vec3F::new(0.0, 0.0, v),
+          true,
+          Color::WHITE
+     )
+     .unwrap()]);
+     apply_force_modules_for_each(&mut particles, G, &modules);
+
+     assert!((-particles.acceleration[0].z - expected).abs() < 1e-5 * expected);
+}
+
+#[test]
+fn test_parse_force_modules() {
+     let units = UnitSystem::Si;
+
+     let friction = DynamicalFriction::parse("1e3:2 km/s:5:1e6", &units).unwrap();
+     assert_eq!(friction.dispersion, 2000.0);
+     assert_eq!(friction.min_mass, 1e6);
+     assert!(DynamicalFriction::parse("1e3:0:5:1e6", &units).is_err());
+     assert!(DynamicalFriction::parse("1e3:10:5", &units).is_err());
+
+     assert_eq!(
+          Drag::parse("linear:0.5").unwrap(),
+          Drag::Linear { rate: 0.5 }
+     );
+     assert_eq!(
+          Drag::parse("quadratic:2").unwrap(),
+          Drag::Quadratic { coefficient: 2.0 }
+     );
+     assert!(Drag::parse("linear:-1").is_err());
+     assert!(Drag::parse("cubic:1").is_err());
+}
@@ -0,0 +1,355 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use std::f64::consts::PI;
+
+use anyhow::Context;
+
+use super::{
+     object::ObjectKind,
+     particles::Particles,
+     settings::InvalidSettingError,
+     tree::QuadTree
+};
+use crate::common::{
+     units::{Dimension, UnitSystem},
+     vec2::{Real, Vec2}
+};
+
+/// Maximum number of gas parcels in a leaf of the neighbour search tree
+const LEAF_SIZE: usize = 8;
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/// Relation between the pressure and the density of the gas
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EquationOfState {
+     /// `P = c^2 rho`, for a gas radiating its heat away at once
+     Isothermal { sound_speed: f64 },
+
+     /// `P = K rho^gamma`, for a gas compressed without exchanging heat. The
+     /// entropy being the same everywhere, `K` is a constant.
+     Adiabatic { k: f64, gamma: f64 }
+}
+
+impl EquationOfState {
+     fn pressure<F: Real>(&self, density: F) -> F {
+          match *self {
+               EquationOfState::Isothermal { sound_speed } => {
+                    F::from(sound_speed * sound_speed).unwrap() * density
+               },
+               EquationOfState::Adiabatic { k, gamma } => {
+                    F::from(k).unwrap() * density.powf(F::from(gamma).unwrap())
+               },
+          }
+     }
+
+     fn sound_speed<F: Real>(&self, density: F) -> F {
+          match *self {
+               EquationOfState::Isothermal { sound_speed } => F::from(sound_speed).unwrap(),
+               EquationOfState::Adiabatic { gamma, .. } => {
+                    if density > F::zero() {
+                         (F::from(gamma).unwrap() * self.pressure(density) / density).sqrt()
+                    } else {
+                         F::zero()
+                    }
+               },
+          }
+     }
+}
+
+/// Settings of the smoothed particle hydrodynamics of the gas parcels
+pub struct GasSettings {
+     /// Smoothing length `h`, the parcels interacting up to `2 h`
+     smoothing_length: f64,
+
+     equation_of_state: EquationOfState,
+
+     /// Coefficients of the linear and quadratic terms of Monaghan's
+     /// artificial viscosity, spreading the shocks over a few smoothing
+     /// lengths
+     viscosity_alpha: f64,
+     viscosity_beta: f64
+}
+
+impl GasSettings {
+     /// Create the settings with the usual viscosity coefficients, 1 and 2
+     pub fn new(
+          smoothing_length: f64,
+          equation_of_state: EquationOfState
+     ) -> Result<Self, InvalidSettingError> {
+          let non_negative = |v: f64| v.is_finite() && v >= 0.0;
+
+          if !(smoothing_length.is_finite() && smoothing_length > 0.0) {
+               return Err(InvalidSettingError::new(
+                    "smoothing length",
+                    smoothing_length,
+                    "finite and larger than zero"
+               ));
+          }
+
+          let valid = match equation_of_state {
+               EquationOfState::Isothermal { sound_speed } => non_negative(sound_speed),
+               EquationOfState::Adiabatic { k, gamma } => {
+                    non_negative(k) && gamma.is_finite() && gamma >= 1.0
+               },
+          };
+          if !valid {
+               return Err(InvalidSettingError::new(
+                    "equation of state",
+                    format!("{:?}", equation_of_state),
+                    "made of finite positive values, with gamma at least 1"
+               ));
+          }
+
+          Ok(Self {
+               smoothing_length,
+               equation_of_state,
+               viscosity_alpha: 1.0,
+               viscosity_beta: 2.0
+          })
+     }
+
+     /// Change the artificial viscosity coefficients
+     pub fn with_viscosity(mut self, alpha: f64, beta: f64) -> Result<Self, InvalidSettingError> {
+          if !(alpha.is_finite() && alpha >= 0.0 && beta.is_finite() && beta >= 0.0) {
+               return Err(InvalidSettingError::new(
+                    "viscosity",
+                    format!("{}:{}", alpha, beta),
+                    "made of finite positive coefficients"
+               ));
+          }
+
+          self.viscosity_alpha = alpha;
+          self.viscosity_beta = beta;
+          Ok(self)
+     }
+
+     /// Parse `isothermal:<sound speed>:<smoothing length>` or
+     /// `adiabatic:<k>:<gamma>:<smoothing length>`. The speeds and lengths are
+     /// in `units` unless followed by their unit, `k` always being in `units`.
+     pub fn parse(s: &str, units: &UnitSystem) -> anyhow::Result<Self> {
+          let number = |v: &str| -> anyhow::Result<f64> {
+               v.parse()
+                    .with_context(|| format!("'{}' is not a valid number.", v))
+          };
+
+          let parts = s.split(':').collect::<Vec<_>>();
+          let (equation_of_state, smoothing_length) = match parts.as_slice() {
+               ["isothermal", sound_speed, smoothing_length] => (
+                    EquationOfState::Isothermal {
+                         sound_speed: units.parse(sound_speed, Dimension::Velocity)?
+                    },
+                    smoothing_length
+               ),
+               ["adiabatic", k, gamma, smoothing_length] => (
+                    EquationOfState::Adiabatic {
+                         k: number(k)?,
+                         gamma: number(gamma)?
+                    },
+                    smoothing_length
+               ),
+               _ => anyhow::bail!("'{}' is not a valid gas.", s)
+          };
+
+          Ok(Self::new(
+               units.parse(smoothing_length, Dimension::Length)?,
+               equation_of_state
+          )?)
+     }
+}
+
+// =============================================================================
+// Kernel
+// =============================================================================
+
+/// Cubic spline kernel in the plane, of support `2 h`
+fn kernel<F: Real>(r: F, h: F) -> F {
+     let k = |value: f64| F::from(value).unwrap();
+
+     let sigma = k(10.0 / (7.0 * PI)) / (h * h);
+     let q = r / h;
+
+     if q < F::one() {
+          sigma * (F::one() - k(1.5) * q * q + k(0.75) * q * q * q)
+     } else if q < k(2.0) {
+          sigma * k(0.25) * (k(2.0) - q).powi(3)
+     } else {
+          F::zero()
+     }
+}
+
+/// Derivative of the kernel with respect to `r`
+fn kernel_derivative<F: Real>(r: F, h: F) -> F {
+     let k = |value: f64| F::from(value).unwrap();
+
+     let sigma = k(10.0 / (7.0 * PI)) / (h * h * h);
+     let q = r / h;
+
+     if q < F::one() {
+          sigma * (k(-3.0) * q + k(2.25) * q * q)
+     } else if q < k(2.0) {
+          sigma * k(-0.75) * (k(2.0) - q).powi(2)
+     } else {
+          F::zero()
+     }
+}
+
+// =============================================================================
+// Functions
+// =============================================================================
+
+/// Gas parcels of the particles, with a tree of their locations to find the
+/// neighbours
+struct Gas<F: Real> {
+     indices: Vec<usize>,
+     locations: Vec<Vec2<F>>,
+     tree: QuadTree<F>
+}
+
+impl<F: Real> Gas<F> {
+     fn new(particles: &Particles<F>) -> Self {
+          let indices = (0..particles.len())
+               .filter(|i| particles.kind[*i] == ObjectKind::Gas)
+               .collect::<Vec<_>>();
+          let locations = indices
+               .iter()
+               .map(|i| particles.location[*i])
+               .collect::<Vec<_>>();
+          let tree = QuadTree::new(&locations, LEAF_SIZE);
+
+          Self {
+               indices,
+               locations,
+               tree
+          }
+     }
+
+     /// Call `f` once for every pair of parcels closer than `radius`, with
+     /// their indices in the gas
+     fn for_each_pair(&self, radius: F, mut f: impl FnMut(usize, usize)) {
+          let mut neighbors = Vec::new();
+
+          for a in 0..self.locations.len() {
+               self.tree
+                    .find_neighbors(&self.locations, self.locations[a], radius, &mut neighbors);
+
+               for &b in neighbors.iter().filter(|b| **b > a) {
+                    f(a, b);
+               }
+          }
+     }
+
+     /// Density of every parcel, summing the kernel over its neighbours and
+     /// itself
+     fn compute_density(&self, particles: &Particles<F>, h: F) -> Vec<F> {
+          let mut density = self
+               .indices
+               .iter()
+               .map(|i| particles.mass[*i] * kernel(F::zero(), h))
+               .collect::<Vec<_>>();
+
+          self.for_each_pair(h + h, |a, b| {
+               let (i, j) = (self.indices[a], self.indices[b]);
+               let r = self.locations[a] - self.locations[b];
+               let w = kernel((r.x * r.x + r.y * r.y).sqrt(), h);
+
+               density[a] += particles.mass[j] * w;
+               density[b] += particles.mass[i] * w;
+          });
+
+          density
+     }
+}
+
+/// Compute the density of every particle, zero for the ones not made of gas
+#[cfg(test)]
+pub fn compute_density_for_each<F: Real>(
+     particles: &Particles<F>,
+     settings: &GasSettings
+) -> Vec<F> {
+     let gas = Gas::new(particles);
+     let h = F::from(settings.smoothing_length).unwrap();
+
+     let mut density = vec![F::zero(); particles.len()];
+     for (a, d) in gas.compute_density(particles, h).into_iter().enumerate() {
+          density[gas.indices[a]] = d;
+     }
+
+     density
+}
+
+/// Add the pressure and viscosity accelerations between the gas parcels to
+/// the ones computed by the gravity solver.
+///
+/// Every pair is evaluated once and both parcels get opposite forces, so the
+/// momentum is conserved.
+pub fn apply_gas_forces_for_each<F: Real>(particles: &mut Particles<F>, settings: &GasSettings) {
+     let k = |value: f64| F::from(value).unwrap();
+
+     let gas = Gas::new(particles);
+     let h = k(settings.smoothing_length);
+     let eos = settings.equation_of_state;
+
+     let density = gas.compute_density(particles, h);
+     let pressure = density.iter().map(|d| eos.pressure(*d)).collect::<Vec<_>>();
+
+     let mut acceleration = vec![Vec2::new_null(); gas.indices.len()];
+     gas.for_each_pair(h + h, |a, b| {
+          let (i, j) = (gas.indices[a], gas.indices[b]);
+
+          let r = gas.locations[a] - gas.locations[b];
+          let d = (r.x * r.x + r.y * r.y).sqrt();
+          if d == F::zero() {
+               return;
+          }
+
+          // artificial viscosity, only between approaching parcels
+          let v = particles.velocity[i] - particles.velocity[j];
+          let approach = v.x * r.x + v.y * r.y;
+          let viscosity = if approach < F::zero() {
+               let mu = h * approach / (d * d + k(0.01) * h * h);
+               let sound_speed =
+                    (eos.sound_speed(density[a]) + eos.sound_speed(density[b])) / k(2.0);
+               let mean_density = (density[a] + density[b]) / k(2.0);
+
+               (-k(settings.viscosity_alpha) * sound_speed * mu
+                    + k(settings.viscosity_beta) * mu * mu)
+                    / mean_density
+          } else {
+               F::zero()
+          };
+
+          let factor = pressure[a] / (density[a] * density[a])
+               + pressure[b] / (density[b] * density[b])
+               + viscosity;
+          let gradient = r * (kernel_derivative(d, h) / d);
+
+          acceleration[a] -= gradient * (particles.mass[j] * factor);
+          acceleration[b] += gradient * (particles.mass[i] * factor);
+     });
+
+     for (a, i) in gas.indices.iter().enumerate() {
+          if particles.can_move[*i] {
+               particles.acceleration[*i] += acceleration[a];
+          }
+     }
+}
@@ -0,0 +1,156 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use sdl2::pixels::Color;
+
+use super::{apply_gas_forces_for_each, compute_density_for_each, EquationOfState, GasSettings};
+use crate::{
+     common::{
+          units::UnitSystem,
+          vec2::{Vec2F, VecLength}
+     },
+     simulation::{
+          object::{Object, ObjectKind},
+          particles::Particles
+     }
+};
+
+fn gas(mass: f64, location: Vec2F, velocity: Vec2F) -> Object {
+     Object::new_of_kind(
+          ObjectKind::Gas,
+          mass,
+          location,
+          Vec2F::new_null(),
+          velocity,
+          true,
+          Color::GREY
+     )
+     .unwrap()
+}
+
+#[test]
+fn test_density_of_uniform_lattice() {
+     let objects = (0..30 * 30)
+          .map(|i| {
+               gas(
+                    1.0,
+                    Vec2F::new((i % 30) as f64, (i / 30) as f64),
+                    Vec2F::new_null()
+               )
+          })
+          .collect::<Vec<_>>();
+     let particles = Particles::from_objects(&objects);
+
+     let settings =
+          GasSettings::new(1.3, EquationOfState::Isothermal { sound_speed: 1.0 }).unwrap();
+     let density = compute_density_for_each(&particles, &settings);
+
+     // one unit of mass per unit of area, away from the edges
+     let center = density[15 * 30 + 15];
+     assert!((center - 1.0).abs() < 0.02, "density of {}", center);
+
+     // the parcels on the edges miss half of their neighbours
+     assert!(density[15 * 30] < 0.8 * center);
+}
+
+#[test]
+fn test_pressure_pushes_parcels_apart() {
+     let mut particles = Particles::from_objects(&[
+          gas(1.0, Vec2F::new(0.0, 0.0), Vec2F::new_null()),
+          gas(1.0, Vec2F::new(1.0, 0.0), Vec2F::new_null()),
+          Object::new(
+               1.0,
+               Vec2F::new(0.5, 0.0),
+               Vec2F::new_null(),
+               Vec2F::new_null(),
+               true,
+               Color::WHITE
+          )
+          .unwrap()
+     ]);
+
+     let settings = GasSettings::new(
+          1.0,
+          EquationOfState::Adiabatic {
+               k: 1.0,
+               gamma: 5.0 / 3.0
+          }
+     )
+     .unwrap();
+     apply_gas_forces_for_each(&mut particles, &settings);
+
+     assert!(particles.acceleration[0].x < 0.0);
+     assert!(particles.acceleration[1].x > 0.0);
+     assert_eq!(particles.acceleration[0].y, 0.0);
+
+     // the star doesn't feel the pressure
+     assert_eq!(particles.acceleration[2], Vec2F::new_null());
+}
+
+#[test]
+fn test_gas_forces_conserve_momentum() {
+     let mut rng = StdRng::seed_from_u64(11);
+     let objects = (0..500)
+          .map(|_| {
+               gas(
+                    rng.gen_range(0.5..2.0),
+                    Vec2F::new(rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0)),
+                    Vec2F::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0))
+               )
+          })
+          .collect::<Vec<_>>();
+     let mut particles = Particles::from_objects(&objects);
+
+     let settings =
+          GasSettings::new(1.0, EquationOfState::Isothermal { sound_speed: 2.0 }).unwrap();
+     apply_gas_forces_for_each(&mut particles, &settings);
+
+     let (mut momentum, mut scale) = (Vec2F::new_null(), 0.0);
+     for i in 0..particles.len() {
+          momentum += particles.acceleration[i] * particles.mass[i];
+          scale += (particles.acceleration[i] * particles.mass[i]).length_f64();
+     }
+
+     assert!(scale > 0.0);
+     assert!(momentum.length_f64() < 1e-12 * scale);
+}
+
+#[test]
+fn test_parse_gas() {
+     let units = UnitSystem::Si;
+
+     let settings = GasSettings::parse("isothermal:2 km/s:3 km", &units).unwrap();
+     assert_eq!(settings.smoothing_length, 3000.0);
+     assert_eq!(
+          settings.equation_of_state,
+          EquationOfState::Isothermal {
+               sound_speed: 2000.0
+          }
+     );
+
+     let settings = GasSettings::parse("adiabatic:0.5:1.4:10", &units).unwrap();
+     assert_eq!(
+          settings.equation_of_state,
+          EquationOfState::Adiabatic { k: 0.5, gamma: 1.4 }
+     );
+
+     assert!(GasSettings::parse("isothermal:2 km/s:0 m", &units).is_err());
+     assert!(GasSettings::parse("adiabatic:0.5:0.9:10", &units).is_err());
+     assert!(GasSettings::parse("adiabatic:0.5:10", &units).is_err());
+     assert!(GasSettings::parse("polytropic:1:2:3", &units).is_err());
+     assert!(settings.with_viscosity(-1.0, 2.0).is_err());
+}
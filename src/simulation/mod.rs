@@ -14,6 +14,16 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+pub mod accretion;
+pub mod boundary;
+pub mod events;
+pub mod forces;
+pub mod gas;
 pub mod object;
+pub mod particles;
 pub mod physics;
+pub mod relativity;
+pub mod save;
+pub mod settings;
 pub mod thread;
+pub mod tree;
@@ -29,10 +29,20 @@ pub enum VelocityDirection {
 use std::f64::consts::FRAC_PI_2;
 
 use super::Object;
-use crate::common::{constants::G, maths, vec2::Vec2F};
+use crate::common::{
+     maths,
+     vec2::{Vec2F, VecLength},
+     vec3::Vec3F
+};
 
-/// Make the object o turn around many others objects (called origins)
-pub fn add_orbital_velocity(o: &mut Object, origins: &Vec<Object>, direction: VelocityDirection) {
+/// Make the object o turn around many others objects (called origins), `g`
+/// being the gravitational constant
+pub fn add_orbital_velocity(
+     o: &mut Object,
+     origins: &Vec<Object>,
+     g: f64,
+     direction: VelocityDirection
+) {
      for origin in origins {
           let d = maths::compute_distance(o.location, origin.location);
 
@@ -40,7 +50,7 @@ pub fn add_orbital_velocity(o: &mut Object, origins: &Vec<Object>, direction: Ve
                continue;
           }
 
-          let v = ((G * origin.mass) / d).sqrt();
+          let v = ((g * origin.mass) / d).sqrt();
 
           let a = maths::compute_angle(o.location, origin.location);
           let v_vec = Vec2F::from_angle_value(
@@ -59,9 +69,35 @@ pub fn add_orbital_velocity(o: &mut Object, origins: &Vec<Object>, direction: Ve
 pub fn add_orbital_velocity_for_each(
      objects: &mut Vec<Object>,
      origins: &Vec<Object>,
+     g: f64,
      direction: VelocityDirection
 ) {
      for o in objects {
-          add_orbital_velocity(o, origins, direction)
+          add_orbital_velocity(o, origins, g, direction)
+     }
+}
+
+/// Make the object o turn around the origins in space, counterclockwise
+/// around `normal`
+pub fn add_orbital_velocity_in_space(
+     o: &mut Object<f64, Vec3F>,
+     origins: &[Object<f64, Vec3F>],
+     g: f64,
+     normal: Vec3F
+) {
+     for origin in origins {
+          let r = o.location - origin.location;
+          let d = r.length_f64();
+
+          // the direction is undefined along the normal
+          let direction = normal.cross(r);
+          let length = direction.length_f64();
+          if d == 0.0 || length == 0.0 {
+               continue;
+          }
+
+          let v = ((g * origin.mass) / d).sqrt();
+
+          o.velocity += direction * (v / length);
      }
 }
@@ -0,0 +1,146 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+     fmt::{Debug, Display},
+     str::FromStr
+};
+
+// =============================================================================
+// Enum
+// =============================================================================
+
+/// The class of an object, deciding how the physics handles it
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ObjectKind {
+     /// An ordinary body
+     Star,
+
+     /// A compact body, absorbing what comes inside its accretion radius
+     BlackHole { accretion_radius: f64 },
+
+     /// A massless tracer, feeling the gravity without exerting any
+     TestParticle,
+
+     /// A body fixed in place
+     Anchor,
+
+     /// A parcel of gas, also pushed by the pressure of the other ones
+     Gas,
+
+     /// A piece of a star torn by a black hole, never torn again
+     Fragment
+}
+
+impl ObjectKind {
+     /// Whether the object can be moved by the forces
+     pub fn can_move(&self) -> bool {
+          !matches!(self, ObjectKind::Anchor)
+     }
+
+     /// Whether the object attracts the others
+     pub fn exerts_gravity(&self) -> bool {
+          !matches!(self, ObjectKind::TestParticle)
+     }
+
+     /// Whether the mass is valid for this kind of object.
+     ///
+     /// Only test particles are allowed to be massless.
+     pub fn is_mass_valid(&self, mass: f64) -> bool {
+          match self {
+               ObjectKind::TestParticle => mass >= 0.0,
+               _ => mass > 0.0
+          }
+     }
+
+     /// Whether the object is compact enough for the post-Newtonian
+     /// corrections to apply between it and the other compact ones
+     pub fn is_compact(&self) -> bool {
+          matches!(self, ObjectKind::BlackHole { .. })
+     }
+
+     /// Get the accretion radius if the object is a black hole
+     pub fn accretion_radius(&self) -> Option<f64> {
+          match self {
+               ObjectKind::BlackHole { accretion_radius } => Some(*accretion_radius),
+               _ => None
+          }
+     }
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+pub struct ObjectKindParseError {
+     value: String
+}
+
+impl Debug for ObjectKindParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.debug_struct("ObjectKindParseError")
+               .field("value", &self.value)
+               .finish()
+     }
+}
+
+impl Display for ObjectKindParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          write!(f, "'{}' is not a valid object kind.", self.value)
+     }
+}
+
+impl std::error::Error for ObjectKindParseError {}
+
+impl Display for ObjectKind {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          match self {
+               ObjectKind::Star => f.write_str("star"),
+               ObjectKind::BlackHole { accretion_radius } => {
+                    write!(f, "black_hole:{}", accretion_radius)
+               },
+               ObjectKind::TestParticle => f.write_str("test_particle"),
+               ObjectKind::Anchor => f.write_str("anchor"),
+               ObjectKind::Gas => f.write_str("gas"),
+               ObjectKind::Fragment => f.write_str("fragment")
+          }
+     }
+}
+
+impl FromStr for ObjectKind {
+     type Err = ObjectKindParseError;
+
+     fn from_str(s: &str) -> Result<Self, Self::Err> {
+          let error = || ObjectKindParseError {
+               value: s.to_string()
+          };
+
+          match s.split_once(':') {
+               Some(("black_hole", r)) => Ok(ObjectKind::BlackHole {
+                    accretion_radius: r.parse().map_err(|_| error())?
+               }),
+               None => match s {
+                    "star" => Ok(ObjectKind::Star),
+                    "test_particle" => Ok(ObjectKind::TestParticle),
+                    "anchor" => Ok(ObjectKind::Anchor),
+                    "gas" => Ok(ObjectKind::Gas),
+                    "fragment" => Ok(ObjectKind::Fragment),
+                    _ => Err(error())
+               },
+               _ => Err(error())
+          }
+     }
+}
@@ -20,6 +20,12 @@ macro_rules! create_object_value_checked {
                .map_err(|e| anyhow::anyhow!(e))
                .with_context(|| "Couldn't create object.")?
      };
+
+     ($kind: expr, $mass: expr, $loc: expr, $can_move: expr, $color: expr) => {
+          Object::new_inactive_of_kind($kind, $mass, $loc, $can_move, $color)
+               .map_err(|e| anyhow::anyhow!(e))
+               .with_context(|| "Couldn't create object.")?
+     };
 }
 
 pub(crate) use create_object_value_checked;
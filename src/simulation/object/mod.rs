@@ -15,13 +15,22 @@
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
 mod initial;
+mod kind;
 pub(crate) mod macros;
-use std::fmt::{Debug, Display};
+use std::{
+     fmt::{Debug, Display},
+     sync::atomic::{AtomicU64, Ordering}
+};
 
 pub use initial::*;
+pub use kind::*;
 use sdl2::pixels::Color;
 
-use crate::common::vec2::Vec2F;
+use crate::common::{
+     maths,
+     vec2::{Real, Vec2, Vec2F},
+     vector::Vector
+};
 
 // =============================================================================
 // Error
@@ -51,61 +60,249 @@ impl Display for NegativeOrNullMassError {
      }
 }
 
+// =============================================================================
+// Identifier
+// =============================================================================
+
+static NEXT_OBJECT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Unique identifier of an object, kept across snapshots, merges and saves
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct ObjectId(u64);
+
+impl ObjectId {
+     /// Get a new identifier, never returned before
+     pub fn generate() -> Self {
+          Self(NEXT_OBJECT_ID.fetch_add(1, Ordering::Relaxed))
+     }
+
+     /// Get the identifier with the given value, making sure generated ones
+     /// won't collide with it, or `None` if it's the last one, which can't be
+     /// followed by generated ones
+     pub fn reserve(value: u64) -> Option<Self> {
+          NEXT_OBJECT_ID.fetch_max(value.checked_add(1)?, Ordering::Relaxed);
+
+          Some(Self(value))
+     }
+
+     pub fn value(&self) -> u64 {
+          self.0
+     }
+}
+
+impl Display for ObjectId {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          write!(f, "#{}", self.0)
+     }
+}
+
+static NEXT_GENERATOR_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Identifier shared by the objects created together by a generator, kept
+/// across snapshots and saves
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct GeneratorId(u64);
+
+impl GeneratorId {
+     /// Get a new identifier, never returned before
+     pub fn generate() -> Self {
+          Self(NEXT_GENERATOR_ID.fetch_add(1, Ordering::Relaxed))
+     }
+
+     /// Get the identifier with the given value, making sure generated ones
+     /// won't collide with it, or `None` if it's the last one, which can't be
+     /// followed by generated ones
+     pub fn reserve(value: u64) -> Option<Self> {
+          NEXT_GENERATOR_ID.fetch_max(value.checked_add(1)?, Ordering::Relaxed);
+
+          Some(Self(value))
+     }
+
+     pub fn value(&self) -> u64 {
+          self.0
+     }
+}
+
 // =============================================================================
 // Type
 // =============================================================================
 
-/// The type used by the physics engine
-#[derive(Clone, Copy)]
-pub struct Object {
-     pub mass: f64,
-     pub location: Vec2F,
-     pub force: Vec2F,
-     pub velocity: Vec2F,
+/// The type used by the physics engine, `F` being the float type of its
+/// physical values and `V` the type of its vectors
+#[derive(Clone)]
+pub struct Object<F: Real = f64, V: Vector<F> = Vec2<F>> {
+     pub id: ObjectId,
+     pub name: Option<String>,
+
+     /// The objects this one has been created from (merges, fragments...)
+     pub parents: Vec<ObjectId>,
+
+     /// The generator which created this object or the one it comes from
+     pub generator: Option<GeneratorId>,
+
+     pub mass: F,
+     pub location: V,
+     pub force: V,
+     pub velocity: V,
      pub can_move: bool,
+     pub kind: ObjectKind,
      pub color: Color
 }
 
-impl Object {
-     /// Create a new object if the mass is valid.
+impl<F: Real, V: Vector<F>> Object<F, V> {
+     /// Create a new object of the given kind if the mass is valid for it.
      /// Else return an error.
-     pub fn new(
-          mass: f64,
-          location: Vec2F,
-          force: Vec2F,
-          velocity: Vec2F,
+     pub fn new_of_kind(
+          kind: ObjectKind,
+          mass: F,
+          location: V,
+          force: V,
+          velocity: V,
           can_move: bool,
           color: Color
      ) -> Result<Self, NegativeOrNullMassError> {
-          if mass <= 0.0 {
-               Err(NegativeOrNullMassError { mass })
+          let mass_f64 = mass.to_f64().unwrap();
+
+          if !kind.is_mass_valid(mass_f64) {
+               Err(NegativeOrNullMassError { mass: mass_f64 })
           } else {
                Ok(Self {
+                    id: ObjectId::generate(),
+                    name: None,
+                    parents: Vec::new(),
+                    generator: None,
                     mass,
                     location,
                     force,
                     velocity,
-                    can_move,
+                    can_move: can_move && kind.can_move(),
+                    kind,
                     color
                })
           }
      }
 
-     /// Create a new inactive object if the mass is valid.
+     /// Create a new star if the mass is valid.
      /// Else return an error.
-     pub fn new_inactive(
-          mass: f64,
-          location: Vec2F,
+     pub fn new(
+          mass: F,
+          location: V,
+          force: V,
+          velocity: V,
           can_move: bool,
           color: Color
      ) -> Result<Self, NegativeOrNullMassError> {
-          Self::new(
+          Self::new_of_kind(
+               ObjectKind::Star,
                mass,
                location,
-               Vec2F::new_null(),
-               Vec2F::new_null(),
+               force,
+               velocity,
                can_move,
                color
           )
      }
+
+     /// Create a new inactive object of the given kind if the mass is valid
+     /// for it. Else return an error.
+     pub fn new_inactive_of_kind(
+          kind: ObjectKind,
+          mass: F,
+          location: V,
+          can_move: bool,
+          color: Color
+     ) -> Result<Self, NegativeOrNullMassError> {
+          Self::new_of_kind(kind, mass, location, V::null(), V::null(), can_move, color)
+     }
+
+     /// Create a new inactive star if the mass is valid.
+     /// Else return an error.
+     pub fn new_inactive(
+          mass: F,
+          location: V,
+          can_move: bool,
+          color: Color
+     ) -> Result<Self, NegativeOrNullMassError> {
+          Self::new(mass, location, V::null(), V::null(), can_move, color)
+     }
+
+     /// Give a name to the object
+     pub fn with_name(mut self, name: &str) -> Self {
+          self.name = Some(name.to_string());
+
+          self
+     }
+
+     /// Mark the object as created by the generator `generator`
+     pub fn with_generator(mut self, generator: GeneratorId) -> Self {
+          self.generator = Some(generator);
+
+          self
+     }
+
+     /// Get the name of the object, or its id if it has none
+     pub fn label(&self) -> String {
+          match &self.name {
+               Some(name) => name.clone(),
+               None => self.id.to_string()
+          }
+     }
+
+     /// Apply `f` to the vectors of the object, keeping the rest
+     pub fn map_vectors<W: Vector<F>>(&self, f: impl Fn(V) -> W) -> Object<F, W> {
+          Object {
+               id: self.id,
+               name: self.name.clone(),
+               parents: self.parents.clone(),
+               generator: self.generator,
+               mass: self.mass,
+               location: f(self.location),
+               force: f(self.force),
+               velocity: f(self.velocity),
+               can_move: self.can_move,
+               kind: self.kind,
+               color: self.color
+          }
+     }
+}
+
+impl<F: Real> Object<F> {
+     /// Convert the physical values to another float type
+     pub fn cast<T: Real>(&self) -> Object<T> {
+          Object {
+               id: self.id,
+               name: self.name.clone(),
+               parents: self.parents.clone(),
+               generator: self.generator,
+               mass: T::from(self.mass).unwrap(),
+               location: self.location.cast(),
+               force: self.force.cast(),
+               velocity: self.velocity.cast(),
+               can_move: self.can_move,
+               kind: self.kind,
+               color: self.color
+          }
+     }
+}
+
+// =============================================================================
+// Functions
+// =============================================================================
+
+/// Find an object from its id
+pub fn find_by_id<F: Real, V: Vector<F>>(
+     objects: &[Object<F, V>],
+     id: ObjectId
+) -> Option<&Object<F, V>> {
+     objects.iter().find(|o| o.id == id)
+}
+
+/// Find the object the nearest to `location`
+pub fn find_nearest(objects: &[Object], location: Vec2F) -> Option<&Object> {
+     objects.iter().min_by(|a, b| {
+          let d_a = maths::compute_distance(a.location, location);
+          let d_b = maths::compute_distance(b.location, location);
+
+          d_a.total_cmp(&d_b)
+     })
 }
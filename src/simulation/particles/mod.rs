@@ -0,0 +1,201 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use sdl2::pixels::Color;
+
+use super::object::{GeneratorId, Object, ObjectId, ObjectKind};
+use crate::common::{
+     vec2::{Real, Vec2},
+     vector::Vector
+};
+
+// =============================================================================
+// Type
+// =============================================================================
+
+/// Structure-of-arrays storage of the bodies used by the physics engine.
+///
+/// Every array has the same length, the values at the same index describing
+/// the same body. `F` is the float type of the physical values and `V` the
+/// type of the vectors.
+pub struct Particles<F: Real = f64, V: Vector<F> = Vec2<F>> {
+     pub ids: Vec<ObjectId>,
+     pub mass: Vec<F>,
+     pub location: Vec<V>,
+     pub velocity: Vec<V>,
+     pub acceleration: Vec<V>,
+     pub can_move: Vec<bool>,
+     pub exerts_gravity: Vec<bool>,
+     pub kind: Vec<ObjectKind>,
+
+     // not used by the physics, only kept to rebuild the objects
+     pub name: Vec<Option<String>>,
+     pub parents: Vec<Vec<ObjectId>>,
+     pub generator: Vec<Option<GeneratorId>>,
+     pub color: Vec<Color>
+}
+
+impl<F: Real, V: Vector<F>> Particles<F, V> {
+     /// Create an empty store with room for `capacity` particles
+     pub fn with_capacity(capacity: usize) -> Self {
+          Self {
+               ids: Vec::with_capacity(capacity),
+               mass: Vec::with_capacity(capacity),
+               location: Vec::with_capacity(capacity),
+               velocity: Vec::with_capacity(capacity),
+               acceleration: Vec::with_capacity(capacity),
+               can_move: Vec::with_capacity(capacity),
+               exerts_gravity: Vec::with_capacity(capacity),
+               kind: Vec::with_capacity(capacity),
+               name: Vec::with_capacity(capacity),
+               parents: Vec::with_capacity(capacity),
+               generator: Vec::with_capacity(capacity),
+               color: Vec::with_capacity(capacity)
+          }
+     }
+
+     /// Build the store from a list of objects, keeping their order
+     pub fn from_objects(objects: &[Object<F, V>]) -> Self {
+          let mut particles = Self::with_capacity(objects.len());
+
+          for o in objects {
+               particles.push(o);
+          }
+
+          particles
+     }
+
+     /// Add an object at the end of the store
+     pub fn push(&mut self, object: &Object<F, V>) {
+          self.ids.push(object.id);
+          self.mass.push(object.mass);
+          self.location.push(object.location);
+          self.velocity.push(object.velocity);
+          self.acceleration.push(
+               if object.mass == F::zero() {
+                    V::null()
+               } else {
+                    object.force / object.mass
+               }
+          );
+          self.can_move
+               .push(object.can_move && object.kind.can_move());
+          self.exerts_gravity.push(object.kind.exerts_gravity());
+          self.kind.push(object.kind);
+          self.name.push(object.name.clone());
+          self.parents.push(object.parents.clone());
+          self.generator.push(object.generator);
+          self.color.push(object.color);
+     }
+
+     /// Remove the particle at index `i` and return it as an object.
+     /// The last particle takes its place.
+     pub fn swap_remove(&mut self, i: usize) -> Object<F, V> {
+          let object = self.object(i);
+
+          self.ids.swap_remove(i);
+          self.mass.swap_remove(i);
+          self.location.swap_remove(i);
+          self.velocity.swap_remove(i);
+          self.acceleration.swap_remove(i);
+          self.can_move.swap_remove(i);
+          self.exerts_gravity.swap_remove(i);
+          self.kind.swap_remove(i);
+          self.name.swap_remove(i);
+          self.parents.swap_remove(i);
+          self.generator.swap_remove(i);
+          self.color.swap_remove(i);
+
+          object
+     }
+
+     /// Merge the particles at index `i` and `j` conserving mass and momentum.
+     ///
+     /// The heaviest one absorbs the other, see [`Particles::absorb`].
+     /// Return the index of the merged particle.
+     pub fn merge(&mut self, i: usize, j: usize) -> usize {
+          if self.mass[i] >= self.mass[j] {
+               self.absorb(i, j)
+          } else {
+               self.absorb(j, i)
+          }
+     }
+
+     /// Make the particle at index `into` absorb the one at index `from`,
+     /// conserving mass and momentum.
+     ///
+     /// The absorbing particle keeps its id, name and color and records the
+     /// other as one of its parents. Return its index once the other removed.
+     pub fn absorb(&mut self, into: usize, from: usize) -> usize {
+          let (m1, m2) = (self.mass[into], self.mass[from]);
+          let m = m1 + m2;
+
+          // a fixed particle stays where it is
+          if self.can_move[into] {
+               self.location[into] = (self.location[into] * m1 + self.location[from] * m2) / m;
+               self.velocity[into] = (self.velocity[into] * m1 + self.velocity[from] * m2) / m;
+          }
+          self.acceleration[into] =
+               (self.acceleration[into] * m1 + self.acceleration[from] * m2) / m;
+          self.mass[into] = m;
+
+          let from_id = self.ids[from];
+          self.parents[into].push(from_id);
+
+          self.swap_remove(from);
+
+          // the last particle moved at the removed index
+          if into == self.len() {
+               from
+          } else {
+               into
+          }
+     }
+
+     /// Rebuild the object at index `i`
+     pub fn object(&self, i: usize) -> Object<F, V> {
+          Object {
+               id: self.ids[i],
+               name: self.name[i].clone(),
+               parents: self.parents[i].clone(),
+               generator: self.generator[i],
+               mass: self.mass[i],
+               location: self.location[i],
+               force: self.acceleration[i] * self.mass[i],
+               velocity: self.velocity[i],
+               can_move: self.can_move[i],
+               kind: self.kind[i],
+               color: self.color[i]
+          }
+     }
+
+     /// Rebuild the list of objects, in the order of the store
+     pub fn to_objects(&self) -> Vec<Object<F, V>> {
+          (0..self.len()).map(|i| self.object(i)).collect()
+     }
+
+     /// Number of particles in the store
+     pub fn len(&self) -> usize {
+          self.ids.len()
+     }
+
+     pub fn is_empty(&self) -> bool {
+          self.ids.is_empty()
+     }
+}
@@ -0,0 +1,133 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::time::Instant;
+
+use rand::thread_rng;
+use sdl2::pixels::Color;
+
+use super::Particles;
+use crate::{
+     common::{constants::G, vec2::Vec2F},
+     generation,
+     simulation::{object::Object, physics}
+};
+
+#[test]
+fn test_objects_round_trip() {
+     let objects = [
+          Object::new(
+               10.0,
+               Vec2F::new(1.0, 2.0),
+               Vec2F::new(20.0, -10.0),
+               Vec2F::new(3.0, 4.0),
+               true,
+               Color::CYAN
+          )
+          .unwrap(),
+          Object::new_inactive(1000.0, Vec2F::new(0.0, 0.0), false, Color::RED).unwrap()
+     ];
+
+     let particles = Particles::from_objects(&objects);
+     assert_eq!(particles.len(), 2);
+     assert_ne!(particles.ids[0], particles.ids[1]);
+
+     for (o, p) in objects.iter().zip(particles.to_objects().iter()) {
+          assert_eq!(o.mass, p.mass);
+          assert_eq!(o.location, p.location);
+          assert_eq!(o.force, p.force);
+          assert_eq!(o.velocity, p.velocity);
+          assert_eq!(o.can_move, p.can_move);
+          assert_eq!(o.color, p.color);
+     }
+}
+
+#[test]
+fn test_merge_conserves_momentum() {
+     let heavy = Object::new(
+          30.0,
+          Vec2F::new(0.0, 0.0),
+          Vec2F::new_null(),
+          Vec2F::new(1.0, 0.0),
+          true,
+          Color::RED
+     )
+     .unwrap()
+     .with_name("heavy");
+     let light = Object::new(
+          10.0,
+          Vec2F::new(4.0, 0.0),
+          Vec2F::new_null(),
+          Vec2F::new(0.0, 3.0),
+          true,
+          Color::CYAN
+     )
+     .unwrap();
+     let other = Object::new_inactive(1.0, Vec2F::new(100.0, 0.0), true, Color::CYAN).unwrap();
+
+     let (heavy_id, light_id, other_id) = (heavy.id, light.id, other.id);
+     let mut particles = Particles::from_objects(&[light, heavy, other]);
+
+     let i = particles.merge(0, 1);
+     assert_eq!(particles.len(), 2);
+
+     let merged = particles.object(i);
+     assert_eq!(merged.id, heavy_id);
+     assert_eq!(merged.name.as_deref(), Some("heavy"));
+     assert_eq!(merged.parents, vec![light_id]);
+     assert_eq!(merged.mass, 40.0);
+     assert_eq!(merged.location, Vec2F::new(1.0, 0.0));
+     assert_eq!(merged.velocity * merged.mass, Vec2F::new(30.0, 30.0));
+
+     // the other particle keeps its identity
+     assert!(particles.to_objects().iter().any(|o| o.id == other_id));
+}
+
+/// Throughput of one engine substep on the particle store.
+///
+/// Run with:
+/// `cargo test --release bench_particles_step -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn bench_particles_step() {
+     let mut rng = thread_rng();
+
+     for number in [1_000, 10_000, 100_000] {
+          let objects = generation::generate_random_objects_in_circle(
+               &mut rng,
+               Vec2F::new_null(),
+               1000.0,
+               10.0..100.0,
+               number,
+               Color::CYAN
+          )
+          .unwrap();
+          let mut particles = Particles::from_objects(&objects);
+
+          let start = Instant::now();
+          physics::compute_particle_acceleration_for_each(&mut particles, G, 50.0);
+          physics::compute_particle_next_position_for_each(&mut particles, 7200.0);
+          let elapsed = start.elapsed().as_secs_f64();
+
+          println!(
+               "{:>7} bodies: {:>10.3} ms/step, {:>12.0} bodies/s, {:>14.0} pairs/s",
+               number,
+               elapsed * 1e3,
+               number as f64 / elapsed,
+               (number * (number - 1)) as f64 / elapsed
+          );
+     }
+}
@@ -0,0 +1,523 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use rustfft::num_complex::Complex;
+
+use super::{compute_pair_acceleration, compute_pair_potential};
+use crate::{
+     common::{maths, vec2::Vec2F},
+     simulation::{particles::Particles, settings::InvalidSettingError, tree::QuadTree}
+};
+
+/// Maximum number of particles in a leaf of the tree
+const LEAF_SIZE: usize = 16;
+
+/// Two cells interact through their expansions if the sum of their radii and
+/// of the smoothing length is smaller than this fraction of their distance
+const OPENING_ANGLE: f64 = 0.5;
+
+// =============================================================================
+// Expansions
+// =============================================================================
+
+/// Index of the coefficient of `z^k conj(z)^l` in an expansion
+fn index(k: usize, l: usize) -> usize {
+     let n = k + l;
+
+     n * (n + 1) / 2 + l
+}
+
+/// Number of coefficients of an expansion of the given order
+fn coefficients(order: usize) -> usize {
+     (order + 1) * (order + 2) / 2
+}
+
+fn to_complex(v: Vec2F) -> Complex<f64> {
+     Complex::new(v.x, v.y)
+}
+
+/// Fill `out` with the powers of `z`, from `z^0`
+fn compute_powers(z: Complex<f64>, out: &mut [Complex<f64>]) {
+     let mut power = Complex::new(1.0, 0.0);
+     for p in out.iter_mut() {
+          *p = power;
+          power *= z;
+     }
+}
+
+/// Coefficient `z^k conj(z)^l / (k! l!)` of a monomial, the powers of `z`
+/// being given
+fn monomial(powers: &[Complex<f64>], inv_factorials: &[f64], k: usize, l: usize) -> Complex<f64> {
+     powers[k] * powers[l].conj() * (inv_factorials[k] * inv_factorials[l])
+}
+
+/// Compute the derivatives `d^n g / dq^n` of the potential `g` of the engine's
+/// force as a function of the squared distance `q`, up to the length of `out`.
+///
+/// The force being `1 / (q + e)` with `e` the force smoothings,
+/// `g'(q) = -h(q) / 2` with `h(q) = q^(-1/2) (q + e)^(-1)`, whose Taylor
+/// coefficients are the product of the ones of both factors.
+fn compute_radial_derivatives(q: f64, force_smoothings: f64, out: &mut [f64]) {
+     let order = out.len() - 1;
+     let (d, e) = (q.sqrt(), force_smoothings);
+
+     out[0] = -compute_pair_potential(d, e);
+
+     // Taylor coefficients of q^(-1/2), then their product with the ones of
+     // (q + e)^(-1), computed in place from the highest
+     let mut a = 1.0 / d;
+     for m in 0..order {
+          out[m + 1] = a;
+          a *= (-0.5 - m as f64) / ((m + 1) as f64 * q);
+     }
+     for m in (0..order).rev() {
+          let mut c = 0.0;
+          for i in 0..=m {
+               c += out[i + 1] * (-1.0 / (q + e)).powi((m - i) as i32) / (q + e);
+          }
+          out[m + 1] = c;
+     }
+
+     let mut factorial = 1.0;
+     for (n, v) in out.iter_mut().enumerate().skip(1) {
+          *v *= -0.5 * factorial;
+          factorial *= n as f64;
+     }
+}
+
+// =============================================================================
+// Tree
+// =============================================================================
+
+struct Cell {
+     /// Center of the expansions, the center of mass if the cell has a mass
+     center: Vec2F,
+
+     /// Distance from the center to the farthest particle of the cell
+     radius: f64,
+
+     /// Range of the cell in the sorted indices
+     start: usize,
+     end: usize,
+
+     children: Vec<usize>
+}
+
+// =============================================================================
+// Type
+// =============================================================================
+
+/// Fast multipole method solver.
+///
+/// The particles are sorted in a quadtree, the cells far enough from each
+/// other interacting through complex multipole and local expansions of the
+/// given order and the near ones through direct summation. The engine's
+/// `1 / d^2` force doesn't derive from a harmonic potential in the plane, so
+/// the expansions are in both `z` and `conj(z)`, the derivatives of its radial
+/// potential being the ones along the squared distance. The force smoothing is
+/// part of that potential, so it also applies to the far field.
+///
+/// The error decreases geometrically with the order. The buffers of the
+/// expansions are kept from one evaluation to the next.
+pub struct FastMultipole {
+     order: usize,
+     factorials: Vec<f64>,
+     inv_factorials: Vec<f64>,
+
+     cells: Vec<Cell>,
+     indices: Vec<usize>,
+     acceleration: Vec<Vec2F>,
+
+     /// Expansions of the cells, one after the other
+     multipoles: Vec<Complex<f64>>,
+     locals: Vec<Complex<f64>>,
+
+     /// Buffers of a single interaction
+     powers: Vec<Complex<f64>>,
+     radial: Vec<f64>,
+     derivatives: Vec<Complex<f64>>
+}
+
+impl FastMultipole {
+     /// Create a solver using expansions of the given order
+     pub fn new(order: usize) -> Result<Self, InvalidSettingError> {
+          if order == 0 {
+               return Err(InvalidSettingError::new(
+                    "expansion order",
+                    order,
+                    "at least 1"
+               ));
+          }
+
+          let mut factorials = vec![1.0; order + 1];
+          for k in 1..=order {
+               factorials[k] = factorials[k - 1] * k as f64;
+          }
+
+          Ok(Self {
+               order,
+               inv_factorials: factorials.iter().map(|f| 1.0 / f).collect(),
+               factorials,
+               cells: Vec::new(),
+               indices: Vec::new(),
+               acceleration: Vec::new(),
+               multipoles: Vec::new(),
+               locals: Vec::new(),
+               powers: vec![Complex::default(); order + 1],
+               radial: vec![0.0; order + 1],
+               derivatives: vec![Complex::default(); coefficients(order)]
+          })
+     }
+
+     /// Build the tree and the multipole expansions of every cell
+     fn build(&mut self, particles: &Particles) {
+          let QuadTree { nodes, indices } = QuadTree::new(&particles.location, LEAF_SIZE);
+
+          self.cells.clear();
+          for node in nodes {
+               let cell_indices = &indices[node.start..node.end];
+
+               // center of mass, or center of the square for massless cells
+               let (mut mass, mut weighted) = (0.0, Vec2F::new_null());
+               for &i in cell_indices {
+                    if particles.exerts_gravity[i] {
+                         mass += particles.mass[i];
+                         weighted += particles.location[i] * particles.mass[i];
+                    }
+               }
+               let center = if mass > 0.0 {
+                    weighted / mass
+               } else {
+                    node.center
+               };
+
+               let radius = cell_indices
+                    .iter()
+                    .map(|i| maths::compute_distance(center, particles.location[*i]))
+                    .fold(0.0, f64::max);
+
+               self.cells.push(Cell {
+                    center,
+                    radius,
+                    start: node.start,
+                    end: node.end,
+                    children: node.children
+               });
+          }
+          self.indices = indices;
+
+          let Self {
+               order,
+               inv_factorials,
+               cells,
+               indices,
+               multipoles,
+               locals,
+               powers,
+               ..
+          } = self;
+          let (order, n) = (*order, coefficients(*order));
+
+          for expansions in [&mut *multipoles, &mut *locals] {
+               expansions.clear();
+               expansions.resize(cells.len() * n, Complex::default());
+          }
+
+          // children always come after their parent, so going backward is an
+          // upward pass
+          for c in (0..cells.len()).rev() {
+               let center = to_complex(cells[c].center);
+               let (head, tail) = multipoles.split_at_mut((c + 1) * n);
+               let multipole = &mut head[c * n..];
+
+               if cells[c].children.is_empty() {
+                    // particles to multipole
+                    for &i in &indices[cells[c].start..cells[c].end] {
+                         if !particles.exerts_gravity[i] {
+                              continue;
+                         }
+
+                         compute_powers(center - to_complex(particles.location[i]), powers);
+                         for degree in 0..=order {
+                              for l in 0..=degree {
+                                   let k = degree - l;
+                                   multipole[index(k, l)] +=
+                                        monomial(powers, inv_factorials, k, l) * particles.mass[i];
+                              }
+                         }
+                    }
+               } else {
+                    // multipole to multipole
+                    for &child in &cells[c].children {
+                         compute_powers(center - to_complex(cells[child].center), powers);
+                         let m = &tail[(child - c - 1) * n..][..n];
+
+                         for degree in 0..=order {
+                              for l in 0..=degree {
+                                   let k = degree - l;
+
+                                   let mut v = Complex::default();
+                                   for j in 0..=l {
+                                        for i in 0..=k {
+                                             v += m[index(i, j)]
+                                                  * monomial(powers, inv_factorials, k - i, l - j);
+                                        }
+                                   }
+                                   multipole[index(k, l)] += v;
+                              }
+                         }
+                    }
+               }
+          }
+     }
+
+     /// Compute the derivatives `d^(k+l) / dz^k dconj(z)^l` of the potential at
+     /// `r`, up to the order of the expansions
+     fn compute_derivatives(&mut self, r: Complex<f64>, force_smoothings: f64) {
+          let Self {
+               order,
+               factorials,
+               inv_factorials,
+               powers,
+               radial,
+               derivatives,
+               ..
+          } = self;
+
+          compute_radial_derivatives(r.norm_sqr(), force_smoothings, radial);
+          compute_powers(r, powers);
+
+          // the potential being g(z conj(z)), its derivative (k, l) is the sum
+          // over j of C(k, j) C(l, j) j! conj(z)^(k-j) z^(l-j) g^(k+l-j)
+          for degree in 0..=*order {
+               for l in 0..=degree {
+                    let k = degree - l;
+
+                    let mut v = Complex::default();
+                    for j in 0..=k.min(l) {
+                         let c = factorials[k] * factorials[l] / factorials[j]
+                              * inv_factorials[k - j]
+                              * inv_factorials[l - j];
+
+                         v += powers[k - j].conj() * powers[l - j] * (c * radial[degree - j]);
+                    }
+                    derivatives[index(k, l)] = v;
+               }
+          }
+     }
+
+     /// Make the far cells `a` and `b` interact through their expansions
+     fn multipole_to_local(&mut self, a: usize, b: usize, force_smoothings: f64) {
+          let n = coefficients(self.order);
+
+          let r = to_complex(self.cells[a].center - self.cells[b].center);
+          self.compute_derivatives(r, force_smoothings);
+
+          for degree in 0..=self.order {
+               for j in 0..=degree {
+                    let i = degree - j;
+
+                    let (mut to_a, mut to_b) = (Complex::default(), Complex::default());
+                    for m in 0..=(self.order - degree) {
+                         // derivatives of the opposite vector change sign with
+                         // the parity of their degree
+                         let sign = if (m + degree) % 2 == 0 { 1.0 } else { -1.0 };
+
+                         for l in 0..=m {
+                              let k = m - l;
+                              let d = self.derivatives[index(k + i, l + j)];
+
+                              to_a += d * self.multipoles[b * n + index(k, l)];
+                              to_b += d * self.multipoles[a * n + index(k, l)] * sign;
+                         }
+                    }
+
+                    self.locals[a * n + index(i, j)] += to_a;
+                    self.locals[b * n + index(i, j)] += to_b;
+               }
+          }
+     }
+
+     /// Direct summation between the particles of the cells `a` and `b`, or
+     /// inside `a` if both are the same
+     fn particles_to_particles(
+          &mut self,
+          particles: &Particles,
+          a: usize,
+          b: usize,
+          force_smoothings: f64
+     ) {
+          let (a_start, a_end) = (self.cells[a].start, self.cells[a].end);
+          let (b_start, b_end) = (self.cells[b].start, self.cells[b].end);
+
+          for ia in a_start..a_end {
+               let i = self.indices[ia];
+
+               let b_first = if a == b { ia + 1 } else { b_start };
+               for ib in b_first..b_end {
+                    let j = self.indices[ib];
+
+                    let pair = compute_pair_acceleration(
+                         particles.location[j] - particles.location[i],
+                         force_smoothings
+                    );
+
+                    if particles.exerts_gravity[j] {
+                         self.acceleration[i] += pair * particles.mass[j];
+                    }
+                    if particles.exerts_gravity[i] {
+                         self.acceleration[j] -= pair * particles.mass[i];
+                    }
+               }
+          }
+     }
+
+     /// Dual tree walk, making every pair of cells interact once
+     fn interact(&mut self, particles: &Particles, a: usize, b: usize, force_smoothings: f64) {
+          let a_leaf = self.cells[a].children.is_empty();
+          let b_leaf = self.cells[b].children.is_empty();
+
+          if a == b {
+               if a_leaf {
+                    self.particles_to_particles(particles, a, a, force_smoothings);
+               } else {
+                    let count = self.cells[a].children.len();
+                    for k1 in 0..count {
+                         for k2 in k1..count {
+                              let (c1, c2) =
+                                   (self.cells[a].children[k1], self.cells[a].children[k2]);
+                              self.interact(particles, c1, c2, force_smoothings);
+                         }
+                    }
+               }
+
+               return;
+          }
+
+          // the expansions only converge far enough from the smoothing
+          let d = maths::compute_distance(self.cells[a].center, self.cells[b].center);
+          let extent = self.cells[a].radius + self.cells[b].radius + force_smoothings.sqrt();
+
+          if extent < OPENING_ANGLE * d {
+               self.multipole_to_local(a, b, force_smoothings);
+          } else if a_leaf && b_leaf {
+               self.particles_to_particles(particles, a, b, force_smoothings);
+          } else if b_leaf || (!a_leaf && self.cells[a].radius >= self.cells[b].radius) {
+               for k in 0..self.cells[a].children.len() {
+                    let c = self.cells[a].children[k];
+                    self.interact(particles, c, b, force_smoothings);
+               }
+          } else {
+               for k in 0..self.cells[b].children.len() {
+                    let c = self.cells[b].children[k];
+                    self.interact(particles, a, c, force_smoothings);
+               }
+          }
+     }
+
+     /// Translate the local expansions down the tree and evaluate them at the
+     /// particles of the leaves
+     fn evaluate_locals(&mut self, particles: &Particles) {
+          let Self {
+               order,
+               inv_factorials,
+               cells,
+               indices,
+               acceleration,
+               locals,
+               powers,
+               ..
+          } = self;
+          let (order, n) = (*order, coefficients(*order));
+
+          // parents always come before their children, so going forward is a
+          // downward pass
+          for c in 0..cells.len() {
+               let center = to_complex(cells[c].center);
+               let (head, tail) = locals.split_at_mut((c + 1) * n);
+               let local = &head[c * n..];
+
+               // local to local
+               for &child in &cells[c].children {
+                    compute_powers(to_complex(cells[child].center) - center, powers);
+                    let child_local = &mut tail[(child - c - 1) * n..][..n];
+
+                    for degree in 0..=order {
+                         for j in 0..=degree {
+                              let i = degree - j;
+
+                              let mut v = Complex::default();
+                              for m in degree..=order {
+                                   for l in j..=(m - i) {
+                                        let k = m - l;
+                                        v += local[index(k, l)]
+                                             * monomial(powers, inv_factorials, k - i, l - j);
+                                   }
+                              }
+                              child_local[index(i, j)] += v;
+                         }
+                    }
+               }
+
+               // local to particles, the acceleration being the gradient of
+               // the potential, 2 d/dconj(z)
+               if cells[c].children.is_empty() {
+                    for &i in &indices[cells[c].start..cells[c].end] {
+                         compute_powers(to_complex(particles.location[i]) - center, powers);
+
+                         let mut a = Complex::<f64>::default();
+                         for degree in 0..order {
+                              for l in 0..=degree {
+                                   let k = degree - l;
+                                   a += local[index(k, l + 1)]
+                                        * monomial(powers, inv_factorials, k, l);
+                              }
+                         }
+
+                         acceleration[i] += Vec2F::new(2.0 * a.re, 2.0 * a.im);
+                    }
+               }
+          }
+     }
+
+     /// Compute the acceleration of each particle, `g` being the gravitational
+     /// constant
+     pub fn compute_acceleration_for_each(
+          &mut self,
+          particles: &mut Particles,
+          g: f64,
+          force_smoothings: f64
+     ) {
+          if particles.is_empty() {
+               return;
+          }
+
+          self.build(particles);
+          self.acceleration.clear();
+          self.acceleration.resize(particles.len(), Vec2F::new_null());
+
+          self.interact(particles, 0, 0, force_smoothings);
+          self.evaluate_locals(particles);
+
+          for i in 0..particles.len() {
+               particles.acceleration[i] = if particles.can_move[i] {
+                    self.acceleration[i] * g
+               } else {
+                    Vec2F::new_null()
+               };
+          }
+     }
+}
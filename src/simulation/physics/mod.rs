@@ -0,0 +1,201 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+mod fmm;
+mod pm;
+mod simd;
+#[cfg(test)]
+mod test;
+
+use anyhow::Context;
+pub use fmm::FastMultipole;
+pub use pm::ParticleMesh;
+
+use super::particles::Particles;
+use crate::common::{
+     units::{Dimension, UnitSystem},
+     vec2::{Real, Vec2F},
+     vector::Vector
+};
+
+/// Acceleration per unit of `G` times the source mass felt by a particle,
+/// with `r` the vector going from it to the source
+fn compute_pair_acceleration<F: Real, V: Vector<F>>(r: V, force_smoothings: F) -> V {
+     let d2 = r.dot(r);
+     if d2 == F::zero() {
+          return V::null();
+     }
+
+     r / (d2.sqrt() * (d2 + force_smoothings))
+}
+
+/// Potential energy per unit of `G` times both masses of a pair at the
+/// distance `d`, the one the force of `compute_pair_acceleration` derives from.
+///
+/// The force being `1 / (d^2 + e)` with `e` the force smoothings, the
+/// potential is `-atan(sqrt(e) / d) / sqrt(e)`, null at infinity and finite at
+/// a null distance unless `e` is null.
+pub fn compute_pair_potential<F: Real>(d: F, force_smoothings: F) -> F {
+     if force_smoothings > F::zero() {
+          let e = force_smoothings.sqrt();
+
+          -(e / d).atan() / e
+     } else {
+          -d.recip()
+     }
+}
+
+// =============================================================================
+// Solvers
+// =============================================================================
+
+/// The method used to compute the gravity
+pub enum ForceSolver {
+     /// Sum the attraction of every pair of particles
+     Direct,
+
+     /// Sum the attraction of every pair of particles with SIMD instructions,
+     /// or without if the processor doesn't support them
+     DirectSimd,
+
+     /// Solve the gravity on a grid, in a periodic box
+     ParticleMesh(ParticleMesh),
+
+     /// Make the far groups of particles interact through multipole expansions
+     FastMultipole(FastMultipole)
+}
+
+impl ForceSolver {
+     /// Parse `direct`, `simd`, `pm:<grid size>:<box size>`, the box of the
+     /// mesh being centered on the origin, or `fmm:<order>`. The lengths
+     /// are in `units` unless followed by their unit.
+     pub fn parse(s: &str, units: &UnitSystem) -> anyhow::Result<Self> {
+          let solver = match s.split(':').collect::<Vec<_>>().as_slice() {
+               ["direct"] => ForceSolver::Direct,
+               ["simd"] => ForceSolver::DirectSimd,
+               ["pm", grid_size, size] => {
+                    let grid_size = grid_size
+                         .parse()
+                         .with_context(|| format!("'{}' is not a valid grid size.", grid_size))?;
+                    let size = units.parse(size, Dimension::Length)?;
+
+                    ForceSolver::ParticleMesh(ParticleMesh::new(
+                         grid_size,
+                         Vec2F::new(-size / 2.0, -size / 2.0),
+                         size
+                    )?)
+               },
+               ["fmm", order] => {
+                    let order = order
+                         .parse()
+                         .with_context(|| format!("'{}' is not a valid order.", order))?;
+
+                    ForceSolver::FastMultipole(FastMultipole::new(order)?)
+               },
+               _ => anyhow::bail!("'{}' is not a valid solver.", s)
+          };
+
+          Ok(solver)
+     }
+}
+
+/// Compute the acceleration each particle is affected by with the given
+/// solver, `g` being the gravitational constant in the units of the
+/// simulation
+pub fn compute_acceleration_for_each(
+     particles: &mut Particles,
+     solver: &mut ForceSolver,
+     g: f64,
+     force_smoothings: f64
+) {
+     match solver {
+          ForceSolver::Direct => {
+               compute_particle_acceleration_for_each(particles, g, force_smoothings)
+          },
+          ForceSolver::DirectSimd => {
+               simd::compute_acceleration_for_each(particles, g, force_smoothings, true)
+          },
+          ForceSolver::ParticleMesh(pm) => pm.compute_acceleration_for_each(particles, g),
+          ForceSolver::FastMultipole(fmm) => {
+               fmm.compute_acceleration_for_each(particles, g, force_smoothings)
+          },
+     }
+}
+
+// =============================================================================
+// Direct summation
+// =============================================================================
+
+/// Compute the global acceleration each particle is affected by.
+///
+/// Each pair is evaluated once and both particles get opposite contributions,
+/// so the momentum is conserved to round-off.
+pub fn compute_particle_acceleration_for_each<F: Real, V: Vector<F>>(
+     particles: &mut Particles<F, V>,
+     g: F,
+     force_smoothings: F
+) {
+     let Particles {
+          mass,
+          location,
+          acceleration,
+          exerts_gravity,
+          ..
+     } = particles;
+
+     acceleration.fill(V::null());
+
+     for i in 0..location.len() {
+          let mut a = V::null();
+
+          for j in (i + 1)..location.len() {
+               let pair = compute_pair_acceleration(location[j] - location[i], force_smoothings);
+
+               // test particles don't attract the others
+               if exerts_gravity[j] {
+                    a += pair * mass[j];
+               }
+               if exerts_gravity[i] {
+                    acceleration[j] -= pair * mass[i];
+               }
+          }
+
+          acceleration[i] += a;
+     }
+
+     for a in acceleration.iter_mut() {
+          *a = *a * g;
+     }
+}
+
+// =============================================================================
+// Integration
+// =============================================================================
+
+/// Compute all particles' next position
+pub fn compute_particle_next_position_for_each<F: Real, V: Vector<F>>(
+     particles: &mut Particles<F, V>,
+     delta_t: F
+) {
+     for i in 0..particles.len() {
+          if !particles.can_move[i] {
+               continue;
+          }
+
+          particles.velocity[i] += particles.acceleration[i] * delta_t;
+          particles.location[i] += particles.velocity[i] * delta_t;
+     }
+}
@@ -0,0 +1,208 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{f64::consts::PI, sync::Arc};
+
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+
+use crate::{
+     common::vec2::Vec2F,
+     simulation::{particles::Particles, settings::InvalidSettingError}
+};
+
+// =============================================================================
+// Type
+// =============================================================================
+
+/// Particle-mesh gravity solver in a periodic square box.
+///
+/// The masses are assigned to a grid with the cloud-in-cell scheme, the
+/// potential is solved in Fourier space and the accelerations are
+/// interpolated back with the same scheme. The Green's function is the one of
+/// the `1 / d^2` force used by the direct summation, not the 2D logarithmic
+/// one, and the grid acts as a softening of about one cell.
+pub struct ParticleMesh {
+     grid_size: usize,
+     min: Vec2F,
+     size: f64,
+
+     fft: Arc<dyn Fft<f64>>,
+     ifft: Arc<dyn Fft<f64>>,
+
+     density: Vec<Complex<f64>>,
+     acceleration_x: Vec<Complex<f64>>,
+     acceleration_y: Vec<Complex<f64>>
+}
+
+impl ParticleMesh {
+     /// Create a solver for the box starting at `min` of side `size`, divided
+     /// in `grid_size` cells on each side
+     pub fn new(grid_size: usize, min: Vec2F, size: f64) -> Result<Self, InvalidSettingError> {
+          if grid_size < 2 {
+               return Err(InvalidSettingError::new(
+                    "grid size",
+                    grid_size,
+                    "at least 2"
+               ));
+          }
+          if !(size.is_finite() && size > 0.0 && min.x.is_finite() && min.y.is_finite()) {
+               return Err(InvalidSettingError::new(
+                    "mesh box",
+                    format!("{:?} of side {}", min, size),
+                    "finite and larger than zero"
+               ));
+          }
+
+          let mut planner = FftPlanner::new();
+
+          Ok(Self {
+               grid_size,
+               min,
+               size,
+               fft: planner.plan_fft_forward(grid_size),
+               ifft: planner.plan_fft_inverse(grid_size),
+               density: vec![Complex::default(); grid_size * grid_size],
+               acceleration_x: vec![Complex::default(); grid_size * grid_size],
+               acceleration_y: vec![Complex::default(); grid_size * grid_size]
+          })
+     }
+
+     /// Get the four cells around a location with their cloud-in-cell weights
+     fn cic_cells(&self, location: Vec2F) -> [(usize, f64); 4] {
+          let n = self.grid_size;
+          let h = self.size / n as f64;
+
+          // cell centers are at (i + 0.5) * h
+          let gx = (location.x - self.min.x) / h - 0.5;
+          let gy = (location.y - self.min.y) / h - 0.5;
+
+          let (x0, y0) = (gx.floor(), gy.floor());
+          let (dx, dy) = (gx - x0, gy - y0);
+
+          let wrap = |i: f64| (i as i64).rem_euclid(n as i64) as usize;
+          let (x0, x1) = (wrap(x0), wrap(x0 + 1.0));
+          let (y0, y1) = (wrap(y0), wrap(y0 + 1.0));
+
+          [
+               (y0 * n + x0, (1.0 - dx) * (1.0 - dy)),
+               (y0 * n + x1, dx * (1.0 - dy)),
+               (y1 * n + x0, (1.0 - dx) * dy),
+               (y1 * n + x1, dx * dy)
+          ]
+     }
+
+     /// Wave number of the frequency at index `i`
+     fn wave_number(&self, i: usize) -> f64 {
+          let n = self.grid_size;
+          let i = if i <= n / 2 {
+               i as f64
+          } else {
+               i as f64 - n as f64
+          };
+
+          2.0 * PI * i / self.size
+     }
+
+     /// Compute the acceleration of each particle, `g` being the gravitational
+     /// constant
+     pub fn compute_acceleration_for_each(&mut self, particles: &mut Particles, g: f64) {
+          let n = self.grid_size;
+          let cell_area = (self.size / n as f64).powi(2);
+
+          // mass assignment, as a surface density
+          self.density.fill(Complex::default());
+          for i in 0..particles.len() {
+               if !particles.exerts_gravity[i] {
+                    continue;
+               }
+
+               for (cell, w) in self.cic_cells(particles.location[i]) {
+                    self.density[cell].re += particles.mass[i] * w / cell_area;
+               }
+          }
+
+          fft_2d(&mut self.density, n, self.fft.as_ref());
+
+          // potential then acceleration in Fourier space:
+          // phi(k) = -2 pi g density(k) / |k| and a(k) = -i k phi(k)
+          for y in 0..n {
+               let ky = self.wave_number(y);
+
+               for x in 0..n {
+                    let kx = self.wave_number(x);
+                    let k = (kx.powi(2) + ky.powi(2)).sqrt();
+
+                    let cell = y * n + x;
+                    if k == 0.0 {
+                         self.acceleration_x[cell] = Complex::default();
+                         self.acceleration_y[cell] = Complex::default();
+
+                         continue;
+                    }
+
+                    let phi = self.density[cell] * (-2.0 * PI * g / k);
+                    let minus_i_phi = Complex::new(phi.im, -phi.re);
+
+                    self.acceleration_x[cell] = minus_i_phi * kx;
+                    self.acceleration_y[cell] = minus_i_phi * ky;
+               }
+          }
+
+          fft_2d(&mut self.acceleration_x, n, self.ifft.as_ref());
+          fft_2d(&mut self.acceleration_y, n, self.ifft.as_ref());
+
+          // the inverse transform isn't normalized
+          let norm = (n * n) as f64;
+
+          // interpolation back with the same weights, so no self force
+          for i in 0..particles.len() {
+               // like in the direct summation, no stale acceleration is left
+               if !particles.can_move[i] {
+                    particles.acceleration[i] = Vec2F::new_null();
+                    continue;
+               }
+
+               let mut a = Vec2F::new_null();
+               for (cell, w) in self.cic_cells(particles.location[i]) {
+                    a += Vec2F::new(self.acceleration_x[cell].re, self.acceleration_y[cell].re) * w;
+               }
+
+               particles.acceleration[i] = a / norm;
+          }
+     }
+}
+
+// =============================================================================
+// Functions
+// =============================================================================
+
+/// Transpose a square matrix in place
+fn transpose(data: &mut [Complex<f64>], n: usize) {
+     for y in 0..n {
+          for x in (y + 1)..n {
+               data.swap(y * n + x, x * n + y);
+          }
+     }
+}
+
+/// Apply the transform on the rows then on the columns of a square grid
+fn fft_2d(data: &mut [Complex<f64>], n: usize, fft: &dyn Fft<f64>) {
+     // process transforms every consecutive chunk of the length of the plan
+     fft.process(data);
+     transpose(data, n);
+     fft.process(data);
+     transpose(data, n);
+}
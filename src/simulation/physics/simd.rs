@@ -0,0 +1,172 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+use crate::{common::vec2::Vec2F, simulation::particles::Particles};
+
+// =============================================================================
+// Type
+// =============================================================================
+
+/// Locations and masses packed in separate arrays, the particles not exerting
+/// gravity having a null mass
+struct Packed {
+     x: Vec<f64>,
+     y: Vec<f64>,
+     mass: Vec<f64>
+}
+
+impl Packed {
+     fn new(particles: &Particles) -> Self {
+          Self {
+               x: particles.location.iter().map(|l| l.x).collect(),
+               y: particles.location.iter().map(|l| l.y).collect(),
+               mass: (0..particles.len())
+                    .map(|i| {
+                         if particles.exerts_gravity[i] {
+                              particles.mass[i]
+                         } else {
+                              0.0
+                         }
+                    })
+                    .collect()
+          }
+     }
+}
+
+// =============================================================================
+// Kernels
+// =============================================================================
+
+/// Sum of `m / (d (d^2 + eps))` times the vector to each source from
+/// `start` onward, the sources at the same location being skipped
+fn accumulate_scalar(packed: &Packed, start: usize, xi: f64, yi: f64, eps: f64) -> (f64, f64) {
+     let (mut ax, mut ay) = (0.0, 0.0);
+
+     for j in start..packed.x.len() {
+          let (dx, dy) = (packed.x[j] - xi, packed.y[j] - yi);
+          let d2 = dx * dx + dy * dy;
+          if d2 == 0.0 {
+               continue;
+          }
+
+          let f = packed.mass[j] / (d2.sqrt() * (d2 + eps));
+          ax += f * dx;
+          ay += f * dy;
+     }
+
+     (ax, ay)
+}
+
+/// Same as [`accumulate_scalar`], four sources at a time
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn accumulate_avx(packed: &Packed, xi: f64, yi: f64, eps: f64) -> (f64, f64) {
+     let n = packed.x.len();
+     let chunks = n / 4;
+
+     let (vxi, vyi, veps) = (_mm256_set1_pd(xi), _mm256_set1_pd(yi), _mm256_set1_pd(eps));
+     let zero = _mm256_setzero_pd();
+     let (mut vax, mut vay) = (zero, zero);
+
+     for c in 0..chunks {
+          let j = c * 4;
+
+          let dx = _mm256_sub_pd(_mm256_loadu_pd(packed.x.as_ptr().add(j)), vxi);
+          let dy = _mm256_sub_pd(_mm256_loadu_pd(packed.y.as_ptr().add(j)), vyi);
+          let m = _mm256_loadu_pd(packed.mass.as_ptr().add(j));
+
+          let d2 = _mm256_add_pd(_mm256_mul_pd(dx, dx), _mm256_mul_pd(dy, dy));
+          let f = _mm256_div_pd(
+               m,
+               _mm256_mul_pd(_mm256_sqrt_pd(d2), _mm256_add_pd(d2, veps))
+          );
+
+          // the lanes at a null distance are not finite, drop them
+          let f = _mm256_and_pd(f, _mm256_cmp_pd::<_CMP_GT_OQ>(d2, zero));
+
+          vax = _mm256_add_pd(vax, _mm256_mul_pd(f, dx));
+          vay = _mm256_add_pd(vay, _mm256_mul_pd(f, dy));
+     }
+
+     let (mut lanes_x, mut lanes_y) = ([0.0; 4], [0.0; 4]);
+     _mm256_storeu_pd(lanes_x.as_mut_ptr(), vax);
+     _mm256_storeu_pd(lanes_y.as_mut_ptr(), vay);
+
+     let (rx, ry) = accumulate_scalar(packed, chunks * 4, xi, yi, eps);
+
+     (
+          lanes_x.iter().sum::<f64>() + rx,
+          lanes_y.iter().sum::<f64>() + ry
+     )
+}
+
+// =============================================================================
+// Functions
+// =============================================================================
+
+/// Whether the vectorized kernel can run on this processor
+pub fn is_available() -> bool {
+     #[cfg(target_arch = "x86_64")]
+     {
+          is_x86_feature_detected!("avx")
+     }
+
+     #[cfg(not(target_arch = "x86_64"))]
+     {
+          false
+     }
+}
+
+/// Compute the global acceleration each particle is affected by, summing
+/// over every other particle with the vectorized kernel if `vectorized` is
+/// set and available, with the scalar one otherwise
+pub fn compute_acceleration_for_each(
+     particles: &mut Particles,
+     g: f64,
+     force_smoothings: f64,
+     vectorized: bool
+) {
+     let packed = Packed::new(particles);
+     let vectorized = vectorized && is_available();
+
+     for i in 0..particles.len() {
+          if !particles.can_move[i] {
+               particles.acceleration[i] = Vec2F::new_null();
+               continue;
+          }
+
+          let (xi, yi) = (packed.x[i], packed.y[i]);
+
+          #[cfg(target_arch = "x86_64")]
+          let (ax, ay) = if vectorized {
+               // SAFETY: the processor supports AVX, checked by is_available
+               unsafe { accumulate_avx(&packed, xi, yi, force_smoothings) }
+          } else {
+               accumulate_scalar(&packed, 0, xi, yi, force_smoothings)
+          };
+
+          #[cfg(not(target_arch = "x86_64"))]
+          let (ax, ay) = {
+               let _ = vectorized;
+               accumulate_scalar(&packed, 0, xi, yi, force_smoothings)
+          };
+
+          particles.acceleration[i] = Vec2F::new(ax, ay) * g;
+     }
+}
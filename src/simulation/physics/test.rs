@@ -0,0 +1,538 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::time::Instant;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use sdl2::pixels::Color;
+
+use super::{
+     compute_acceleration_for_each,
+     compute_pair_acceleration,
+     compute_pair_potential,
+     compute_particle_acceleration_for_each,
+     compute_particle_next_position_for_each,
+     simd,
+     FastMultipole,
+     ForceSolver,
+     ParticleMesh
+};
+use crate::{
+     common::{
+          constants::G,
+          units::UnitSystem,
+          vec2::{Vec2F, VecLength},
+          vec3::Vec3F
+     },
+     simulation::{
+          object::{self, Object, ObjectKind},
+          particles::Particles
+     }
+};
+
+#[test]
+fn test_test_particles_only_feel_gravity() {
+     let star = Object::new_inactive(100.0, Vec2F::new(0.0, 0.0), true, Color::CYAN).unwrap();
+     let tracer = Object::new_inactive_of_kind(
+          ObjectKind::TestParticle,
+          0.0,
+          Vec2F::new(10.0, 0.0),
+          true,
+          Color::WHITE
+     )
+     .unwrap();
+
+     let mut particles = Particles::from_objects(&[star, tracer]);
+     compute_particle_acceleration_for_each(&mut particles, G, 0.0);
+
+     assert_eq!(particles.acceleration[0], Vec2F::new_null());
+     assert!(particles.acceleration[1].x < 0.0);
+}
+
+#[test]
+fn test_anchors_do_not_move() {
+     let anchor = Object::new_inactive_of_kind(
+          ObjectKind::Anchor,
+          100.0,
+          Vec2F::new(0.0, 0.0),
+          true,
+          Color::RED
+     )
+     .unwrap();
+     let star = Object::new_inactive(100.0, Vec2F::new(10.0, 0.0), true, Color::CYAN).unwrap();
+
+     let mut particles = Particles::from_objects(&[anchor, star]);
+     compute_particle_acceleration_for_each(&mut particles, G, 0.0);
+     compute_particle_next_position_for_each(&mut particles, 1.0);
+
+     assert_eq!(particles.location[0], Vec2F::new(0.0, 0.0));
+     assert!(particles.acceleration[1].length_f64() > 0.0);
+}
+
+#[test]
+fn test_solvers_reset_fixed_particles() {
+     let anchor = Object::new_inactive_of_kind(
+          ObjectKind::Anchor,
+          100.0,
+          Vec2F::new(0.0, 0.0),
+          true,
+          Color::RED
+     )
+     .unwrap();
+     let star = Object::new_inactive(100.0, Vec2F::new(10.0, 0.0), true, Color::CYAN).unwrap();
+
+     let solvers = [
+          ForceSolver::DirectSimd,
+          ForceSolver::ParticleMesh(
+               ParticleMesh::new(64, Vec2F::new(-100.0, -100.0), 200.0).unwrap()
+          ),
+          ForceSolver::FastMultipole(FastMultipole::new(4).unwrap())
+     ];
+
+     for mut solver in solvers {
+          let mut particles = Particles::from_objects(&[anchor.clone(), star.clone()]);
+          // left by a previous step
+          particles.acceleration[0] = Vec2F::new(1.0, 1.0);
+
+          compute_acceleration_for_each(&mut particles, &mut solver, G, 0.0);
+
+          assert_eq!(particles.acceleration[0], Vec2F::new_null());
+          assert!(particles.acceleration[1].length_f64() > 0.0);
+     }
+}
+
+#[test]
+fn test_mass_validity() {
+     let null_star = Object::new_inactive(0.0, Vec2F::new_null(), true, Color::CYAN);
+     let null_tracer = Object::new_inactive_of_kind(
+          ObjectKind::TestParticle,
+          0.0,
+          Vec2F::new_null(),
+          true,
+          Color::CYAN
+     );
+     let negative_tracer = Object::new_inactive_of_kind(
+          ObjectKind::TestParticle,
+          -1.0,
+          Vec2F::new_null(),
+          true,
+          Color::CYAN
+     );
+
+     assert!(null_star.is_err());
+     assert!(null_tracer.is_ok());
+     assert!(negative_tracer.is_err());
+}
+
+#[test]
+fn test_pair_potential_matches_force() {
+     for force_smoothings in [0.0, 0.5, 4.0] {
+          for d in [0.1, 1.0, 3.0, 50.0] {
+               let h = 1e-6 * d;
+               let slope = (compute_pair_potential(d + h, force_smoothings)
+                    - compute_pair_potential(d - h, force_smoothings))
+                    / (2.0 * h);
+               let force = compute_pair_acceleration(Vec2F::new(d, 0.0), force_smoothings).x;
+
+               assert!((slope - force).abs() < 1e-6 * force, "{} {}", slope, force);
+          }
+     }
+
+     // null at infinity, finite at a null distance once smoothed
+     assert!(compute_pair_potential(1e12, 1.0_f64).abs() < 1e-11);
+     assert!(compute_pair_potential(0.0, 1.0_f64).is_finite());
+}
+
+#[test]
+fn test_particle_mesh_matches_direct_summation() {
+     // a small system in the middle of a large box, so the periodic images
+     // barely matter, with bodies several cells away from each other
+     let locations = [
+          Vec2F::new(0.0, 0.0),
+          Vec2F::new(60.0, 10.0),
+          Vec2F::new(-40.0, 50.0),
+          Vec2F::new(-30.0, -70.0),
+          Vec2F::new(80.0, -60.0)
+     ];
+     let masses = [1000.0, 200.0, 300.0, 150.0, 250.0];
+
+     let objects = locations
+          .iter()
+          .zip(masses.iter())
+          .map(|(l, m)| Object::new_inactive(*m, *l, true, Color::CYAN).unwrap())
+          .collect::<Vec<_>>();
+
+     let mut direct = Particles::from_objects(&objects);
+     compute_particle_acceleration_for_each(&mut direct, G, 0.0);
+
+     let mut mesh = Particles::from_objects(&objects);
+     let mut pm = ParticleMesh::new(512, Vec2F::new(-1000.0, -1000.0), 2000.0).unwrap();
+     pm.compute_acceleration_for_each(&mut mesh, G);
+
+     for (a_direct, a_mesh) in direct.acceleration.iter().zip(mesh.acceleration.iter()) {
+          let error = (*a_mesh - *a_direct).length_f64() / a_direct.length_f64();
+
+          assert!(error < 0.05, "relative error too large: {}", error);
+     }
+}
+
+#[test]
+fn test_parse_solvers() {
+     let units = UnitSystem::Si;
+
+     assert!(matches!(
+          ForceSolver::parse("direct", &units).unwrap(),
+          ForceSolver::Direct
+     ));
+     assert!(matches!(
+          ForceSolver::parse("simd", &units).unwrap(),
+          ForceSolver::DirectSimd
+     ));
+     assert!(matches!(
+          ForceSolver::parse("pm:64:2 km", &units).unwrap(),
+          ForceSolver::ParticleMesh(_)
+     ));
+
+     assert!(matches!(
+          ForceSolver::parse("fmm:4", &units).unwrap(),
+          ForceSolver::FastMultipole(_)
+     ));
+
+     assert!(ForceSolver::parse("pm:1:2 km", &units).is_err());
+     assert!(ForceSolver::parse("fmm:0", &units).is_err());
+     assert!(ForceSolver::parse("pm:64:0", &units).is_err());
+     assert!(ForceSolver::parse("pm:64", &units).is_err());
+     assert!(ForceSolver::parse("tree", &units).is_err());
+}
+
+/// Total momentum of the particles, with the sum of the norms of the
+/// momenta to scale it
+fn compute_momentum(particles: &Particles) -> (Vec2F, f64) {
+     (0..particles.len()).fold((Vec2F::new_null(), 0.0), |(p, norm), i| {
+          let m = particles.velocity[i] * particles.mass[i];
+
+          (p + m, norm + m.length_f64())
+     })
+}
+
+/// Random disk of `n` bodies
+fn random_particles(n: usize) -> Particles {
+     let mut rng = StdRng::seed_from_u64(7);
+
+     let objects = (0..n)
+          .map(|_| {
+               let location = Vec2F::new(
+                    rng.gen_range(-1000.0..1000.0),
+                    rng.gen_range(-1000.0..1000.0)
+               );
+
+               Object::new_inactive(rng.gen_range(1.0..10.0), location, true, Color::CYAN).unwrap()
+          })
+          .collect::<Vec<_>>();
+
+     Particles::from_objects(&objects)
+}
+
+/// Largest error on the accelerations, relative to the mean of their norms
+fn max_relative_error(reference: &Particles, other: &Particles) -> f64 {
+     let mean = reference
+          .acceleration
+          .iter()
+          .map(|a| a.length_f64())
+          .sum::<f64>()
+          / reference.len() as f64;
+
+     reference
+          .acceleration
+          .iter()
+          .zip(other.acceleration.iter())
+          .map(|(a, b)| (*b - *a).length_f64() / mean)
+          .fold(0.0, f64::max)
+}
+
+#[test]
+fn test_fast_multipole_matches_direct_summation() {
+     let mut direct = random_particles(2000);
+     compute_particle_acceleration_for_each(&mut direct, G, 0.0);
+
+     let mut previous = f64::MAX;
+     for order in [2, 4, 6] {
+          let mut particles = random_particles(2000);
+          FastMultipole::new(order)
+               .unwrap()
+               .compute_acceleration_for_each(&mut particles, G, 0.0);
+
+          let error = max_relative_error(&direct, &particles);
+          assert!(
+               error < previous,
+               "order {} didn't improve the error: {}",
+               order,
+               error
+          );
+
+          previous = error;
+     }
+
+     assert!(previous < 1e-3, "relative error too large: {}", previous);
+}
+
+#[test]
+fn test_fast_multipole_smoothed_far_field() {
+     // a smoothing length of 40 m, large enough to change the far field
+     let force_smoothings = 1600.0;
+
+     let mut direct = random_particles(2000);
+     compute_particle_acceleration_for_each(&mut direct, G, force_smoothings);
+
+     let mut particles = random_particles(2000);
+     FastMultipole::new(6)
+          .unwrap()
+          .compute_acceleration_for_each(&mut particles, G, force_smoothings);
+
+     let error = max_relative_error(&direct, &particles);
+     assert!(error < 1e-3, "relative error too large: {}", error);
+}
+
+/// Compare the fast multipole method to the direct summation.
+///
+/// Run with:
+/// cargo test --release bench_fast_multipole -- --ignored --nocapture
+#[test]
+#[ignore]
+fn bench_fast_multipole() {
+     for n in [1_000, 5_000, 20_000] {
+          let mut direct = random_particles(n);
+          let start = Instant::now();
+          compute_particle_acceleration_for_each(&mut direct, G, 0.0);
+          println!(
+               "{} bodies: direct {:.1} ms",
+               n,
+               start.elapsed().as_secs_f64() * 1000.0
+          );
+
+          for order in [2, 4, 6, 8] {
+               let mut particles = random_particles(n);
+               let mut fmm = FastMultipole::new(order).unwrap();
+
+               let start = Instant::now();
+               fmm.compute_acceleration_for_each(&mut particles, G, 0.0);
+               let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+
+               println!(
+                    "{} bodies: order {} {:.1} ms, max relative error {:e}",
+                    n,
+                    order,
+                    elapsed,
+                    max_relative_error(&direct, &particles)
+               );
+          }
+     }
+}
+
+#[test]
+fn test_direct_summation_conserves_momentum() {
+     let mut particles = random_particles(500);
+
+     // the total force is null
+     compute_particle_acceleration_for_each(&mut particles, G, 50.0);
+     let (force, norm) = (0..particles.len()).fold((Vec2F::new_null(), 0.0), |(f, norm), i| {
+          let force = particles.acceleration[i] * particles.mass[i];
+
+          (f + force, norm + force.length_f64())
+     });
+     assert!(
+          force.length_f64() / norm < 1e-12,
+          "total force: {:?}",
+          force
+     );
+
+     // so the momentum stays the same along the steps
+     for _ in 0..20 {
+          compute_particle_acceleration_for_each(&mut particles, G, 50.0);
+          compute_particle_next_position_for_each(&mut particles, 3600.0);
+     }
+     let (momentum, norm) = compute_momentum(&particles);
+     assert!(
+          momentum.length_f64() / norm < 1e-12,
+          "total momentum: {:?}",
+          momentum
+     );
+}
+
+#[test]
+fn test_simd_matches_scalar() {
+     // not a multiple of the vector width, so the remainder is used
+     let mut direct = random_particles(1003);
+     compute_particle_acceleration_for_each(&mut direct, G, 50.0);
+
+     for vectorized in [false, true] {
+          let mut particles = random_particles(1003);
+          simd::compute_acceleration_for_each(&mut particles, G, 50.0, vectorized);
+
+          let error = max_relative_error(&direct, &particles);
+          assert!(error < 1e-12, "relative error too large: {}", error);
+     }
+}
+
+/// Compare the vectorized direct summation to the scalar ones.
+///
+/// Run with:
+/// cargo test --release bench_simd -- --ignored --nocapture
+#[test]
+#[ignore]
+fn bench_simd() {
+     println!("SIMD available: {}", simd::is_available());
+
+     for n in [1_000, 10_000] {
+          let mut particles = random_particles(n);
+
+          let start = Instant::now();
+          compute_particle_acceleration_for_each(&mut particles, G, 50.0);
+          let symmetric = start.elapsed().as_secs_f64() * 1000.0;
+
+          let start = Instant::now();
+          simd::compute_acceleration_for_each(&mut particles, G, 50.0, false);
+          let scalar = start.elapsed().as_secs_f64() * 1000.0;
+
+          let start = Instant::now();
+          simd::compute_acceleration_for_each(&mut particles, G, 50.0, true);
+          let vectorized = start.elapsed().as_secs_f64() * 1000.0;
+
+          println!(
+               "{} bodies: symmetric {:.1} ms, scalar {:.1} ms, vectorized {:.1} ms",
+               n, symmetric, scalar, vectorized
+          );
+     }
+}
+
+#[test]
+fn test_single_precision_matches_double() {
+     // in N-body units, so the steps are well resolved in single precision
+     let (g, force_smoothings, delta_t) = (1.0, 0.05, 0.01);
+
+     let mut rng = StdRng::seed_from_u64(7);
+     let objects = (0..100)
+          .map(|_| {
+               let location = Vec2F::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+
+               Object::new_inactive(rng.gen_range(0.5..1.5), location, true, Color::CYAN).unwrap()
+          })
+          .collect::<Vec<_>>();
+     let start = objects.iter().map(|o| o.location).collect::<Vec<_>>();
+
+     let mut double = Particles::from_objects(&objects);
+     let objects = objects.iter().map(|o| o.cast::<f32>()).collect::<Vec<_>>();
+     let mut single = Particles::from_objects(&objects);
+
+     compute_particle_acceleration_for_each(&mut double, g, force_smoothings);
+     compute_particle_acceleration_for_each(&mut single, g as f32, force_smoothings as f32);
+
+     let mean = double
+          .acceleration
+          .iter()
+          .map(|a| a.length_f64())
+          .sum::<f64>()
+          / 100.0;
+     let acceleration_error = double
+          .acceleration
+          .iter()
+          .zip(single.acceleration.iter())
+          .map(|(a, b)| (b.cast::<f64>() - *a).length_f64() / mean)
+          .fold(0.0, f64::max);
+
+     for _ in 0..50 {
+          compute_particle_acceleration_for_each(&mut double, g, force_smoothings);
+          compute_particle_next_position_for_each(&mut double, delta_t);
+
+          compute_particle_acceleration_for_each(&mut single, g as f32, force_smoothings as f32);
+          compute_particle_next_position_for_each(&mut single, delta_t as f32);
+     }
+
+     // both runs have moved, relative to the size of the system
+     let mean_displacement = double
+          .location
+          .iter()
+          .zip(start.iter())
+          .map(|(a, b)| (*a - *b).length_f64())
+          .sum::<f64>()
+          / 100.0;
+     let single_displacement = single
+          .location
+          .iter()
+          .zip(start.iter())
+          .map(|(a, b)| (a.cast::<f64>() - *b).length_f64())
+          .sum::<f64>()
+          / 100.0;
+     let location_error = double
+          .location
+          .iter()
+          .zip(single.location.iter())
+          .map(|(a, b)| (b.cast::<f64>() - *a).length_f64())
+          .fold(0.0, f64::max);
+
+     assert!(
+          mean_displacement > 0.1,
+          "displacement: {}",
+          mean_displacement
+     );
+     assert!(
+          (single_displacement - mean_displacement).abs() < 1e-3 * mean_displacement,
+          "displacements: {} and {}",
+          single_displacement,
+          mean_displacement
+     );
+     assert!(
+          acceleration_error < 1e-5,
+          "acceleration error: {}",
+          acceleration_error
+     );
+     assert!(location_error < 1e-3, "location error: {}", location_error);
+}
+
+#[test]
+fn test_inclined_orbit_stays_in_its_plane() {
+     let inclination = 0.5_f64;
+     let normal = Vec3F::new(0.0, -inclination.sin(), inclination.cos());
+
+     let center = Object::new_inactive(1000.0, Vec3F::new_null(), true, Color::RED).unwrap();
+     let mut satellite = Object::new_inactive(
+          1.0,
+          Vec3F::new(0.0, 100.0 * inclination.cos(), 100.0 * inclination.sin()),
+          true,
+          Color::CYAN
+     )
+     .unwrap();
+     object::add_orbital_velocity_in_space(
+          &mut satellite,
+          std::slice::from_ref(&center),
+          G,
+          normal
+     );
+
+     let mut particles = Particles::from_objects(&[center, satellite]);
+     for _ in 0..2000 {
+          compute_particle_acceleration_for_each(&mut particles, G, 0.0);
+          compute_particle_next_position_for_each(&mut particles, 3600.0);
+     }
+
+     let r = particles.location[1] - particles.location[0];
+     assert!(r.dot(normal).abs() < 1e-9, "left the plane: {:?}", r);
+     assert!(
+          (r.length_f64() - 100.0).abs() < 2.0,
+          "orbit not circular: {:?}",
+          r
+     );
+}
@@ -0,0 +1,153 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use super::{particles::Particles, settings::InvalidSettingError};
+use crate::common::{vec2::Real, vector::Vector};
+
+// =============================================================================
+// Type
+// =============================================================================
+
+/// Settings of the post-Newtonian corrections between the compact objects
+pub struct PostNewtonian {
+     /// Speed of light in the units of the simulation, the one of
+     /// `UnitSystem::speed_of_light` unless slowed down to show the effects
+     speed_of_light: f64,
+
+     /// Whether to add the 2.5PN radiation reaction, making the orbits shrink
+     radiation_reaction: bool
+}
+
+impl PostNewtonian {
+     pub fn new(
+          speed_of_light: f64,
+          radiation_reaction: bool
+     ) -> Result<Self, InvalidSettingError> {
+          if !(speed_of_light.is_finite() && speed_of_light > 0.0) {
+               return Err(InvalidSettingError::new(
+                    "speed of light",
+                    speed_of_light,
+                    "finite and larger than zero"
+               ));
+          }
+
+          Ok(Self {
+               speed_of_light,
+               radiation_reaction
+          })
+     }
+
+     /// Parse the order of the corrections, `1pn` or `2.5pn` to add the
+     /// radiation reaction
+     pub fn parse(order: &str, speed_of_light: f64) -> anyhow::Result<Self> {
+          let radiation_reaction = match order {
+               "1pn" => false,
+               "2.5pn" => true,
+               _ => anyhow::bail!("'{}' is not a valid post-Newtonian order.", order)
+          };
+
+          Ok(Self::new(speed_of_light, radiation_reaction)?)
+     }
+}
+
+// =============================================================================
+// Functions
+// =============================================================================
+
+/// Compute the post-Newtonian correction to the relative acceleration of two
+/// bodies of total mass `m` and symmetric mass ratio `eta`, with `x` and `v`
+/// their relative location and velocity.
+///
+/// The terms are the ones of the two-body equations of motion in harmonic
+/// coordinates, without the Newtonian one already computed by the solvers.
+fn compute_relative_correction<F: Real, V: Vector<F>>(
+     x: V,
+     v: V,
+     m: F,
+     eta: F,
+     g: F,
+     settings: &PostNewtonian
+) -> V {
+     let r = x.dot(x).sqrt();
+     if r == F::zero() {
+          return V::null();
+     }
+
+     let n = x / r;
+     let gm = g * m;
+
+     // the coefficients are computed in double precision, the powers of the
+     // speed of light overflowing in single precision
+     let f = |value: F| value.to_f64().unwrap();
+     let (r_dot, v2, gm_r, eta) = (f(n.dot(v)), f(v.dot(v)), f(gm / r), f(eta));
+
+     let c2 = settings.speed_of_light * settings.speed_of_light;
+
+     // 1PN
+     let mut a = (-1.5 * eta * r_dot * r_dot + v2 + 3.0 * eta * v2 - gm_r * (4.0 + 2.0 * eta)) / c2;
+     let mut b = (-4.0 * r_dot + 2.0 * eta * r_dot) / c2;
+
+     // 2.5PN, taking energy away from the orbit
+     if settings.radiation_reaction {
+          let c5 = c2 * c2 * settings.speed_of_light;
+
+          a -= 1.6 * eta * gm_r * r_dot * (17.0 / 3.0 * gm_r + 3.0 * v2) / c5;
+          b += 1.6 * eta * gm_r * (3.0 * gm_r + v2) / c5;
+     }
+
+     let k = |value: f64| F::from(value).unwrap();
+
+     -(n * k(a) + v * k(b)) * (gm / r / r)
+}
+
+/// Add the post-Newtonian corrections to the acceleration of every pair of
+/// compact particles, the correction of the relative acceleration being
+/// shared according to their masses. `g` is the gravitational constant.
+pub fn apply_post_newtonian_for_each<F: Real, V: Vector<F>>(
+     particles: &mut Particles<F, V>,
+     g: F,
+     settings: &PostNewtonian
+) {
+     let compact = (0..particles.len())
+          .filter(|i| particles.kind[*i].is_compact())
+          .collect::<Vec<_>>();
+
+     for (k, &i) in compact.iter().enumerate() {
+          for &j in compact[(k + 1)..].iter() {
+               let (m1, m2) = (particles.mass[i], particles.mass[j]);
+               let m = m1 + m2;
+
+               let a = compute_relative_correction(
+                    particles.location[i] - particles.location[j],
+                    particles.velocity[i] - particles.velocity[j],
+                    m,
+                    (m1 / m) * (m2 / m),
+                    g,
+                    settings
+               );
+
+               if particles.can_move[i] {
+                    particles.acceleration[i] += a * (m2 / m);
+               }
+               if particles.can_move[j] {
+                    particles.acceleration[j] -= a * (m1 / m);
+               }
+          }
+     }
+}
@@ -0,0 +1,206 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::f64::consts::PI;
+
+use sdl2::pixels::Color;
+
+use super::{apply_post_newtonian_for_each, PostNewtonian};
+use crate::{
+     common::{
+          constants::G,
+          units::UnitSystem,
+          vec2::{Vec2, Vec2F, VecLength},
+          vector::Vector
+     },
+     simulation::{
+          object::{Object, ObjectKind},
+          particles::Particles,
+          physics
+     }
+};
+
+const M1: f64 = 1e12;
+const M2: f64 = 1e9;
+const SEMI_MAJOR_AXIS: f64 = 100.0;
+const ECCENTRICITY: f64 = 0.5;
+
+/// Relativistic parameter `G M / (c^2 a (1 - e^2))` of the orbit
+const STRENGTH: f64 = 1e-3;
+
+fn black_hole(mass: f64, location: Vec2F, velocity: Vec2F) -> Object {
+     Object::new_of_kind(
+          ObjectKind::BlackHole {
+               accretion_radius: 1.0
+          },
+          mass,
+          location,
+          Vec2F::new_null(),
+          velocity,
+          true,
+          Color::RED
+     )
+     .unwrap()
+}
+
+fn speed_of_light() -> f64 {
+     let semi_latus_rectum = SEMI_MAJOR_AXIS * (1.0 - ECCENTRICITY.powi(2));
+
+     (G * (M1 + M2) / (semi_latus_rectum * STRENGTH)).sqrt()
+}
+
+/// Integrate a binary starting at its periapsis along x for a few orbits and
+/// return the mean advance of the periapsis per orbit
+fn measure_precession(post_newtonian: Option<&PostNewtonian>) -> f64 {
+     let m = M1 + M2;
+     let mu = G * m;
+
+     let r = SEMI_MAJOR_AXIS * (1.0 - ECCENTRICITY);
+     let v = (mu * (1.0 + ECCENTRICITY) / r).sqrt();
+     let period = 2.0 * PI * (SEMI_MAJOR_AXIS.powi(3) / mu).sqrt();
+
+     // center of mass at rest at the origin
+     let mut particles = Particles::from_objects(&[
+          black_hole(M1, Vec2F::new(r * M2 / m, 0.0), Vec2F::new(0.0, v * M2 / m)),
+          black_hole(
+               M2,
+               Vec2F::new(-r * M1 / m, 0.0),
+               Vec2F::new(0.0, -v * M1 / m)
+          )
+     ]);
+
+     let delta_t = period / 20000.0;
+     let relative = |p: &Particles| p.location[0] - p.location[1];
+
+     let mut angles = Vec::new();
+     let (mut previous, mut current) = (f64::MAX, f64::MAX);
+     for _ in 0..(5 * 20000 + 100) {
+          physics::compute_particle_acceleration_for_each(&mut particles, G, 0.0);
+          if let Some(settings) = post_newtonian {
+               apply_post_newtonian_for_each(&mut particles, G, settings);
+          }
+          physics::compute_particle_next_position_for_each(&mut particles, delta_t);
+
+          let x = relative(&particles);
+          let d = (x.x.powi(2) + x.y.powi(2)).sqrt();
+
+          // the previous step was a periapsis
+          if current < previous && current < d {
+               angles.push(x.y.atan2(x.x));
+          }
+
+          previous = current;
+          current = d;
+     }
+
+     assert!(angles.len() >= 4, "not enough orbits: {}", angles.len());
+
+     (angles[angles.len() - 1] - angles[0]) / (angles.len() - 1) as f64
+}
+
+#[test]
+fn test_perihelion_precession() {
+     let settings = PostNewtonian::new(speed_of_light(), false).unwrap();
+
+     // the integrator alone makes the orbit precess a bit
+     let newtonian = measure_precession(None);
+     let relativistic = measure_precession(Some(&settings));
+
+     let expected = 6.0 * PI * STRENGTH;
+     let measured = relativistic - newtonian;
+
+     assert!(
+          (measured - expected).abs() < 0.02 * expected,
+          "precession of {} rad per orbit, expected {}",
+          measured,
+          expected
+     );
+}
+
+#[test]
+fn test_radiation_reaction_power() {
+     let m = M1 + M2;
+     let mu = M1 * M2 / m;
+     let c = speed_of_light();
+
+     // circular orbit, the 1PN correction being radial and doing no work
+     let r = SEMI_MAJOR_AXIS;
+     let v = (G * m / r).sqrt();
+     let mut particles = Particles::from_objects(&[
+          black_hole(M1, Vec2F::new(r * M2 / m, 0.0), Vec2F::new(0.0, v * M2 / m)),
+          black_hole(
+               M2,
+               Vec2F::new(-r * M1 / m, 0.0),
+               Vec2F::new(0.0, -v * M1 / m)
+          )
+     ]);
+     apply_post_newtonian_for_each(&mut particles, G, &PostNewtonian::new(c, true).unwrap());
+
+     let power = (0..particles.len())
+          .map(|i| particles.mass[i] * particles.velocity[i].dot(particles.acceleration[i]))
+          .sum::<f64>();
+
+     // quadrupole formula of Peters
+     let expected = -32.0 / 5.0 * G.powi(4) * mu.powi(2) * m.powi(3) / (c.powi(5) * r.powi(5));
+
+     assert!(
+          (power - expected).abs() < 1e-9 * expected.abs(),
+          "radiated power of {}, expected {}",
+          power,
+          expected
+     );
+}
+
+#[test]
+fn test_radiation_reaction_in_single_precision() {
+     // a tight binary in SI, whose c^5 overflows in single precision
+     let c = UnitSystem::Si.speed_of_light();
+     let objects = [
+          black_hole(2e31, Vec2F::new(5e5, 0.0), Vec2F::new(1e6, 2e7)),
+          black_hole(2e31, Vec2F::new(-5e5, 0.0), Vec2F::new(-1e6, -2e7))
+     ];
+
+     // acceleration of the first black hole due to the radiation reaction
+     let double = |radiation_reaction: bool| {
+          let mut particles = Particles::from_objects(&objects);
+          particles.acceleration.fill(Vec2F::new_null());
+          apply_post_newtonian_for_each(
+               &mut particles,
+               G,
+               &PostNewtonian::new(c, radiation_reaction).unwrap()
+          );
+
+          particles.acceleration[0]
+     };
+     let single = |radiation_reaction: bool| {
+          let objects = objects.iter().map(|o| o.cast::<f32>()).collect::<Vec<_>>();
+          let mut particles = Particles::from_objects(&objects);
+          particles.acceleration.fill(Vec2::new_null());
+          apply_post_newtonian_for_each(
+               &mut particles,
+               G as f32,
+               &PostNewtonian::new(c, radiation_reaction).unwrap()
+          );
+
+          particles.acceleration[0].cast::<f64>()
+     };
+
+     let expected = double(true) - double(false);
+     let error = (single(true) - single(false) - expected).length_f64() / expected.length_f64();
+
+     assert!(expected.length_f64() > 0.0);
+     assert!(error < 1e-2, "relative error of {}", error);
+}
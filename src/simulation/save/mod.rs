@@ -0,0 +1,290 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use std::{
+     collections::HashSet,
+     fs::File,
+     io::{BufRead, BufReader, BufWriter, Write},
+     path::Path
+};
+
+use anyhow::Context;
+use sdl2::pixels::Color;
+
+use super::object::{GeneratorId, Object, ObjectId, ObjectKind};
+use crate::common::{
+     units::{Dimension, UnitSystem},
+     vec2::Vec2F
+};
+
+/// Start of the first line, followed by the version of the format
+const HEADER_PREFIX: &str = "# GalaxyX objects v";
+
+/// Version of the format written, the first one having no kinds and the
+/// second no generators
+const VERSION: u32 = 3;
+
+/// Start of the line giving the unit system of the values
+const UNITS_PREFIX: &str = "# units:";
+
+/// Objects read from a file, with the units of their values
+pub struct Scenario {
+     pub objects: Vec<Object>,
+     pub units: UnitSystem
+}
+
+// =============================================================================
+// Save
+// =============================================================================
+
+/// Write the objects into `writer`, their values being in `units`.
+///
+/// Each object is written on its own line, as tab separated values:
+/// `id kind mass x y vx vy fx fy can_move r g b a parents generator name`, the
+/// parents being a comma separated list of ids (`-` if none), the generator an
+/// id (`-` if none) and the name being empty if the object has none.
+pub fn write_objects<W: Write>(
+     writer: &mut W,
+     objects: &[Object],
+     units: &UnitSystem
+) -> anyhow::Result<()> {
+     writeln!(writer, "{}{}", HEADER_PREFIX, VERSION)?;
+     writeln!(writer, "{} {}", UNITS_PREFIX, units)?;
+     writeln!(
+          writer,
+          "# id\tkind\tmass ({})\tx y ({})\tvx vy ({})\tfx fy ({})\tcan_move\tr g b a\tparents\tgenerator\tname",
+          units.symbol(Dimension::Mass),
+          units.symbol(Dimension::Length),
+          units.symbol(Dimension::Velocity),
+          units.symbol(Dimension::Force)
+     )?;
+
+     for o in objects {
+          let parents = if o.parents.is_empty() {
+               "-".to_string()
+          } else {
+               o.parents
+                    .iter()
+                    .map(|p| p.value().to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+          };
+          let generator = match o.generator {
+               Some(generator) => generator.value().to_string(),
+               None => "-".to_string()
+          };
+
+          writeln!(
+               writer,
+               "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+               o.id.value(),
+               o.kind,
+               o.mass,
+               o.location.x,
+               o.location.y,
+               o.velocity.x,
+               o.velocity.y,
+               o.force.x,
+               o.force.y,
+               o.can_move,
+               o.color.r,
+               o.color.g,
+               o.color.b,
+               o.color.a,
+               parents,
+               generator,
+               o.name.as_deref().unwrap_or("")
+          )?;
+     }
+
+     Ok(())
+}
+
+/// Save the objects into the file at `path`, their values being in `units`
+pub fn save_objects(path: &Path, objects: &[Object], units: &UnitSystem) -> anyhow::Result<()> {
+     let file = File::create(path)
+          .with_context(|| format!("Couldn't create the file {}.", path.display()))?;
+
+     let mut writer = BufWriter::new(file);
+     write_objects(&mut writer, objects, units)?;
+     writer.flush()?;
+
+     Ok(())
+}
+
+// =============================================================================
+// Load
+// =============================================================================
+
+/// Parse an object written in the given version of the format, its physical
+/// values being in `units` unless followed by their own unit
+fn parse_object(line: &str, units: &UnitSystem, version: u32) -> anyhow::Result<Object> {
+     let count = match version {
+          1 => 15,
+          2 => 16,
+          _ => 17
+     };
+     let mut fields = line.splitn(count, '\t').collect::<Vec<_>>();
+     if fields.len() != count {
+          anyhow::bail!("Expected {} fields, found {}.", count, fields.len());
+     }
+
+     // every object was a star before the kinds
+     if version == 1 {
+          fields.insert(1, "star");
+     }
+     // nor generators before the third version
+     if version <= 2 {
+          fields.insert(15, "-");
+     }
+
+     let quantity_field = |i: usize, dimension: Dimension| -> anyhow::Result<f64> {
+          Ok(units.parse(fields[i], dimension)?)
+     };
+     let u8_field = |i: usize| -> anyhow::Result<u8> {
+          fields[i]
+               .parse::<u8>()
+               .with_context(|| format!("Invalid color component '{}'.", fields[i]))
+     };
+     let id_field = |s: &str| -> anyhow::Result<ObjectId> {
+          let value = s
+               .parse::<u64>()
+               .with_context(|| format!("Invalid id '{}'.", s))?;
+
+          ObjectId::reserve(value).with_context(|| format!("Id {} is too large.", value))
+     };
+
+     let kind = fields[1].parse::<ObjectKind>()?;
+
+     let mut object = Object::new_of_kind(
+          kind,
+          quantity_field(2, Dimension::Mass)?,
+          Vec2F::new(
+               quantity_field(3, Dimension::Length)?,
+               quantity_field(4, Dimension::Length)?
+          ),
+          Vec2F::new(
+               quantity_field(7, Dimension::Force)?,
+               quantity_field(8, Dimension::Force)?
+          ),
+          Vec2F::new(
+               quantity_field(5, Dimension::Velocity)?,
+               quantity_field(6, Dimension::Velocity)?
+          ),
+          fields[9]
+               .parse::<bool>()
+               .with_context(|| format!("Invalid boolean '{}'.", fields[9]))?,
+          Color::RGBA(u8_field(10)?, u8_field(11)?, u8_field(12)?, u8_field(13)?)
+     )
+     .map_err(|e| anyhow::anyhow!(e))?;
+
+     object.id = id_field(fields[0])?;
+     if fields[14] != "-" {
+          object.parents = fields[14]
+               .split(',')
+               .map(id_field)
+               .collect::<anyhow::Result<Vec<_>>>()?;
+     }
+     if fields[15] != "-" {
+          let generator = fields[15]
+               .parse::<u64>()
+               .with_context(|| format!("Invalid generator '{}'.", fields[15]))?;
+
+          object.generator = Some(GeneratorId::reserve(generator)
+               .with_context(|| format!("Generator {} is too large.", generator))?);
+     }
+     if !fields[16].is_empty() {
+          object.name = Some(fields[16].to_string());
+     }
+
+     Ok(object)
+}
+
+/// Read objects from `reader`.
+///
+/// The format is the one of the version in the header, the current one
+/// without header. The values are in the unit system given by a
+/// `# units: <system>` line before the objects, SI by default. Each of them
+/// can also be followed by its own unit, like `1e10 Msun`, to be converted
+/// into the system.
+pub fn read_objects<R: BufRead>(reader: R) -> anyhow::Result<Scenario> {
+     let mut scenario = Scenario {
+          objects: Vec::new(),
+          units: UnitSystem::Si
+     };
+     let mut version = VERSION;
+     let mut ids = HashSet::new();
+
+     for (i, line) in reader.lines().enumerate() {
+          let line = line?;
+
+          if let Some(v) = line.strip_prefix(HEADER_PREFIX) {
+               version = match v.trim().parse::<u32>() {
+                    Ok(v) if (1..=VERSION).contains(&v) => v,
+                    _ => anyhow::bail!(
+                         "Error at line {}: unsupported version '{}', at most {} is supported.",
+                         i + 1,
+                         v.trim(),
+                         VERSION
+                    )
+               };
+               continue;
+          }
+
+          if let Some(units) = line.strip_prefix(UNITS_PREFIX) {
+               if !scenario.objects.is_empty() {
+                    anyhow::bail!(
+                         "Error at line {}: the units must come before the objects.",
+                         i + 1
+                    );
+               }
+
+               scenario.units = units
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("Error at line {}.", i + 1))?;
+               continue;
+          }
+
+          // skip the header, comments and empty lines
+          if line.trim().is_empty() || line.starts_with('#') {
+               continue;
+          }
+
+          let object = parse_object(&line, &scenario.units, version)
+               .with_context(|| format!("Error at line {}.", i + 1))?;
+
+          // parents and trails find the objects by their ids
+          if !ids.insert(object.id) {
+               anyhow::bail!("Error at line {}: duplicate id {}.", i + 1, object.id);
+          }
+
+          scenario.objects.push(object);
+     }
+
+     Ok(scenario)
+}
+
+/// Load the objects from the file at `path`
+pub fn load_objects(path: &Path) -> anyhow::Result<Scenario> {
+     let file = File::open(path)
+          .with_context(|| format!("Couldn't open the file {}.", path.display()))?;
+
+     read_objects(BufReader::new(file))
+}
@@ -0,0 +1,173 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use sdl2::pixels::Color;
+
+use super::{read_objects, write_objects};
+use crate::{
+     common::{
+          units::{Dimension, UnitSystem},
+          vec2::Vec2F
+     },
+     simulation::object::{GeneratorId, Object, ObjectId, ObjectKind}
+};
+
+#[test]
+fn test_save_load_round_trip() {
+     let bh = Object::new_inactive_of_kind(
+          ObjectKind::BlackHole {
+               accretion_radius: 12.5
+          },
+          1000.0,
+          Vec2F::new(0.0, 0.0),
+          false,
+          Color::RED
+     )
+     .unwrap()
+     .with_name("central black hole");
+     let mut star = Object::new(
+          10.5,
+          Vec2F::new(-1.25, 2.0),
+          Vec2F::new(1e-9, -3.0),
+          Vec2F::new(3.0, 4.5),
+          true,
+          Color::RGBA(1, 2, 3, 4)
+     )
+     .unwrap();
+     star.parents = vec![bh.id, ObjectId::generate()];
+     star.generator = Some(GeneratorId::generate());
+
+     let tracer = Object::new_inactive_of_kind(
+          ObjectKind::TestParticle,
+          0.0,
+          Vec2F::new(5.0, 5.0),
+          true,
+          Color::WHITE
+     )
+     .unwrap();
+
+     let objects = vec![bh, star, tracer];
+
+     let mut buffer = Vec::new();
+     write_objects(&mut buffer, &objects, &UnitSystem::Astronomical).unwrap();
+     let scenario = read_objects(buffer.as_slice()).unwrap();
+     let loaded = scenario.objects;
+
+     assert_eq!(scenario.units, UnitSystem::Astronomical);
+
+     assert_eq!(loaded.len(), objects.len());
+     for (o, l) in objects.iter().zip(loaded.iter()) {
+          assert_eq!(o.id, l.id);
+          assert_eq!(o.name, l.name);
+          assert_eq!(o.parents, l.parents);
+          assert_eq!(o.generator, l.generator);
+          assert_eq!(o.mass, l.mass);
+          assert_eq!(o.location, l.location);
+          assert_eq!(o.velocity, l.velocity);
+          assert_eq!(o.force, l.force);
+          assert_eq!(o.can_move, l.can_move);
+          assert_eq!(o.kind, l.kind);
+          assert_eq!(o.color, l.color);
+     }
+
+     // loaded ids are reserved, new ones can't collide with them
+     let new_id = ObjectId::generate();
+     assert!(loaded.iter().all(|o| o.id != new_id));
+}
+
+#[test]
+fn test_load_invalid_line() {
+     let data = "# GalaxyX objects v2\n0\t1.0\tnope\n";
+
+     assert!(read_objects(data.as_bytes()).is_err());
+}
+
+#[test]
+fn test_load_values_with_units() {
+     let data = "# GalaxyX objects v2\n\
+                 # units: astronomical\n\
+                 0\tstar\t2e40 kg\t1\t500 pc\t0\t220 km/s\t0\t0\ttrue\t0\t0\t0\t255\t-\t\n";
+     let scenario = read_objects(data.as_bytes()).unwrap();
+     let o = &scenario.objects[0];
+
+     let units = UnitSystem::Astronomical;
+     assert!((o.mass - units.convert_from_si(2e40, Dimension::Mass)).abs() < 1e-9 * o.mass);
+     assert_eq!(o.location.x, 1.0);
+     assert!((o.location.y - 0.5).abs() < 1e-12);
+     assert!((o.velocity.y - 0.225).abs() < 1e-3);
+
+     // without generators before the third version
+     assert_eq!(o.generator, None);
+
+     // the units must be known before the values
+     let data = "0\tstar\t1\t0\t0\t0\t0\t0\t0\ttrue\t0\t0\t0\t255\t-\t-\t\n# units: si\n";
+     assert!(read_objects(data.as_bytes()).is_err());
+}
+
+#[test]
+fn test_load_first_version() {
+     // without kinds, the objects are stars
+     let data = "# GalaxyX objects v1\n\
+                 7\t2.5\t1\t2\t3\t4\t0\t0\tfalse\t10\t20\t30\t255\t-\tsun\n";
+     let scenario = read_objects(data.as_bytes()).unwrap();
+     let o = &scenario.objects[0];
+
+     assert_eq!(o.kind, ObjectKind::Star);
+     assert_eq!(o.mass, 2.5);
+     assert_eq!(o.velocity, Vec2F::new(3.0, 4.0));
+     assert!(!o.can_move);
+     assert_eq!(o.color, Color::RGB(10, 20, 30));
+     assert_eq!(o.name.as_deref(), Some("sun"));
+
+     // the current format isn't read as the first one
+     let data =
+          "# GalaxyX objects v1\n7\tstar\t2.5\t1\t2\t3\t4\t0\t0\tfalse\t10\t20\t30\t255\t-\tsun\n";
+     assert!(read_objects(data.as_bytes()).is_err());
+
+     let error = read_objects("# GalaxyX objects v4\n".as_bytes())
+          .err()
+          .unwrap();
+     assert!(
+          error.to_string().contains("unsupported version '4'"),
+          "{}",
+          error
+     );
+}
+
+#[test]
+fn test_load_invalid_ids() {
+     let error = read_objects(
+          "0\tstar\t1\t0\t0\t0\t0\t0\t0\ttrue\t0\t0\t0\t255\t-\t-\t\n\
+           0\tstar\t1\t1\t0\t0\t0\t0\t0\ttrue\t0\t0\t0\t255\t-\t-\t\n"
+               .as_bytes()
+     )
+     .err()
+     .unwrap();
+     assert!(error.to_string().contains("duplicate id #0"), "{}", error);
+
+     // generated ids couldn't follow the last one
+     let data = format!(
+          "{}\tstar\t1\t0\t0\t0\t0\t0\t0\ttrue\t0\t0\t0\t255\t-\t-\t\n",
+          u64::MAX
+     );
+     assert!(read_objects(data.as_bytes()).is_err());
+
+     let data = format!(
+          "0\tstar\t1\t0\t0\t0\t0\t0\t0\ttrue\t0\t0\t0\t255\t-\t{}\t\n",
+          u64::MAX
+     );
+     assert!(read_objects(data.as_bytes()).is_err());
+}
@@ -0,0 +1,190 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::{
+     fmt::{Debug, Display},
+     str::FromStr
+};
+
+use super::{
+     accretion::AccretionSettings,
+     boundary::{Boundary, EscaperDetector},
+     forces::ForceModule,
+     gas::GasSettings,
+     physics::ForceSolver,
+     relativity::PostNewtonian
+};
+use crate::common::units::UnitSystem;
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/// The float type the physics is run with
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Precision {
+     /// f64, the default
+     Double,
+
+     /// f32, faster but less precise, for huge numbers of particles. Only the
+     /// direct summation is supported.
+     Single
+}
+
+/// Settings of the physics engine
+pub struct EngineSettings {
+     /// The units of the values of the simulation, deciding the value of the
+     /// gravitational constant
+     pub units: UnitSystem,
+
+     pub delta_t: f64,
+     pub force_smoothings: f64,
+     pub substep: u32,
+
+     /// The float type of the physical values in the engine
+     pub precision: Precision,
+
+     /// The method used to compute the gravity
+     pub solver: ForceSolver,
+
+     /// Post-Newtonian corrections between the black holes, disabled if
+     /// `None`
+     pub post_newtonian: Option<PostNewtonian>,
+
+     /// Forces added to the gravity, like dynamical friction or drag
+     pub forces: Vec<ForceModule>,
+
+     /// Pressure and viscosity between the gas parcels, disabled if `None`
+     pub gas: Option<GasSettings>,
+
+     /// Accretion by the black holes, disabled if `None`
+     pub accretion: Option<AccretionSettings>,
+
+     /// Limits of the simulated domain, unlimited if `None`
+     pub boundary: Option<Boundary>,
+
+     /// Removal of the objects escaping the system, disabled if `None`
+     pub escapers: Option<EscaperDetector>
+}
+
+impl EngineSettings {
+     pub fn new(delta_t: f64, force_smoothings: f64, substep: u32) -> Self {
+          Self {
+               units: UnitSystem::Si,
+               delta_t,
+               force_smoothings,
+               substep,
+               precision: Precision::Double,
+               solver: ForceSolver::Direct,
+               post_newtonian: None,
+               forces: Vec::new(),
+               gas: None,
+               accretion: None,
+               boundary: None,
+               escapers: None
+          }
+     }
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+impl Display for Precision {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.write_str(match self {
+               Precision::Double => "double",
+               Precision::Single => "single"
+          })
+     }
+}
+
+pub struct PrecisionParseError {
+     value: String
+}
+
+impl Debug for PrecisionParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.debug_struct("PrecisionParseError")
+               .field("value", &self.value)
+               .finish()
+     }
+}
+
+impl Display for PrecisionParseError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          write!(f, "'{}' is not a valid precision.", self.value)
+     }
+}
+
+impl std::error::Error for PrecisionParseError {}
+
+impl FromStr for Precision {
+     type Err = PrecisionParseError;
+
+     fn from_str(s: &str) -> Result<Self, Self::Err> {
+          match s {
+               "double" => Ok(Precision::Double),
+               "single" => Ok(Precision::Single),
+               _ => Err(PrecisionParseError {
+                    value: s.to_string()
+               })
+          }
+     }
+}
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// A setting out of the range of values it can take
+pub struct InvalidSettingError {
+     name: &'static str,
+     value: String,
+     expected: &'static str
+}
+
+impl InvalidSettingError {
+     pub fn new(name: &'static str, value: impl Display, expected: &'static str) -> Self {
+          Self {
+               name,
+               value: value.to_string(),
+               expected
+          }
+     }
+}
+
+impl Debug for InvalidSettingError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          f.debug_struct("InvalidSettingError")
+               .field("name", &self.name)
+               .field("value", &self.value)
+               .field("expected", &self.expected)
+               .finish()
+     }
+}
+
+impl Display for InvalidSettingError {
+     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+          write!(
+               f,
+               "Invalid {} ({}), it must be {}.",
+               self.name, self.value, self.expected
+          )
+     }
+}
+
+impl std::error::Error for InvalidSettingError {}
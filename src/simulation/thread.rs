@@ -1,44 +1,245 @@
 use std::{
-     sync::mpsc::{self, Receiver, Sender},
+     sync::mpsc::{self, Receiver, SyncSender},
      thread
 };
 
-use super::physics;
-use crate::object::Object;
+use super::{
+     accretion,
+     boundary,
+     events::Event,
+     forces,
+     gas,
+     particles::Particles,
+     physics::{self, ForceSolver},
+     relativity,
+     settings::{EngineSettings, Precision}
+};
+use crate::{
+     common::{
+          vec2::{Real, Vec2F},
+          vec3::Vec3F,
+          vector::Vector
+     },
+     object::Object
+};
+
+/// Number of snapshots waiting to be received before the engine waits too, so
+/// they don't pile up when the receiver is slower or paused
+const SNAPSHOT_BUFFER: usize = 4;
+
+/// State of the simulation sent by the engine thread
+pub struct Snapshot<V: Vector<f64> = Vec2F> {
+     pub objects: Vec<Object<f64, V>>,
+
+     /// What happened since the previous snapshot
+     pub events: Vec<Event>
+}
+
+/// What the engine does with the particles besides the gravity, depending on
+/// their float and vector types
+trait Simulated {
+     /// The type of the vectors of the objects sent in the snapshots
+     type Output: Vector<f64>;
+
+     /// Add the accelerations of the modules to the ones of the gravity
+     fn apply_forces(&mut self, settings: &EngineSettings);
+
+     /// Apply the modules run at every substep
+     fn apply_modules(&mut self, settings: &EngineSettings, time: f64, events: &mut Vec<Event>);
+
+     /// Apply the modules run once per snapshot
+     fn apply_snapshot_modules(
+          &mut self,
+          settings: &EngineSettings,
+          time: f64,
+          events: &mut Vec<Event>
+     );
+
+     fn snapshot_objects(&self) -> Vec<Object<f64, Self::Output>>;
+}
+
+impl<F: Real> Simulated for Particles<F> {
+     type Output = Vec2F;
+
+     fn apply_forces(&mut self, settings: &EngineSettings) {
+          if let Some(gas) = &settings.gas {
+               gas::apply_gas_forces_for_each(self, gas);
+          }
+     }
+
+     fn apply_modules(&mut self, settings: &EngineSettings, time: f64, events: &mut Vec<Event>) {
+          if let Some(accretion) = &settings.accretion {
+               events.extend(accretion::apply_accretion_for_each(self, accretion, time));
+          }
+
+          if let Some(b) = &settings.boundary {
+               events.extend(boundary::apply_boundary_for_each(self, b, time));
+          }
+     }
+
+     fn apply_snapshot_modules(
+          &mut self,
+          settings: &EngineSettings,
+          time: f64,
+          events: &mut Vec<Event>
+     ) {
+          // costs as much as a force computation, so only once per snapshot
+          if let Some(detector) = &settings.escapers {
+               let g = F::from(settings.units.gravitational_constant()).unwrap();
+               let force_smoothings = F::from(settings.force_smoothings).unwrap();
+
+               events.extend(boundary::remove_escapers(
+                    self,
+                    detector,
+                    g,
+                    force_smoothings,
+                    time
+               ));
+          }
+     }
+
+     fn snapshot_objects(&self) -> Vec<Object> {
+          self.to_objects().iter().map(|o| o.cast()).collect()
+     }
+}
+
+/// The modules only work in the plane, their absence is checked at launch
+impl Simulated for Particles<f64, Vec3F> {
+     type Output = Vec3F;
+
+     fn apply_forces(&mut self, _: &EngineSettings) {}
+
+     fn apply_modules(&mut self, _: &EngineSettings, _: f64, _: &mut Vec<Event>) {}
+
+     fn apply_snapshot_modules(&mut self, _: &EngineSettings, _: f64, _: &mut Vec<Event>) {}
+
+     fn snapshot_objects(&self) -> Vec<Object<f64, Vec3F>> {
+          self.to_objects()
+     }
+}
 
 /// Function in the engine thread
-fn engine_thread(
-     mut objects: Vec<Object>,
-     sender: Sender<Vec<Object>>,
-     force_smoothings: f64,
-     delta_t: f64,
-     substep: u32
-) {
-     let sub_delta_t = delta_t / f64::from(substep);
+fn engine_thread(objects: Vec<Object>, sender: SyncSender<Snapshot>, settings: EngineSettings) {
+     match settings.precision {
+          Precision::Double => run(
+               Particles::from_objects(&objects),
+               sender,
+               settings,
+               physics::compute_acceleration_for_each
+          ),
+          Precision::Single => {
+               let objects = objects.iter().map(|o| o.cast::<f32>()).collect::<Vec<_>>();
+
+               run(
+                    Particles::from_objects(&objects),
+                    sender,
+                    settings,
+                    |particles, _, g, force_smoothings| {
+                         physics::compute_particle_acceleration_for_each(
+                              particles,
+                              g,
+                              force_smoothings
+                         )
+                    }
+               )
+          }
+     }
+}
+
+/// Run the simulation with the float type `F` and the vector type `V`,
+/// `accelerate` computing the acceleration of every particle
+fn run<F: Real, V: Vector<F>>(
+     mut particles: Particles<F, V>,
+     sender: SyncSender<Snapshot<<Particles<F, V> as Simulated>::Output>>,
+     mut settings: EngineSettings,
+     accelerate: impl Fn(&mut Particles<F, V>, &mut ForceSolver, F, F)
+) where
+     Particles<F, V>: Simulated
+{
+     let sub_delta_t = settings.delta_t / f64::from(settings.substep);
+     let delta_t = F::from(sub_delta_t).unwrap();
+     let force_smoothings = F::from(settings.force_smoothings).unwrap();
+     let g = F::from(settings.units.gravitational_constant()).unwrap();
+
+     let mut time = 0.0;
 
      loop {
-          for _ in 0..substep {
-               physics::compute_object_global_force_for_each(&mut objects, force_smoothings);
-               physics::compute_object_next_position_for_each(&mut objects, sub_delta_t);
+          let mut events = Vec::new();
+
+          for _ in 0..settings.substep {
+               accelerate(&mut particles, &mut settings.solver, g, force_smoothings);
+               if let Some(post_newtonian) = &settings.post_newtonian {
+                    relativity::apply_post_newtonian_for_each(&mut particles, g, post_newtonian);
+               }
+               forces::apply_force_modules_for_each(&mut particles, g, &settings.forces);
+               particles.apply_forces(&settings);
+               physics::compute_particle_next_position_for_each(&mut particles, delta_t);
+
+               time += sub_delta_t;
+
+               particles.apply_modules(&settings, time, &mut events);
           }
 
-          let r = sender.send(objects.clone());
+          particles.apply_snapshot_modules(&settings, time, &mut events);
+
+          let r = sender.send(Snapshot {
+               objects: particles.snapshot_objects(),
+               events
+          });
           if r.is_err() {
                break;
           }
      }
 }
 
-/// Init the engine thread
+/// Init the engine thread, failing if the settings can't be run together
 pub fn launch_engine_thread(
      objects: Vec<Object>,
-     force_smoothings: f64,
-     delta_t: f64,
-     substep: u32
-) -> Receiver<Vec<Object>> {
-     let (tx, rx) = mpsc::channel();
+     settings: EngineSettings
+) -> anyhow::Result<Receiver<Snapshot>> {
+     anyhow::ensure!(
+          settings.precision == Precision::Double || matches!(settings.solver, ForceSolver::Direct),
+          "Only the direct summation supports single precision."
+     );
+
+     let (tx, rx) = mpsc::sync_channel(SNAPSHOT_BUFFER);
+
+     thread::spawn(move || engine_thread(objects, tx, settings));
+
+     Ok(rx)
+}
+
+/// Init the engine thread for objects in space.
+///
+/// Only the direct summation in double precision is supported, without the
+/// modules working in the plane (gas, accretion, boundary and escapers).
+pub fn launch_engine_thread_3d(
+     objects: Vec<Object<f64, Vec3F>>,
+     settings: EngineSettings
+) -> anyhow::Result<Receiver<Snapshot<Vec3F>>> {
+     anyhow::ensure!(
+          settings.precision == Precision::Double
+               && matches!(settings.solver, ForceSolver::Direct)
+               && settings.accretion.is_none()
+               && settings.boundary.is_none()
+               && settings.escapers.is_none()
+               && settings.gas.is_none(),
+          "Only the direct summation in double precision is supported in space, without \
+           accretion, boundary, escapers or gas."
+     );
+
+     let (tx, rx) = mpsc::sync_channel(SNAPSHOT_BUFFER);
 
-     thread::spawn(move || engine_thread(objects, tx, force_smoothings, delta_t, substep));
+     thread::spawn(move || {
+          run(
+               Particles::from_objects(&objects),
+               tx,
+               settings,
+               |particles, _, g, force_smoothings| {
+                    physics::compute_particle_acceleration_for_each(particles, g, force_smoothings)
+               }
+          )
+     });
 
-     rx
+     Ok(rx)
 }
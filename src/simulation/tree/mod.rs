@@ -0,0 +1,193 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+#[cfg(test)]
+mod test;
+
+use crate::common::vec2::{Real, Vec2};
+
+/// Maximum depth of the tree, reached only by locations at the same place
+const MAX_DEPTH: u32 = 32;
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/// A square of the tree
+pub struct Node<F: Real> {
+     pub center: Vec2<F>,
+     pub half_size: F,
+
+     /// Range of the node in the sorted indices
+     pub start: usize,
+     pub end: usize,
+
+     pub children: Vec<usize>
+}
+
+/// Quadtree sorting locations in squares, each divided in four until holding
+/// few enough of them.
+///
+/// The root is the first node and the children of a node always come after
+/// it.
+pub struct QuadTree<F: Real> {
+     pub nodes: Vec<Node<F>>,
+
+     /// Indices of the locations, those of a node being contiguous
+     pub indices: Vec<usize>
+}
+
+impl<F: Real> QuadTree<F> {
+     /// Build the tree of the given locations, its leaves holding at most
+     /// `leaf_size` of them
+     pub fn new(locations: &[Vec2<F>], leaf_size: usize) -> Self {
+          let mut tree = Self {
+               nodes: Vec::new(),
+               indices: (0..locations.len()).collect()
+          };
+          if locations.is_empty() {
+               return tree;
+          }
+
+          let (mut min, mut max) = (locations[0], locations[0]);
+          for l in locations.iter() {
+               min = Vec2::new(min.x.min(l.x), min.y.min(l.y));
+               max = Vec2::new(max.x.max(l.x), max.y.max(l.y));
+          }
+
+          // slightly larger, so the locations on the edges are inside
+          let two = F::one() + F::one();
+          let half_size =
+               (max.x - min.x).max(max.y - min.y) / two * F::from(1.0001).unwrap() + F::epsilon();
+
+          tree.build_node(
+               locations,
+               leaf_size,
+               0,
+               locations.len(),
+               (min + max) / two,
+               half_size,
+               0
+          );
+
+          tree
+     }
+
+     /// Build the node containing the indices in `start..end` and return its
+     /// index
+     #[allow(clippy::too_many_arguments)]
+     fn build_node(
+          &mut self,
+          locations: &[Vec2<F>],
+          leaf_size: usize,
+          start: usize,
+          end: usize,
+          center: Vec2<F>,
+          half_size: F,
+          depth: u32
+     ) -> usize {
+          let node = self.nodes.len();
+          self.nodes.push(Node {
+               center,
+               half_size,
+               start,
+               end,
+               children: Vec::new()
+          });
+
+          if end - start <= leaf_size || depth >= MAX_DEPTH {
+               return node;
+          }
+
+          // sort the locations by quadrant, then build the non-empty ones
+          let quadrant = |i: usize| {
+               let l = locations[i];
+
+               usize::from(l.x >= center.x) + 2 * usize::from(l.y >= center.y)
+          };
+          self.indices[start..end].sort_unstable_by_key(|i| quadrant(*i));
+
+          let quarter = half_size / (F::one() + F::one());
+          let mut s = start;
+          for q in 0..4 {
+               let mut e = s;
+               while e < end && quadrant(self.indices[e]) == q {
+                    e += 1;
+               }
+
+               if e > s {
+                    let offset = Vec2::new(
+                         if q & 1 == 1 { quarter } else { -quarter },
+                         if q & 2 == 2 { quarter } else { -quarter }
+                    );
+
+                    let child = self.build_node(
+                         locations,
+                         leaf_size,
+                         s,
+                         e,
+                         center + offset,
+                         quarter,
+                         depth + 1
+                    );
+                    self.nodes[node].children.push(child);
+               }
+
+               s = e;
+          }
+
+          node
+     }
+
+     /// Put in `neighbors` the indices of the locations closer than `radius`
+     /// to `point`
+     pub fn find_neighbors(
+          &self,
+          locations: &[Vec2<F>],
+          point: Vec2<F>,
+          radius: F,
+          neighbors: &mut Vec<usize>
+     ) {
+          neighbors.clear();
+          if self.nodes.is_empty() {
+               return;
+          }
+
+          let mut stack = vec![0];
+          while let Some(n) = stack.pop() {
+               let node = &self.nodes[n];
+
+               // distance from the point to the square
+               let dx = ((point.x - node.center.x).abs() - node.half_size).max(F::zero());
+               let dy = ((point.y - node.center.y).abs() - node.half_size).max(F::zero());
+               if dx * dx + dy * dy > radius * radius {
+                    continue;
+               }
+
+               if node.children.is_empty() {
+                    for &i in &self.indices[node.start..node.end] {
+                         let r = locations[i] - point;
+
+                         if r.x * r.x + r.y * r.y < radius * radius {
+                              neighbors.push(i);
+                         }
+                    }
+               } else {
+                    stack.extend(node.children.iter());
+               }
+          }
+     }
+}
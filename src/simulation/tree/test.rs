@@ -0,0 +1,55 @@
+// A galaxy simulator made in Rust.
+// Copyright (C) 2022 NovaliX
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::QuadTree;
+use crate::common::vec2::Vec2F;
+
+#[test]
+fn test_neighbors_match_brute_force() {
+     let mut rng = StdRng::seed_from_u64(3);
+     let locations = (0..1000)
+          .map(|_| Vec2F::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0)))
+          .collect::<Vec<_>>();
+
+     let tree = QuadTree::new(&locations, 8);
+
+     // every location is in exactly one leaf
+     let mut counted = vec![0; locations.len()];
+     for node in tree.nodes.iter().filter(|n| n.children.is_empty()) {
+          for &i in &tree.indices[node.start..node.end] {
+               counted[i] += 1;
+          }
+     }
+     assert!(counted.iter().all(|c| *c == 1));
+
+     let mut neighbors = Vec::new();
+     for point in locations.iter().take(50) {
+          tree.find_neighbors(&locations, *point, 15.0, &mut neighbors);
+          neighbors.sort_unstable();
+
+          let expected = (0..locations.len())
+               .filter(|i| {
+                    let r = locations[*i] - *point;
+
+                    r.x * r.x + r.y * r.y < 15.0 * 15.0
+               })
+               .collect::<Vec<_>>();
+
+          assert_eq!(neighbors, expected);
+     }
+}